@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pcap_file::pcap::PcapReader;
+
+fuzz_target!(|data: &[u8]| {
+
+    if let Ok(pcap_reader) = PcapReader::new(data) {
+        for packet in pcap_reader {
+            if packet.is_err() {
+                break;
+            }
+        }
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use byteorder::{BigEndian, LittleEndian};
+use libfuzzer_sys::fuzz_target;
+use pcap_file::pcapng::{Block, BlockType, ParsedBlock};
+
+// The first 4 bytes select the block type, the rest is the body
+fuzz_target!(|data: &[u8]| {
+
+    if data.len() < 4 {
+        return;
+    }
+
+    let type_ = BlockType::from(u32::from_le_bytes([data[0], data[1], data[2], data[3]]));
+    let body = &data[4..];
+
+    let _ = ParsedBlock::from_slice::<BigEndian>(type_, body);
+    let _ = ParsedBlock::from_slice::<LittleEndian>(type_, body);
+
+    if let Ok((_, block)) = Block::from_slice::<LittleEndian>(body) {
+        let _ = block.parsed();
+    }
+});
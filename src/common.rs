@@ -1,6 +1,10 @@
 use byteorder::ByteOrder;
+use crate::errors::{need, PcapError};
+use core::fmt;
+use core::str::FromStr;
 
 /// Timestamp resolution of the pcap
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum TsResolution {
     MicroSecond,
@@ -8,6 +12,7 @@ pub enum TsResolution {
 }
 
 /// Endianness of the pcap
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Endianness {
     Big,
@@ -142,6 +147,7 @@ pub enum DataLink {
     USBPCAP,
     RTAC_SERIAL,
     BLUETOOTH_LE_LL,
+    WIRESHARK_UPPER_PDU,
     NETLINK,
     BLUETOOTH_LINUX_MONITOR,
     BLUETOOTH_BREDR_BB,
@@ -157,10 +163,216 @@ pub enum DataLink {
     RDS,
     USB_DARWIN,
     SDLC,
+    LORATAP,
+    VSOCK,
+    NORDIC_BLE,
+    DOCSIS31_XRA31,
+    ETHERNET_MPACKET,
+    DISPLAYPORT_AUX,
+    LINUX_SLL2,
 
     Unknown(u32)
 }
 
+impl DataLink {
+
+    /// Returns the length of the link-layer header of the link types where it is fixed,
+    /// e.g. to skip it and reach the network layer packet.
+    ///
+    /// The header of `NULL` and `LOOP` is the 4 bytes address family, `ETHERNET` has a 14 bytes header
+    /// without VLAN tags, `LINUX_SLL` and `LINUX_SLL2` have 16 and 20 bytes headers,
+    /// and `RAW`, `IPV4` and `IPV6` packets start directly with the IP header.
+    ///
+    /// Returns `None` for the link types whose header has a variable length or is not known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::DataLink;
+    ///
+    /// assert_eq!(DataLink::ETHERNET.min_header_len(), Some(14));
+    /// assert_eq!(DataLink::RAW.min_header_len(), Some(0));
+    /// assert_eq!(DataLink::IEEE802_11_RADIOTAP.min_header_len(), None);
+    /// ```
+    pub fn min_header_len(self) -> Option<usize> {
+        match self {
+            DataLink::NULL | DataLink::LOOP => Some(4),
+            DataLink::ETHERNET => Some(14),
+            DataLink::LINUX_SLL => Some(16),
+            DataLink::LINUX_SLL2 => Some(20),
+            DataLink::RAW | DataLink::IPV4 | DataLink::IPV6 => Some(0),
+            _ => None
+        }
+    }
+
+    /// Returns `true` if the `DataLink` is a named link type, `false` for `DataLink::Unknown`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::DataLink;
+    ///
+    /// assert!(DataLink::ETHERNET.is_known());
+    /// assert!(!DataLink::Unknown(0xFFFF).is_known());
+    /// ```
+    pub fn is_known(self) -> bool {
+        !matches!(self, DataLink::Unknown(_))
+    }
+
+    /// Returns the canonical name of the `DataLink`, e.g. `ETHERNET`, or `None` for `DataLink::Unknown`.
+    ///
+    /// It is the name formatted by `Display` and parsed by `FromStr`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::DataLink;
+    ///
+    /// assert_eq!(DataLink::LINUX_SLL.name(), Some("LINUX_SLL"));
+    /// assert_eq!(DataLink::Unknown(0xFFFF).name(), None);
+    /// ```
+    pub const fn name(self) -> Option<&'static str> {
+
+        let name = match self {
+            DataLink::NULL => "NULL",
+            DataLink::ETHERNET => "ETHERNET",
+            DataLink::AX25 => "AX25",
+            DataLink::IEEE802_5 => "IEEE802_5",
+            DataLink::ARCNET_BSD => "ARCNET_BSD",
+            DataLink::SLIP => "SLIP",
+            DataLink::PPP => "PPP",
+            DataLink::FDDI => "FDDI",
+            DataLink::PPP_HDLC => "PPP_HDLC",
+            DataLink::PPP_ETHER => "PPP_ETHER",
+            DataLink::ATM_RFC1483 => "ATM_RFC1483",
+            DataLink::RAW => "RAW",
+            DataLink::C_HDLC => "C_HDLC",
+            DataLink::IEEE802_11 => "IEEE802_11",
+            DataLink::FRELAY => "FRELAY",
+            DataLink::LOOP => "LOOP",
+            DataLink::LINUX_SLL => "LINUX_SLL",
+            DataLink::LTALK => "LTALK",
+            DataLink::PFLOG => "PFLOG",
+            DataLink::IEEE802_11_PRISM => "IEEE802_11_PRISM",
+            DataLink::IP_OVER_FC => "IP_OVER_FC",
+            DataLink::SUNATM => "SUNATM",
+            DataLink::IEEE802_11_RADIOTAP => "IEEE802_11_RADIOTAP",
+            DataLink::ARCNET_LINUX => "ARCNET_LINUX",
+            DataLink::APPLE_IP_OVER_IEEE1394 => "APPLE_IP_OVER_IEEE1394",
+            DataLink::MTP2_WITH_PHDR => "MTP2_WITH_PHDR",
+            DataLink::MTP2 => "MTP2",
+            DataLink::MTP3 => "MTP3",
+            DataLink::SCCP => "SCCP",
+            DataLink::DOCSIS => "DOCSIS",
+            DataLink::LINUX_IRDA => "LINUX_IRDA",
+            DataLink::USER0 => "USER0",
+            DataLink::USER1 => "USER1",
+            DataLink::USER2 => "USER2",
+            DataLink::USER3 => "USER3",
+            DataLink::USER4 => "USER4",
+            DataLink::USER5 => "USER5",
+            DataLink::USER6 => "USER6",
+            DataLink::USER7 => "USER7",
+            DataLink::USER8 => "USER8",
+            DataLink::USER9 => "USER9",
+            DataLink::USER10 => "USER10",
+            DataLink::USER11 => "USER11",
+            DataLink::USER12 => "USER12",
+            DataLink::USER13 => "USER13",
+            DataLink::USER14 => "USER14",
+            DataLink::USER15 => "USER15",
+            DataLink::IEEE802_11_AVS => "IEEE802_11_AVS",
+            DataLink::BACNET_MS_TP => "BACNET_MS_TP",
+            DataLink::PPP_PPPD => "PPP_PPPD",
+            DataLink::GPRS_LLC => "GPRS_LLC",
+            DataLink::GPF_T => "GPF_T",
+            DataLink::GPF_F => "GPF_F",
+            DataLink::LINUX_LAPD => "LINUX_LAPD",
+            DataLink::BLUETOOTH_HCI_H4 => "BLUETOOTH_HCI_H4",
+            DataLink::USB_LINUX => "USB_LINUX",
+            DataLink::PPI => "PPI",
+            DataLink::IEEE802_15_4 => "IEEE802_15_4",
+            DataLink::SITA => "SITA",
+            DataLink::ERF => "ERF",
+            DataLink::BLUETOOTH_HCI_H4_WITH_PHDR => "BLUETOOTH_HCI_H4_WITH_PHDR",
+            DataLink::AX25_KISS => "AX25_KISS",
+            DataLink::LAPD => "LAPD",
+            DataLink::PPP_WITH_DIR => "PPP_WITH_DIR",
+            DataLink::C_HDLC_WITH_DIR => "C_HDLC_WITH_DIR",
+            DataLink::FRELAY_WITH_DIR => "FRELAY_WITH_DIR",
+            DataLink::IPMB_LINUX => "IPMB_LINUX",
+            DataLink::IEEE802_15_4_NONASK_PHY => "IEEE802_15_4_NONASK_PHY",
+            DataLink::USB_LINUX_MMAPPED => "USB_LINUX_MMAPPED",
+            DataLink::FC_2 => "FC_2",
+            DataLink::FC_2_WITH_FRAME_DELIMS => "FC_2_WITH_FRAME_DELIMS",
+            DataLink::IPNET => "IPNET",
+            DataLink::CAN_SOCKETCAN => "CAN_SOCKETCAN",
+            DataLink::IPV4 => "IPV4",
+            DataLink::IPV6 => "IPV6",
+            DataLink::IEEE802_15_4_NOFCS => "IEEE802_15_4_NOFCS",
+            DataLink::DBUS => "DBUS",
+            DataLink::DVB_CI => "DVB_CI",
+            DataLink::MUX27010 => "MUX27010",
+            DataLink::STANAG_5066_D_PDU => "STANAG_5066_D_PDU",
+            DataLink::NFLOG => "NFLOG",
+            DataLink::NETANALYZER => "NETANALYZER",
+            DataLink::NETANALYZER_TRANSPARENT => "NETANALYZER_TRANSPARENT",
+            DataLink::IPOIB => "IPOIB",
+            DataLink::MPEG_2_TS => "MPEG_2_TS",
+            DataLink::NG40 => "NG40",
+            DataLink::NFC_LLCP => "NFC_LLCP",
+            DataLink::INFINIBAND => "INFINIBAND",
+            DataLink::SCTP => "SCTP",
+            DataLink::USBPCAP => "USBPCAP",
+            DataLink::RTAC_SERIAL => "RTAC_SERIAL",
+            DataLink::BLUETOOTH_LE_LL => "BLUETOOTH_LE_LL",
+            DataLink::WIRESHARK_UPPER_PDU => "WIRESHARK_UPPER_PDU",
+            DataLink::NETLINK => "NETLINK",
+            DataLink::BLUETOOTH_LINUX_MONITOR => "BLUETOOTH_LINUX_MONITOR",
+            DataLink::BLUETOOTH_BREDR_BB => "BLUETOOTH_BREDR_BB",
+            DataLink::BLUETOOTH_LE_LL_WITH_PHDR => "BLUETOOTH_LE_LL_WITH_PHDR",
+            DataLink::PROFIBUS_DL => "PROFIBUS_DL",
+            DataLink::PKTAP => "PKTAP",
+            DataLink::EPON => "EPON",
+            DataLink::IPMI_HPM_2 => "IPMI_HPM_2",
+            DataLink::ZWAVE_R1_R2 => "ZWAVE_R1_R2",
+            DataLink::ZWAVE_R3 => "ZWAVE_R3",
+            DataLink::WATTSTOPPER_DLM => "WATTSTOPPER_DLM",
+            DataLink::ISO_14443 => "ISO_14443",
+            DataLink::RDS => "RDS",
+            DataLink::USB_DARWIN => "USB_DARWIN",
+            DataLink::SDLC => "SDLC",
+            DataLink::LORATAP => "LORATAP",
+            DataLink::VSOCK => "VSOCK",
+            DataLink::NORDIC_BLE => "NORDIC_BLE",
+            DataLink::DOCSIS31_XRA31 => "DOCSIS31_XRA31",
+            DataLink::ETHERNET_MPACKET => "ETHERNET_MPACKET",
+            DataLink::DISPLAYPORT_AUX => "DISPLAYPORT_AUX",
+            DataLink::LINUX_SLL2 => "LINUX_SLL2",
+            DataLink::Unknown(_) => return None
+        };
+
+        Some(name)
+    }
+
+    /// Returns an iterator over all the named `DataLink` variants, `DataLink::Unknown` excluded.
+    ///
+    /// The code of each variant is given by `u32::from()` and its canonical name by `Display`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::DataLink;
+    ///
+    /// for datalink in DataLink::all_known() {
+    ///     println!("{}: {}", u32::from(datalink), datalink);
+    /// }
+    /// ```
+    pub fn all_known() -> impl Iterator<Item = DataLink> {
+        KNOWN_DATALINKS.iter().copied()
+    }
+}
+
 impl From<u32> for DataLink {
 
     fn from(n: u32) -> DataLink {
@@ -258,6 +470,7 @@ impl From<u32> for DataLink {
             249 => DataLink::USBPCAP,
             250 => DataLink::RTAC_SERIAL,
             251 => DataLink::BLUETOOTH_LE_LL,
+            252 => DataLink::WIRESHARK_UPPER_PDU,
             253 => DataLink::NETLINK,
             254 => DataLink::BLUETOOTH_LINUX_MONITOR,
             255 => DataLink::BLUETOOTH_BREDR_BB,
@@ -273,6 +486,13 @@ impl From<u32> for DataLink {
             265 => DataLink::RDS,
             266 => DataLink::USB_DARWIN,
             268 => DataLink::SDLC,
+            270 => DataLink::LORATAP,
+            271 => DataLink::VSOCK,
+            272 => DataLink::NORDIC_BLE,
+            273 => DataLink::DOCSIS31_XRA31,
+            274 => DataLink::ETHERNET_MPACKET,
+            275 => DataLink::DISPLAYPORT_AUX,
+            276 => DataLink::LINUX_SLL2,
 
             _ => DataLink::Unknown(n)
         }
@@ -377,6 +597,7 @@ impl From<DataLink> for u32 {
             DataLink::USBPCAP => 249,
             DataLink::RTAC_SERIAL => 250,
             DataLink::BLUETOOTH_LE_LL => 251,
+            DataLink::WIRESHARK_UPPER_PDU => 252,
             DataLink::NETLINK => 253,
             DataLink::BLUETOOTH_LINUX_MONITOR => 254,
             DataLink::BLUETOOTH_BREDR_BB => 255,
@@ -392,8 +613,213 @@ impl From<DataLink> for u32 {
             DataLink::RDS => 265,
             DataLink::USB_DARWIN => 266,
             DataLink::SDLC => 268,
+            DataLink::LORATAP => 270,
+            DataLink::VSOCK => 271,
+            DataLink::NORDIC_BLE => 272,
+            DataLink::DOCSIS31_XRA31 => 273,
+            DataLink::ETHERNET_MPACKET => 274,
+            DataLink::DISPLAYPORT_AUX => 275,
+            DataLink::LINUX_SLL2 => 276,
 
             DataLink::Unknown(n) => n
         }
     }
 }
+
+/// Formats the `DataLink` with its canonical name, e.g. `ETHERNET` or `LINUX_SLL`.
+///
+/// Unknown link types are formatted as `Unknown(code)`.
+impl fmt::Display for DataLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "Unknown({})", u32::from(*self))
+        }
+    }
+}
+
+/// Parses a `DataLink` from its canonical name, case-insensitively.
+impl FromStr for DataLink {
+    type Err = PcapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let is_unknown = s.get(..8).is_some_and(|prefix| prefix.eq_ignore_ascii_case("Unknown("));
+        if is_unknown && s.ends_with(')') {
+            let code = s[8..s.len() - 1].parse::<u32>().map_err(|_| PcapError::InvalidField("DataLink: invalid unknown code"))?;
+            return Ok(DataLink::from(code));
+        }
+
+        KNOWN_DATALINKS.iter()
+            .find(|datalink| datalink.name().is_some_and(|name| name.eq_ignore_ascii_case(s)))
+            .copied()
+            .ok_or(PcapError::InvalidField("DataLink: unknown name"))
+    }
+}
+
+/// All the named `DataLink` variants
+const KNOWN_DATALINKS: &[DataLink] = &[
+    DataLink::NULL,
+    DataLink::ETHERNET,
+    DataLink::AX25,
+    DataLink::IEEE802_5,
+    DataLink::ARCNET_BSD,
+    DataLink::SLIP,
+    DataLink::PPP,
+    DataLink::FDDI,
+    DataLink::PPP_HDLC,
+    DataLink::PPP_ETHER,
+    DataLink::ATM_RFC1483,
+    DataLink::RAW,
+    DataLink::C_HDLC,
+    DataLink::IEEE802_11,
+    DataLink::FRELAY,
+    DataLink::LOOP,
+    DataLink::LINUX_SLL,
+    DataLink::LTALK,
+    DataLink::PFLOG,
+    DataLink::IEEE802_11_PRISM,
+    DataLink::IP_OVER_FC,
+    DataLink::SUNATM,
+    DataLink::IEEE802_11_RADIOTAP,
+    DataLink::ARCNET_LINUX,
+    DataLink::APPLE_IP_OVER_IEEE1394,
+    DataLink::MTP2_WITH_PHDR,
+    DataLink::MTP2,
+    DataLink::MTP3,
+    DataLink::SCCP,
+    DataLink::DOCSIS,
+    DataLink::LINUX_IRDA,
+    DataLink::USER0,
+    DataLink::USER1,
+    DataLink::USER2,
+    DataLink::USER3,
+    DataLink::USER4,
+    DataLink::USER5,
+    DataLink::USER6,
+    DataLink::USER7,
+    DataLink::USER8,
+    DataLink::USER9,
+    DataLink::USER10,
+    DataLink::USER11,
+    DataLink::USER12,
+    DataLink::USER13,
+    DataLink::USER14,
+    DataLink::USER15,
+    DataLink::IEEE802_11_AVS,
+    DataLink::BACNET_MS_TP,
+    DataLink::PPP_PPPD,
+    DataLink::GPRS_LLC,
+    DataLink::GPF_T,
+    DataLink::GPF_F,
+    DataLink::LINUX_LAPD,
+    DataLink::BLUETOOTH_HCI_H4,
+    DataLink::USB_LINUX,
+    DataLink::PPI,
+    DataLink::IEEE802_15_4,
+    DataLink::SITA,
+    DataLink::ERF,
+    DataLink::BLUETOOTH_HCI_H4_WITH_PHDR,
+    DataLink::AX25_KISS,
+    DataLink::LAPD,
+    DataLink::PPP_WITH_DIR,
+    DataLink::C_HDLC_WITH_DIR,
+    DataLink::FRELAY_WITH_DIR,
+    DataLink::IPMB_LINUX,
+    DataLink::IEEE802_15_4_NONASK_PHY,
+    DataLink::USB_LINUX_MMAPPED,
+    DataLink::FC_2,
+    DataLink::FC_2_WITH_FRAME_DELIMS,
+    DataLink::IPNET,
+    DataLink::CAN_SOCKETCAN,
+    DataLink::IPV4,
+    DataLink::IPV6,
+    DataLink::IEEE802_15_4_NOFCS,
+    DataLink::DBUS,
+    DataLink::DVB_CI,
+    DataLink::MUX27010,
+    DataLink::STANAG_5066_D_PDU,
+    DataLink::NFLOG,
+    DataLink::NETANALYZER,
+    DataLink::NETANALYZER_TRANSPARENT,
+    DataLink::IPOIB,
+    DataLink::MPEG_2_TS,
+    DataLink::NG40,
+    DataLink::NFC_LLCP,
+    DataLink::INFINIBAND,
+    DataLink::SCTP,
+    DataLink::USBPCAP,
+    DataLink::RTAC_SERIAL,
+    DataLink::BLUETOOTH_LE_LL,
+    DataLink::WIRESHARK_UPPER_PDU,
+    DataLink::NETLINK,
+    DataLink::BLUETOOTH_LINUX_MONITOR,
+    DataLink::BLUETOOTH_BREDR_BB,
+    DataLink::BLUETOOTH_LE_LL_WITH_PHDR,
+    DataLink::PROFIBUS_DL,
+    DataLink::PKTAP,
+    DataLink::EPON,
+    DataLink::IPMI_HPM_2,
+    DataLink::ZWAVE_R1_R2,
+    DataLink::ZWAVE_R3,
+    DataLink::WATTSTOPPER_DLM,
+    DataLink::ISO_14443,
+    DataLink::RDS,
+    DataLink::USB_DARWIN,
+    DataLink::SDLC,
+    DataLink::LORATAP,
+    DataLink::VSOCK,
+    DataLink::NORDIC_BLE,
+    DataLink::DOCSIS31_XRA31,
+    DataLink::ETHERNET_MPACKET,
+    DataLink::DISPLAYPORT_AUX,
+    DataLink::LINUX_SLL2,
+];
+
+/// Reads the integers at the start of a slice and advances the slice past them, like `byteorder::ReadBytesExt`
+/// does on a `&[u8]` but without `std::io`, so that the slice parsers are available without `std`.
+///
+/// A slice shorter than the integer is a `PcapError::IncompleteBuffer`.
+pub(crate) trait ReadSlice {
+    fn read_u8(&mut self) -> Result<u8, PcapError>;
+    fn read_u16<B: ByteOrder>(&mut self) -> Result<u16, PcapError>;
+    fn read_u32<B: ByteOrder>(&mut self) -> Result<u32, PcapError>;
+    fn read_u64<B: ByteOrder>(&mut self) -> Result<u64, PcapError>;
+    fn read_i64<B: ByteOrder>(&mut self) -> Result<i64, PcapError>;
+}
+
+impl ReadSlice for &[u8] {
+
+    fn read_u8(&mut self) -> Result<u8, PcapError> {
+        Ok(take(self, 1)?[0])
+    }
+
+    fn read_u16<B: ByteOrder>(&mut self) -> Result<u16, PcapError> {
+        Ok(B::read_u16(take(self, 2)?))
+    }
+
+    fn read_u32<B: ByteOrder>(&mut self) -> Result<u32, PcapError> {
+        Ok(B::read_u32(take(self, 4)?))
+    }
+
+    fn read_u64<B: ByteOrder>(&mut self) -> Result<u64, PcapError> {
+        Ok(B::read_u64(take(self, 8)?))
+    }
+
+    fn read_i64<B: ByteOrder>(&mut self) -> Result<i64, PcapError> {
+        Ok(B::read_i64(take(self, 8)?))
+    }
+}
+
+/// Splits the first `len` bytes off the slice
+fn take<'a>(slice: &mut &'a [u8], len: usize) -> Result<&'a [u8], PcapError> {
+
+    if slice.len() < len {
+        return Err(need(slice.len(), len));
+    }
+
+    let (bytes, rem) = slice.split_at(len);
+    *slice = rem;
+
+    Ok(bytes)
+}
@@ -1,39 +1,115 @@
+#[cfg(feature = "std")]
 use thiserror::Error;
+use crate::prelude::*;
 
+#[cfg(feature = "std")]
 pub(crate) type ResultParsing<T> = Result<T, PcapError>;
 
-#[derive(Error, Debug)]
+/// Errors of the crate.
+///
+/// With the `std` feature it implements `std::error::Error`: the underlying IO and UTF-8 errors, as well as the error
+/// wrapped by `AtOffset`, are available through `source()`. Without it, the error only implements `Debug`.
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Debug)]
 pub enum PcapError {
 
-    #[error("Need at least {0} more bytes")]
+    /// The buffer is too small, at least this number of bytes is needed to parse it
+    #[cfg_attr(feature = "std", error("Need at least {0} more bytes"))]
     IncompleteBuffer(usize),
 
+    /// Error of the underlying reader or writer, only with the `std` feature
+    #[cfg(feature = "std")]
     #[error("Error reading bytes")]
     IoError(#[source] std::io::Error),
 
-    #[error("Invalid field value: {0}")]
+    /// A field has an invalid value, the message describes the field
+    #[cfg_attr(feature = "std", error("Invalid field value: {0}"))]
     InvalidField(&'static str),
 
-    #[error("UTF8 error")]
-    Utf8Error(#[source] std::str::Utf8Error),
+    /// An option has an invalid value, `code` is the code of the option and the message describes the problem
+    #[cfg_attr(feature = "std", error("Invalid option {code}: {reason}"))]
+    InvalidOption {
+        code: u16,
+        reason: &'static str
+    },
 
-    #[error("UTF8 error")]
-    FromUtf8Error(#[source] std::string::FromUtf8Error),
+    /// A string field is not valid UTF-8
+    #[cfg_attr(feature = "std", error("UTF8 error"))]
+    Utf8Error(#[cfg_attr(feature = "std", source)] core::str::Utf8Error),
+
+    /// A string field is not valid UTF-8
+    #[cfg_attr(feature = "std", error("UTF8 error"))]
+    FromUtf8Error(#[cfg_attr(feature = "std", source)] std::string::FromUtf8Error),
+
+    /// Error of the block or packet starting at `offset` bytes from the start of the stream
+    #[cfg_attr(feature = "std", error("{source} at offset {offset}"))]
+    AtOffset {
+        offset: u64,
+        source: Box<PcapError>
+    },
+
+    /// The captured length of a packet exceeds the snaplen of the file
+    #[cfg_attr(feature = "std", error("Captured length {len} > snaplen {snaplen}"))]
+    SnaplenExceeded {
+        len: u32,
+        snaplen: u32
+    },
+
+    /// The leading and trailing lengths of a block are not equal
+    #[cfg_attr(feature = "std", error("Block length mismatch: leading length {start} != trailing length {end}"))]
+    BlockLengthMismatch {
+        start: u32,
+        end: u32
+    },
+
+    /// The declared length of a block is greater than the maximum accepted by the reader
+    #[cfg_attr(feature = "std", error("Block too large: length {len} > maximum {max}"))]
+    BlockTooLarge {
+        len: u32,
+        max: u32
+    },
+
+    /// The major version of a SectionHeaderBlock is not supported, only the version 1 is
+    #[cfg_attr(feature = "std", error("Unsupported version {major}.{minor}"))]
+    UnsupportedVersion {
+        major: u16,
+        minor: u16
+    },
+}
+
+impl PcapError {
+
+    /// Wraps the error with the offset, in bytes from the start of the stream, of the block or packet which failed.
+    #[cfg(feature = "std")]
+    pub(crate) fn at_offset(self, offset: u64) -> Self {
+        PcapError::AtOffset {
+            offset,
+            source: Box::new(self)
+        }
+    }
+}
+
+/// Returns the `IncompleteBuffer` error of a buffer of `have` bytes when `want` bytes are needed.
+///
+/// The subtraction saturates, so a caller which didn't check `have < want` can't report a wrapped length.
+pub(crate) fn need(have: usize, want: usize) -> PcapError {
+    PcapError::IncompleteBuffer(want.saturating_sub(have))
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for PcapError {
     fn from(err: std::io::Error) -> Self {
         PcapError::IoError(err)
     }
 }
 
-impl From< std::str::Utf8Error> for PcapError {
-    fn from(err: std::str::Utf8Error) -> Self {
+impl From<core::str::Utf8Error> for PcapError {
+    fn from(err: core::str::Utf8Error) -> Self {
         PcapError::Utf8Error(err)
     }
 }
 
-impl From< std::string::FromUtf8Error> for PcapError {
+impl From<std::string::FromUtf8Error> for PcapError {
     fn from(err: std::string::FromUtf8Error) -> Self {
         PcapError::FromUtf8Error(err)
     }
@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use flate2::bufread::MultiGzDecoder;
+use crate::errors::PcapError;
+use crate::pcap::PcapReader;
+use crate::pcapng::PcapNgReader;
+
+/// Magic number at the start of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reader which transparently decompresses a gzip stream.
+///
+/// The gzip magic number is sniffed at the start of the stream:
+/// if it is missing the stream is read as is, so compressed and plain files can be read through the same reader.
+///
+/// Only available with the `flate2` feature.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::MaybeGzReader;
+/// use pcap_file::pcapng::PcapNgReader;
+///
+/// let file_in = File::open("test.pcapng.gz").expect("Error opening file");
+/// let pcapng_reader = PcapNgReader::new(MaybeGzReader::new(file_in).unwrap()).unwrap();
+/// ```
+pub struct MaybeGzReader<R: Read> {
+    inner: Inner<R>
+}
+
+enum Inner<R: Read> {
+    Plain(BufReader<R>),
+    Gzip(MultiGzDecoder<BufReader<R>>)
+}
+
+impl<R: Read> MaybeGzReader<R> {
+
+    /// Creates a new `MaybeGzReader`, sniffing the gzip magic number of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the reader can't be read from.
+    pub fn new(reader: R) -> Result<MaybeGzReader<R>, PcapError> {
+
+        let mut reader = BufReader::new(reader);
+
+        let inner = if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Inner::Gzip(MultiGzDecoder::new(reader))
+        }
+        else {
+            Inner::Plain(reader)
+        };
+
+        Ok(MaybeGzReader { inner })
+    }
+
+    /// Returns true if the stream is gzip compressed
+    pub fn is_gzip(&self) -> bool {
+        matches!(self.inner, Inner::Gzip(_))
+    }
+
+    /// Consumes the `MaybeGzReader`, returning the wrapped reader.
+    pub fn into_inner(self) -> R {
+        match self.inner {
+            Inner::Plain(reader) => reader.into_inner(),
+            Inner::Gzip(decoder) => decoder.into_inner().into_inner()
+        }
+    }
+}
+
+impl<R: Read> Read for MaybeGzReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.read(buf),
+            Inner::Gzip(decoder) => decoder.read(buf)
+        }
+    }
+}
+
+impl PcapReader<MaybeGzReader<File>> {
+
+    /// Opens a Pcap file, which may be gzip compressed (e.g. `test.pcap.gz`).
+    ///
+    /// Only available with the `flate2` feature.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened or if it is not a valid Pcap file.
+    pub fn from_gz_path<P: AsRef<Path>>(path: P) -> Result<Self, PcapError> {
+        PcapReader::new(MaybeGzReader::new(File::open(path)?)?)
+    }
+}
+
+impl PcapNgReader<MaybeGzReader<File>> {
+
+    /// Opens a PcapNg file, which may be gzip compressed (e.g. `test.pcapng.gz`).
+    ///
+    /// Only available with the `flate2` feature.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened or if it doesn't start with a valid SectionHeaderBlock.
+    pub fn from_gz_path<P: AsRef<Path>>(path: P) -> Result<Self, PcapError> {
+        PcapNgReader::new(MaybeGzReader::new(File::open(path)?)?)
+    }
+}
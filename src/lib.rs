@@ -1,13 +1,22 @@
 #![allow(clippy::unreadable_literal)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-//! This crate contains parsers and readers for Pcap and Pcapng files.
-//! It also contains a writer for Pcap files.
+//! This crate contains parsers, readers and writers for Pcap and Pcapng files.
 //!
 //! For Pcap files see
 //! [PcapReader](struct.PcapReader.html), [PcapParser](struct.PcapParser.html) and [PcapWriter](struct.PcapParser.html).
 //!
 //! For PcapNg files see
-//! [PcapNgReader](struct.PcapNgReader.html) and [PcapNgParser](struct.PcapNgParser.html).
+//! [PcapNgReader](struct.PcapNgReader.html), [PcapNgParser](struct.PcapNgParser.html) and [PcapNgWriter](struct.PcapNgWriter.html).
+//!
+//! Without the default `std` feature the crate is `no_std` and needs `alloc`: only the PcapNg blocks and options,
+//! which are parsed from slices, the `DataLink`, `validate_against_linktype()` and the `PcapError` are available.
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc as std;
+
+pub(crate) mod prelude;
 
 pub(crate) mod common;
 pub use common::*;
@@ -15,10 +24,40 @@ pub use common::*;
 pub(crate) mod errors;
 pub use errors::*;
 
+#[cfg(feature = "flate2")]
+pub(crate) mod gzip;
+#[cfg(feature = "flate2")]
+pub use gzip::*;
+
+#[cfg(feature = "memmap2")]
+pub(crate) mod mmap;
+#[cfg(feature = "memmap2")]
+pub use mmap::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod packet_source;
+#[cfg(feature = "std")]
+pub use packet_source::*;
+
+#[cfg(feature = "std")]
 pub mod pcap;
+#[cfg(feature = "std")]
 pub use pcap::{PcapReader, PcapParser, PcapWriter};
 
 pub mod pcapng;
-pub use pcapng::{PcapNgReader, PcapNgParser};
+#[cfg(feature = "std")]
+pub use pcapng::{PcapNgReader, PcapNgParser, PcapNgWriter};
+
+#[cfg(feature = "std")]
+pub mod peek_reader;
+
+#[cfg(feature = "serde")]
+pub(crate) mod serde_impl;
+
+#[cfg(feature = "std")]
+pub(crate) mod stats;
+#[cfg(feature = "std")]
+pub use stats::*;
 
-pub(crate) mod peek_reader;
\ No newline at end of file
+pub(crate) mod validation;
+pub use validation::*;
\ No newline at end of file
@@ -0,0 +1,83 @@
+use std::fs::File;
+use std::ops::Deref;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::errors::PcapError;
+use crate::pcapng::{index, BlockIndexEntry};
+
+/// Read-only memory-mapped capture file.
+///
+/// It dereferences to a `&[u8]` which can be given to the `from_slice` parsers,
+/// so the blocks and packets borrow their data from the mapping without any copy.
+///
+/// Only available with the `memmap2` feature.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pcap_file::MmapFile;
+/// use pcap_file::pcapng::PcapNgParser;
+///
+/// // Safety: test.pcapng is not modified while it is mapped
+/// let file = unsafe { MmapFile::open("test.pcapng") }.expect("Error mapping file");
+///
+/// let (rem, mut pcapng_parser) = PcapNgParser::new(&file).unwrap();
+/// let (rem, block) = pcapng_parser.next_block(rem).unwrap();
+/// ```
+pub struct MmapFile {
+    mmap: Mmap
+}
+
+impl MmapFile {
+
+    /// Maps the file at `path` in memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this process or another, while it is mapped:
+    /// the slices borrowed from the mapping would change under the parsers, which is undefined behavior.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be opened or mapped.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<MmapFile, PcapError> {
+        MmapFile::map(&File::open(path)?)
+    }
+
+    /// Maps an already opened file in memory.
+    ///
+    /// # Safety
+    ///
+    /// See `MmapFile::open`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the file can't be mapped.
+    pub unsafe fn map(file: &File) -> Result<MmapFile, PcapError> {
+        Ok(MmapFile { mmap: Mmap::map(file)? })
+    }
+
+    /// Returns the content of the file
+    pub fn as_slice(&self) -> &[u8] {
+        &self.mmap[..]
+    }
+
+    /// Builds an index of the blocks of the mapped PcapNg file, see `pcapng::index()`.
+    pub fn pcapng_index(&self) -> Result<Vec<BlockIndexEntry>, PcapError> {
+        index(&mut self.as_slice())
+    }
+}
+
+impl Deref for MmapFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for MmapFile {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+use std::time::Duration;
+use derive_into_owned::IntoOwned;
+use crate::{DataLink, PcapError};
+
+/// Packet captured in a Pcap or a PcapNg stream, independently of its format.
+///
+/// The payload can be owned or borrowed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
+pub struct CapturedPacket<'a> {
+
+    /// Absolute capture time of the packet, since the epoch
+    pub timestamp: Duration,
+
+    /// Link type of the interface which captured the packet
+    pub interface_linktype: DataLink,
+
+    /// Original length of the packet on the wire
+    pub orig_len: u32,
+
+    /// Captured data of the packet
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub data: Cow<'a, [u8]>
+}
+
+/// Source of `CapturedPacket`, implemented by `PcapReader` and `PcapNgReader`.
+///
+/// It allows to process the packets of a capture without knowing its format.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::{PacketSource, PcapNgReader, PcapReader};
+///
+/// fn total_len<S: PacketSource>(source: &mut S) -> usize {
+///
+///     let mut len = 0;
+///     while let Some(packet) = source.next_packet() {
+///         len += packet.unwrap().data.len();
+///     }
+///
+///     len
+/// }
+///
+/// let mut pcap_reader = PcapReader::new(File::open("test.pcap").unwrap()).unwrap();
+/// let mut pcapng_reader = PcapNgReader::new(File::open("test.pcapng").unwrap()).unwrap();
+///
+/// assert_eq!(total_len(&mut pcap_reader), total_len(&mut pcapng_reader));
+/// ```
+pub trait PacketSource {
+
+    /// Returns the next packet of the stream, or `None` if there is no more data.
+    fn next_packet(&mut self) -> Option<Result<CapturedPacket<'static>, PcapError>>;
+}
@@ -8,6 +8,7 @@ use crate::{DataLink, Endianness, TsResolution};
 
 
 /// Pcap Global Header
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct PcapHeader {
 
@@ -77,7 +78,7 @@ impl PcapHeader {
     pub fn from_slice(mut slice: &[u8]) -> ResultParsing<(&[u8], PcapHeader)> {
 
         if slice.len() < 24 {
-            return Err(PcapError::IncompleteBuffer(24 - slice.len()))
+            return Err(need(slice.len(), 24))
         }
 
         let header = PcapHeader::from_reader(&mut slice)?;
@@ -2,7 +2,10 @@ use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
 
 use crate::{
     errors::*,
-    TsResolution
+    validate_against_linktype,
+    DataLink,
+    TsResolution,
+    Warning
 };
 
 use std::{
@@ -13,6 +16,7 @@ use std::{
 };
 
 /// Describes a pcap packet header.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
 pub struct PacketHeader {
 
@@ -20,6 +24,9 @@ pub struct PacketHeader {
     pub ts_sec: u32,
 
     /// Nanosecond part of the timestamp
+    ///
+    /// The microsecond timestamps of the streams with a `TsResolution::MicroSecond` magic number
+    /// are converted to nanoseconds when read and back to microseconds when written.
     pub ts_nsec: u32,
 
     /// Number of octets of the packet saved in file
@@ -48,7 +55,8 @@ impl PacketHeader {
         let ts_sec = reader.read_u32::<B>()?;
         let mut ts_nsec = reader.read_u32::<B>()?;
         if ts_resolution == TsResolution::MicroSecond {
-            ts_nsec *= 1000;
+            ts_nsec = ts_nsec.checked_mul(1000)
+                .ok_or(PcapError::InvalidField("PacketHeader ts_usec overflows the nanoseconds"))?;
         }
         let incl_len = reader.read_u32::<B>()?;
         let orig_len = reader.read_u32::<B>()?;
@@ -82,7 +90,7 @@ impl PacketHeader {
 
         //Header len
         if slice.len() < 16 {
-            return Err(PcapError::IncompleteBuffer(16 - slice.len()));
+            return Err(need(slice.len(), 16));
         }
 
         let header = Self::from_reader::<_, B>(&mut slice, ts_resolution)?;
@@ -117,13 +125,15 @@ impl PacketHeader {
 /// Packet with its header and data.
 ///
 /// The payload can be owned or borrowed.
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Packet<'a> {
 
     /// Header of the packet
     pub header: PacketHeader,
 
     /// Payload, owned or borrowed, of the packet
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub data: Cow<'a, [u8]>
 }
 
@@ -185,7 +195,7 @@ impl<'a> Packet<'a> {
         let len = header.incl_len as usize;
 
         if slice.len() < len {
-            return Err(PcapError::IncompleteBuffer(len - slice.len()));
+            return Err(need(slice.len(), len));
         }
 
         let packet = Packet {
@@ -205,4 +215,9 @@ impl<'a> Packet<'a> {
             data: Cow::Owned(self.data.as_ref().to_owned())
         }
     }
+
+    /// Checks the packet against the datalink of its file, see `validate_against_linktype()`.
+    pub fn validate_against_linktype(&self, datalink: DataLink) -> Vec<Warning> {
+        validate_against_linktype(datalink, &self.data, self.header.orig_len)
+    }
 }
@@ -4,7 +4,8 @@ use crate::{
     Endianness,
     errors::*,
     pcap::Packet,
-    pcap::PcapHeader
+    pcap::PcapHeader,
+    TsResolution
 };
 
 
@@ -62,6 +63,13 @@ impl PcapParser {
         Ok((slice, parser))
     }
 
+    /// Returns the timestamp resolution of the stream, given by the magic number of the global header.
+    ///
+    /// The fractional part of the timestamps is always converted to nanoseconds when parsing, whatever the resolution.
+    pub fn timestamp_resolution(&self) -> TsResolution {
+        self.header.ts_resolution()
+    }
+
     /// Returns the next packet and the remainder.
     pub fn next_packet<'a>(&self, slice: &'a[u8]) -> ResultParsing<(&'a [u8], Packet<'a>)> {
 
@@ -1,11 +1,14 @@
 use byteorder::{BigEndian, LittleEndian};
 
 use crate::{
+    CapturedPacket,
     Endianness,
     errors::*,
+    PacketSource,
     pcap::Packet,
     pcap::PcapHeader,
-    peek_reader::PeekReader
+    peek_reader::PeekReader,
+    TsResolution
 };
 
 use std::io::Read;
@@ -15,6 +18,8 @@ use std::io::Read;
 ///
 /// It implements the Iterator trait in order to read one packet at a time
 ///
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the packet which failed.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -37,7 +42,8 @@ use std::io::Read;
 pub struct PcapReader<T: Read> {
 
     pub header: PcapHeader,
-    reader: PeekReader<T>
+    reader: PeekReader<T>,
+    offset: u64
 }
 
 impl <T:Read> PcapReader<T>{
@@ -64,54 +70,94 @@ impl <T:Read> PcapReader<T>{
         Ok(
             PcapReader {
 
-                header : PcapHeader::from_reader(&mut reader)?,
-                reader : PeekReader::new(reader)
+                header : PcapHeader::from_reader(&mut reader).map_err(|err| err.at_offset(0))?,
+                reader : PeekReader::new(reader),
+                offset : 24
             }
         )
     }
 
     /// Consumes the `PcapReader`, returning the wrapped reader.
     pub fn into_reader(self) -> T{
-        self.reader.inner
+        self.reader.into_inner()
     }
 
     /// Gets a reference to the underlying reader.
     ///
     /// It is not advised to directly read from the underlying reader.
     pub fn get_ref(&self) -> &T{
-        &self.reader.inner
+        self.reader.get_ref()
     }
 
     /// Gets a mutable reference to the underlying reader.
     ///
     /// It is not advised to directly read from the underlying reader.
     pub fn get_mut(&mut self) -> &mut T{
-        &mut self.reader.inner
+        self.reader.get_mut()
     }
-}
-
-impl <T:Read> Iterator for PcapReader<T> {
 
-    type Item = ResultParsing<Packet<'static>>;
+    /// Returns the timestamp resolution of the stream, given by the magic number of the global header.
+    ///
+    /// The fractional part of the timestamps is always converted to nanoseconds when reading, whatever the resolution.
+    pub fn timestamp_resolution(&self) -> TsResolution {
+        self.header.ts_resolution()
+    }
 
-    fn next(&mut self) -> Option<ResultParsing<Packet<'static>>> {
+    /// Returns the next packet of the stream, or `None` if there is no more data.
+    ///
+    /// The timestamp is always converted to nanoseconds, see `PacketHeader::ts_nsec`.
+    pub fn read_packet(&mut self) -> Option<ResultParsing<Packet<'static>>> {
 
         match self.reader.is_empty() {
             Ok(is_empty) if is_empty => {
                 return None;
             },
-            Err(err) => return Some(Err(err.into())),
+            Err(err) => return Some(Err(PcapError::from(err).at_offset(self.offset))),
             _ => {}
         }
 
         let ts_resolution = self.header.ts_resolution();
 
+        let packet = match self.header.endianness() {
+            Endianness::Big => Packet::from_reader::<_, BigEndian>(&mut self.reader, ts_resolution),
+            Endianness::Little => Packet::from_reader::<_, LittleEndian>(&mut self.reader, ts_resolution)
+        };
+
         Some(
-            match self.header.endianness() {
-                Endianness::Big => Packet::from_reader::<_, BigEndian>(&mut self.reader, ts_resolution),
-                Endianness::Little => Packet::from_reader::<_, LittleEndian>(&mut self.reader, ts_resolution)
+            match packet {
+                Ok(packet) => {
+                    self.offset += 16 + packet.data.len() as u64;
+                    Ok(packet)
+                },
+                Err(err) => Err(err.at_offset(self.offset))
             }
         )
     }
+}
+
+/// The timestamps are read with the resolution of the global header, the link type is the one of the global header.
+impl<T: Read> PacketSource for PcapReader<T> {
+
+    fn next_packet(&mut self) -> Option<ResultParsing<CapturedPacket<'static>>> {
+
+        let datalink = self.header.datalink;
+
+        self.read_packet().map(|packet| packet.map(|packet| {
+            CapturedPacket {
+                timestamp: packet.header.timestamp(),
+                interface_linktype: datalink,
+                orig_len: packet.header.orig_len,
+                data: packet.data
+            }
+        }))
+    }
+}
 
+impl <T:Read> Iterator for PcapReader<T> {
+
+    type Item = ResultParsing<Packet<'static>>;
+
+    fn next(&mut self) -> Option<ResultParsing<Packet<'static>>> {
+        self.read_packet()
+    }
 }
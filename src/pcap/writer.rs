@@ -2,6 +2,7 @@ use byteorder::{ByteOrder, BigEndian, LittleEndian, NativeEndian};
 
 use crate::{
     Endianness,
+    TsResolution,
     errors::*,
     pcap::PcapHeader,
     pcap::{Packet, PacketHeader}
@@ -79,6 +80,30 @@ impl<W: Write> PcapWriter<W> {
     /// let mut pcap_writer = PcapWriter::new(file_out);
     /// ```
     pub fn new(writer: W) -> ResultParsing<PcapWriter<W>> {
+        PcapWriter::with_ts_resolution(TsResolution::MicroSecond, writer)
+    }
+
+    /// Creates a new `PcapWriter` from an existing writer with the chosen timestamp resolution.
+    ///
+    /// It writes the same default global pcap header as `new()`, in the native endianness of the CPU,
+    /// but with the magic number corresponding to `ts_resolution`:
+    /// 0xa1b2c3d4 for microseconds and 0xa1b23c4d for nanoseconds.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::TsResolution;
+    /// use pcap_file::pcap::PcapWriter;
+    ///
+    /// let file_out = File::create("out.pcap").expect("Error creating file");
+    /// let mut pcap_writer = PcapWriter::with_ts_resolution(TsResolution::NanoSecond, file_out);
+    /// ```
+    pub fn with_ts_resolution(ts_resolution: TsResolution, writer: W) -> ResultParsing<PcapWriter<W>> {
 
         let tmp = NativeEndian::read_u16(&[0x42, 0x00]);
 
@@ -89,6 +114,7 @@ impl<W: Write> PcapWriter<W> {
         };
 
         let mut header = PcapHeader::default();
+        header.set_ts_resolution(ts_resolution);
         header.set_endianness(endianness);
         PcapWriter::with_header(header, writer)
     }
@@ -187,6 +213,11 @@ impl<W: Write> PcapWriter<W> {
 
     /// Writes a `Packet`.
     ///
+    /// # Errors
+    ///
+    /// Return `PcapError::SnaplenExceeded` if the data of the packet is longer than the snaplen of the header,
+    /// unless the snaplen is 0 which means no limit, or an error if the writer can't be written to.
+    ///
     /// # Examples
     /// ```rust,no_run
     /// use std::fs::File;
@@ -205,6 +236,10 @@ impl<W: Write> PcapWriter<W> {
     /// ```
     pub fn write_packet(&mut self, packet: &Packet) -> ResultParsing<()> {
 
+        if self.header.snaplen != 0 && packet.data.len() > self.header.snaplen as usize {
+            return Err(PcapError::SnaplenExceeded { len: packet.data.len() as u32, snaplen: self.header.snaplen });
+        }
+
         let ts_resolution = self.header.ts_resolution();
 
         match self.header.endianness() {
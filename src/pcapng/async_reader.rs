@@ -0,0 +1,211 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use crate::errors::PcapError;
+use crate::Endianness;
+use crate::pcapng::options::Utf8Mode;
+use crate::pcapng::reader::update_section;
+use crate::pcapng::{Block, BlockType, EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, PcapNgReader, SectionHeaderBlock};
+
+/// Wraps an asynchronous reader and uses it to read a PcapNg formated stream.
+///
+/// Each block is read as a whole in memory then decoded with the same code as the synchronous `PcapNgReader`.
+/// Blocks longer than `max_block_len` are rejected with a `PcapError::BlockTooLarge` before their body is read.
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the block which failed.
+///
+/// Only available with the `tokio` feature.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pcap_file::pcapng::AsyncPcapNgReader;
+/// use tokio::io::AsyncRead;
+///
+/// // The stream can be a tokio::net::TcpStream receiving a capture from a remote sensor
+/// async fn read_capture<R: AsyncRead + Unpin>(stream: R) {
+///     let mut pcapng_reader = AsyncPcapNgReader::new(stream).await.unwrap();
+///
+///     while let Some(block) = pcapng_reader.next_block().await {
+///
+///         //Check if there is no error
+///         let block = block.unwrap();
+///
+///         //Parse block content
+///         let parsed_block = block.parsed().unwrap();
+///
+///         //Do something
+///     }
+/// }
+/// ```
+pub struct AsyncPcapNgReader<R: AsyncRead + Unpin> {
+    reader: R,
+    section: SectionHeaderBlock<'static>,
+    interfaces: Vec<InterfaceDescriptionBlock<'static>>,
+    offset: u64,
+    max_block_len: u32
+}
+
+impl<R: AsyncRead + Unpin> AsyncPcapNgReader<R> {
+
+    /// Creates a new `AsyncPcapNgReader` from a reader.
+    /// Reads the first block which must be a valid SectionHeaderBlock
+    ///
+    /// The blocks are limited to `PcapNgReader::DEFAULT_MAX_BLOCK_LEN` bytes.
+    pub async fn new(reader: R) -> Result<AsyncPcapNgReader<R>, PcapError> {
+        AsyncPcapNgReader::with_max_block_len(PcapNgReader::<&[u8]>::DEFAULT_MAX_BLOCK_LEN, reader).await
+    }
+
+    /// Creates a new `AsyncPcapNgReader` from a reader which rejects the blocks longer than `max_block_len` bytes,
+    /// framing included.
+    /// Reads the first block which must be a valid SectionHeaderBlock
+    pub async fn with_max_block_len(max_block_len: u32, mut reader: R) -> Result<AsyncPcapNgReader<R>, PcapError> {
+
+        let block = read_block(&mut reader, Endianness::Big, max_block_len).await
+            .and_then(|block| block.ok_or(PcapError::InvalidField("SectionHeader missing")))
+            .map_err(|err| err.at_offset(0))?;
+
+        let section = match block.parsed_with(Utf8Mode::Lossy).map_err(|err| err.at_offset(0))? {
+            ParsedBlock::SectionHeader(section) => section.into_owned(),
+            _ => return Err(PcapError::InvalidField("SectionHeader missing").at_offset(0))
+        };
+
+        Ok(
+            AsyncPcapNgReader {
+                reader,
+                section,
+                interfaces: vec![],
+                offset: block.initial_len as u64,
+                max_block_len
+            }
+        )
+    }
+
+    /// Returns the next block of the stream, or `None` if there is no more data.
+    ///
+    /// A SectionHeaderBlock starts a new section: its endianness is used for the following blocks
+    /// and the current interfaces are cleared.
+    pub async fn next_block(&mut self) -> Option<Result<Block<'static>, PcapError>> {
+
+        let offset = self.offset;
+
+        let block = match read_block(&mut self.reader, self.section.endianness(), self.max_block_len).await {
+            Ok(Some(block)) => block,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err.at_offset(offset)))
+        };
+        self.offset += block.initial_len as u64;
+
+        if let Err(err) = update_section(&mut self.section, &mut self.interfaces, &block) {
+            return Some(Err(err.at_offset(offset)));
+        }
+
+        Some(Ok(block))
+    }
+
+    /// Returns the maximum length of a block accepted by the reader
+    pub fn max_block_len(&self) -> u32 {
+        self.max_block_len
+    }
+
+    /// Returns the current SectionHeaderBlock
+    pub fn section(&self) -> &SectionHeaderBlock<'static> {
+        &self.section
+    }
+
+    /// Returns the byte order of the current section, given by the magic number of its SectionHeaderBlock.
+    ///
+    /// It is also the byte order of the blocks of the section, see `Block::endianness()`.
+    pub fn current_endianness(&self) -> Endianness {
+        self.section.endianness()
+    }
+
+    /// Returns the current interfaces
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
+        &self.interfaces[..]
+    }
+
+    /// Returns the InterfaceDescriptionBlock corresponding to the given packet
+    ///
+    /// Its linktype and timestamp resolution are the ones of the packet.
+    pub fn packet_interface(&self, packet: &EnhancedPacketBlock) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interface(packet.interface_id)
+    }
+
+    /// Returns the InterfaceDescriptionBlock of the current section whose id is `interface_id`,
+    /// e.g. the `interface_id` of a PacketBlock or an InterfaceStatisticsBlock.
+    pub fn interface(&self, interface_id: u32) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interfaces.get(interface_id as usize)
+    }
+
+    /// Consumes the `AsyncPcapNgReader`, returning the wrapped reader.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// It is not advised to directly read from the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+}
+
+/// Reads a whole block in memory and decodes it with `Block::from_slice`.
+///
+/// Returns `None` if the reader is already at the end of the stream.
+async fn read_block<R: AsyncRead + Unpin>(reader: &mut R, endianness: Endianness, max_block_len: u32) -> Result<Option<Block<'static>>, PcapError> {
+
+    let mut header = [0_u8; 12];
+
+    // Detect the end of the stream on the first byte of the block
+    if reader.read(&mut header[..1]).await? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut header[1..8]).await?;
+
+    // The magic number of a SectionHeaderBlock defines the endianness of its section
+    let mut header_len = 8;
+    let mut endianness = endianness;
+    if BigEndian::read_u32(&header[..4]) == u32::from(BlockType::SectionHeader) {
+
+        reader.read_exact(&mut header[8..12]).await?;
+        header_len = 12;
+
+        endianness = match BigEndian::read_u32(&header[8..12]) {
+            0x1A2B3C4D => Endianness::Big,
+            0x4D3C2B1A => Endianness::Little,
+            _ => return Err(PcapError::InvalidField("SectionHeaderBlock: invalid magic number"))
+        };
+    }
+
+    let len = match endianness {
+        Endianness::Big => BigEndian::read_u32(&header[4..8]),
+        Endianness::Little => LittleEndian::read_u32(&header[4..8])
+    };
+
+    if (len % 4) != 0 {
+        return Err(PcapError::InvalidField("Block: (initial_len % 4) != 0"));
+    }
+
+    if len < 12 {
+        return Err(PcapError::InvalidField("Block: initial_len < 12"));
+    }
+
+    if len > max_block_len {
+        return Err(PcapError::BlockTooLarge { len, max: max_block_len });
+    }
+
+    let mut data = header[..header_len].to_vec();
+    let remaining = len as u64 - header_len as u64;
+    if (&mut *reader).take(remaining).read_to_end(&mut data).await? as u64 != remaining {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let (_, block) = Block::from_slice_endian(&data, endianness)?;
+
+    Ok(Some(block.into_owned()))
+}
@@ -1,10 +1,17 @@
-use byteorder::{ByteOrder, LittleEndian, BigEndian, ReadBytesExt};
-use std::io::Read;
-use crate::errors::PcapError;
+use byteorder::{ByteOrder, LittleEndian, BigEndian};
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+use crate::prelude::*;
+use crate::errors::{need, PcapError};
+use crate::common::ReadSlice;
 use std::borrow::Cow;
+#[cfg(feature = "std")]
 use byteorder::WriteBytesExt;
-use crate::pcapng::blocks::{SectionHeaderBlock, InterfaceDescriptionBlock, EnhancedPacketBlock, SimplePacketBlock, NameResolutionBlock, InterfaceStatisticsBlock, SystemdJournalExportBlock};
-use crate::pcapng::PacketBlock;
+use crate::pcapng::blocks::{SectionHeaderBlock, InterfaceDescriptionBlock, EnhancedPacketBlock, SimplePacketBlock, NameResolutionBlock, InterfaceStatisticsBlock, SystemdJournalExportBlock, DecryptionSecretsBlock, CustomBlock};
+use crate::pcapng::{LazyEnhancedPacketBlock, PacketBlock};
+use crate::pcapng::options::{opt_len, opts_from_slice, str_from_slice, LengthMode, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::opt_to_writer;
 use crate::Endianness;
 use derive_into_owned::IntoOwned;
 
@@ -21,11 +28,15 @@ use derive_into_owned::IntoOwned;
 //  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 //  |                      Block Total Length                       |
 //  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
-/// PcapNg Block
-#[derive(Clone, Debug)]
+/// PcapNg Block: the framing common to all the blocks and the raw body.
+///
+/// Use `parsed()` to decode the body according to the block type.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Block<'a> {
     pub type_: BlockType,
     pub initial_len: u32,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub body: Cow<'a, [u8]>,
     pub trailer_len: u32,
     endianness: Endianness
@@ -34,7 +45,75 @@ pub struct Block<'a> {
 impl<'a> Block<'a> {
 
     /// Create an "owned" `Block` from a reader
-    pub(crate) fn from_reader<R:Read, B: ByteOrder>(reader: &mut R) -> Result<Block<'static>, PcapError> {
+    ///
+    /// `B` is the byte order of the current section, it is ignored if the block is a SectionHeaderBlock
+    /// whose magic number defines the byte order of its own section.
+    /// The leading and trailing block lengths are checked to be equal.
+    ///
+    /// Only the block is read: the memory used is the size of its body, which is at most `u32::MAX - 12` bytes.
+    /// Use `PcapNgReader::with_max_block_len` to read untrusted streams with a lower bound.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R:Read, B: ByteOrder>(reader: &mut R) -> Result<Block<'static>, PcapError> {
+        Block::from_reader_max::<_, B>(reader, u32::MAX)
+    }
+
+    /// Same as `from_reader` but returns `PcapError::BlockTooLarge` if the declared length of the block
+    /// is greater than `max_len`, before reading its body.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_reader_max<R:Read, B: ByteOrder>(reader: &mut R, max_len: u32) -> Result<Block<'static>, PcapError> {
+
+        let mut body = Vec::new();
+        let (type_, initial_len, trailer_len, endianness) = Block::read_framed::<_, B>(reader, max_len, &mut body)?;
+
+        Ok(
+            Block {
+                type_,
+                initial_len,
+                body: Cow::Owned(body),
+                trailer_len,
+                endianness
+            }
+        )
+    }
+
+    /// Same as `from_reader_max` but the body is read in `buffer`, which is cleared first, and borrowed by the block.
+    ///
+    /// The capacity of `buffer` is kept, so reusing it avoids an allocation per block.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_reader_in<R:Read, B: ByteOrder>(reader: &mut R, max_len: u32, buffer: &'a mut Vec<u8>) -> Result<Block<'a>, PcapError> {
+
+        buffer.clear();
+        let (type_, initial_len, trailer_len, endianness) = Block::read_framed::<_, B>(reader, max_len, buffer)?;
+
+        Ok(
+            Block {
+                type_,
+                initial_len,
+                body: Cow::Borrowed(&buffer[..]),
+                trailer_len,
+                endianness
+            }
+        )
+    }
+
+    /// Creates a block borrowing `body`, whose framing has already been read.
+    #[cfg(feature = "std")]
+    pub(crate) fn borrowed(type_: BlockType, initial_len: u32, body: &'a [u8], trailer_len: u32, endianness: Endianness) -> Block<'a> {
+        Block {
+            type_,
+            initial_len,
+            body: Cow::Borrowed(body),
+            trailer_len,
+            endianness
+        }
+    }
+
+    /// Reads a whole block, appends its body to `body` and returns its type, its lengths and its endianness.
+    #[cfg(feature = "std")]
+    fn read_framed<R:Read, B: ByteOrder>(reader: &mut R, max_len: u32, body: &mut Vec<u8>) -> Result<(BlockType, u32, u32, Endianness), PcapError> {
+
+        // Imported here only, the slices of this module are read with `ReadSlice`
+        use byteorder::ReadBytesExt;
 
         let type_ = reader.read_u32::<B>()?.into();
 
@@ -61,11 +140,18 @@ impl<'a> Block<'a> {
                 return Err(PcapError::InvalidField("Block: initial_len < 12"))
             }
 
+            if initial_len > max_len {
+                return Err(PcapError::BlockTooLarge { len: initial_len, max: max_len });
+            }
+
             let body_len = initial_len - 12;
-            let mut body = vec![0_u8; body_len as usize];
+            if body_len < 4 {
+                return Err(PcapError::InvalidField("SectionHeaderBlock: initial_len < 16"))
+            }
+
             // Rewrite the magic in the body
-            (&mut body[..]).write_u32::<BigEndian>(magic)?;
-            reader.read_exact(&mut body[4..])?;
+            body.write_u32::<BigEndian>(magic)?;
+            read_body(reader, body_len as usize - 4, body)?;
 
             let trailer_len = match endianness {
                 Endianness::Big => reader.read_u32::<BigEndian>()?,
@@ -73,18 +159,10 @@ impl<'a> Block<'a> {
             };
 
             if initial_len != trailer_len {
-                return Err(PcapError::InvalidField("Block initial_length != trailer_length"))
+                return Err(PcapError::BlockLengthMismatch { start: initial_len, end: trailer_len });
             }
 
-            Ok(
-                Block {
-                    type_,
-                    initial_len,
-                    body: Cow::Owned(body),
-                    trailer_len,
-                    endianness
-                }
-            )
+            Ok((type_, initial_len, trailer_len, endianness))
         }
         else {
 
@@ -98,32 +176,54 @@ impl<'a> Block<'a> {
                 return Err(PcapError::InvalidField("Block: initial_len < 12"))
             }
 
+            if initial_len > max_len {
+                return Err(PcapError::BlockTooLarge { len: initial_len, max: max_len });
+            }
+
             let body_len = initial_len - 12;
-            let mut body = vec![0_u8; body_len as usize];
-            reader.read_exact(&mut body[..])?;
+            read_body(reader, body_len as usize, body)?;
 
             let trailer_len = reader.read_u32::<B>()?;
             if initial_len != trailer_len {
-                return Err(PcapError::InvalidField("Block initial_length != trailer_length"))
+                return Err(PcapError::BlockLengthMismatch { start: initial_len, end: trailer_len });
             }
 
-            Ok(
-                Block {
-                    type_,
-                    initial_len,
-                    body: Cow::Owned(body),
-                    trailer_len,
-                    endianness: Endianness::new::<B>()
-                }
-            )
+            Ok((type_, initial_len, trailer_len, Endianness::new::<B>()))
         }
     }
 
     /// Create an "borrowed" `Block` from a slice
-    pub(crate) fn from_slice<B: ByteOrder>(mut slice: &'a[u8]) -> Result<(&[u8], Self), PcapError> {
+    ///
+    /// `B` is the byte order of the current section, it is ignored if the block is a SectionHeaderBlock
+    /// whose magic number defines the byte order of its own section.
+    /// The leading and trailing block lengths are checked to be equal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use byteorder::LittleEndian;
+    /// use pcap_file::pcapng::{Block, BlockType, ParsedBlock};
+    ///
+    /// // A SimplePacketBlock containing 1 byte followed by its padding
+    /// let data = [
+    ///     0x03, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00,
+    ///     0x01, 0x00, 0x00, 0x00, 0xFF, 0x00, 0x00, 0x00,
+    ///     0x14, 0x00, 0x00, 0x00
+    /// ];
+    ///
+    /// let (rem, block) = Block::from_slice::<LittleEndian>(&data[..]).unwrap();
+    /// assert!(rem.is_empty());
+    /// assert_eq!(block.type_, BlockType::SimplePacket);
+    ///
+    /// match block.parsed().unwrap() {
+    ///     ParsedBlock::SimplePacket(packet) => assert_eq!(packet.data(), &[0xFF]),
+    ///     _ => unreachable!()
+    /// }
+    /// ```
+    pub fn from_slice<B: ByteOrder>(mut slice: &'a[u8]) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 12 {
-            return Err(PcapError::IncompleteBuffer(12 - slice.len()));
+            return Err(need(slice.len(), 12));
         }
 
         let type_ = slice.read_u32::<B>()?.into();
@@ -156,7 +256,7 @@ impl<'a> Block<'a> {
 
             //Check if there is enough data for the body and the trailer_len
             if slice.len() < initial_len as usize - 8 {
-                return Err(PcapError::IncompleteBuffer(initial_len as usize - 8 - slice.len()));
+                return Err(need(slice.len(), initial_len as usize - 8));
             }
 
             let body_len = initial_len - 12;
@@ -170,7 +270,7 @@ impl<'a> Block<'a> {
             };
 
             if initial_len != trailer_len {
-                return Err(PcapError::InvalidField("Block initial_length != trailer_length"))
+                return Err(PcapError::BlockLengthMismatch { start: initial_len, end: trailer_len });
             }
 
 
@@ -182,7 +282,7 @@ impl<'a> Block<'a> {
                 endianness
             };
 
-            return Ok((rem, block))
+            Ok((rem, block))
         }
         else {
 
@@ -199,7 +299,7 @@ impl<'a> Block<'a> {
 
             //Check if there is enough data for the body and the trailer_len
             if slice.len() < initial_len as usize - 8 {
-                return Err(PcapError::IncompleteBuffer(initial_len as usize - 8 - slice.len()));
+                return Err(need(slice.len(), initial_len as usize - 8));
             }
 
             let body_len = initial_len - 12;
@@ -210,7 +310,7 @@ impl<'a> Block<'a> {
             let trailer_len = rem.read_u32::<B>()?;
 
             if initial_len != trailer_len {
-                return Err(PcapError::InvalidField("Block initial_length != trailer_length"))
+                return Err(PcapError::BlockLengthMismatch { start: initial_len, end: trailer_len });
             }
 
             let block = Block {
@@ -225,30 +325,234 @@ impl<'a> Block<'a> {
         }
     }
 
-    pub fn parsed(&self) -> Result<ParsedBlock, PcapError> {
+    /// Returns the byte order of the section of this block.
+    ///
+    /// It is needed to decode the body of a block by hand, e.g. a vendor block kept as `ParsedBlock::Unknown`.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Convert a borrowed `Block` to an owned one, copying its body if needed.
+    pub fn into_owned(self) -> Block<'static> {
+        Block {
+            type_: self.type_,
+            initial_len: self.initial_len,
+            body: Cow::Owned(self.body.into_owned()),
+            trailer_len: self.trailer_len,
+            endianness: self.endianness
+        }
+    }
+
+    /// Parses the fixed fields and the packet data of an EnhancedPacketBlock, leaving its options undecoded.
+    ///
+    /// Returns `None` if the block is not an EnhancedPacketBlock.
+    /// It is a cheap way to look at a packet, e.g. to filter the blocks before decoding them with `parsed()`.
+    pub fn lazy_enhanced_packet(&self) -> Option<Result<LazyEnhancedPacketBlock<'_>, PcapError>> {
+
+        if self.type_ != BlockType::EnhancedPacket {
+            return None;
+        }
+
+        let res = match self.endianness {
+            Endianness::Big => LazyEnhancedPacketBlock::from_slice::<BigEndian>(&self.body),
+            Endianness::Little => LazyEnhancedPacketBlock::from_slice::<LittleEndian>(&self.body)
+        };
+
+        Some(res.map(|r| r.1).map_err(overrun))
+    }
+
+    /// Same as `from_slice` but the byte order is chosen at runtime, e.g. after reading the magic number of a section.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::Endianness;
+    /// use pcap_file::pcapng::{Block, BlockType};
+    ///
+    /// let data = [
+    ///     0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x10,
+    ///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10
+    /// ];
+    ///
+    /// let (_, block) = Block::from_slice_endian(&data[..], Endianness::Big).unwrap();
+    /// assert_eq!(block.type_, BlockType::SimplePacket);
+    /// assert_eq!(block.endianness(), Endianness::Big);
+    /// ```
+    pub fn from_slice_endian(slice: &'a[u8], endianness: Endianness) -> Result<(&'a [u8], Self), PcapError> {
+        match endianness {
+            Endianness::Big => Block::from_slice::<BigEndian>(slice),
+            Endianness::Little => Block::from_slice::<LittleEndian>(slice)
+        }
+    }
+
+    /// Parses the body of the block according to its type.
+    ///
+    /// The returned `ParsedBlock` borrows the body of this `Block`.
+    /// An invalid UTF-8 string option is an error, see `parsed_with()`.
+    pub fn parsed(&self) -> Result<ParsedBlock<'_>, PcapError> {
+        self.parsed_with(Utf8Mode::Strict)
+    }
+
+    /// Parses the body of the block according to its type, the string options are decoded according to `mode`.
+    pub fn parsed_with(&self, mode: Utf8Mode) -> Result<ParsedBlock<'_>, PcapError> {
+
+        let res = match self.endianness {
+            Endianness::Big => ParsedBlock::from_slice_with::<BigEndian>(self.type_, &self.body, mode),
+            Endianness::Little => ParsedBlock::from_slice_with::<LittleEndian>(self.type_, &self.body, mode)
+        };
+
+        res.map(|r| r.1).map_err(overrun)
+    }
+
+    /// Returns the code and the raw value of each option of the block, without interpreting them.
+    ///
+    /// The values borrow the body of the block and don't include their padding, the end of options marker
+    /// is not returned. It shows exactly what is stored, e.g. to dump an option whose decoded value looks wrong.
+    /// The blocks without options, e.g. SimplePacketBlocks and unknown blocks, return an empty `Vec`.
+    pub fn raw_options(&self) -> Result<Vec<(u16, &[u8])>, PcapError> {
+
+        let res = match self.endianness {
+            Endianness::Big => raw_options::<BigEndian>(self.type_, &self.body),
+            Endianness::Little => raw_options::<LittleEndian>(self.type_, &self.body)
+        };
 
-        match self.endianness {
-            Endianness::Big => ParsedBlock::from_slice::<BigEndian>(self.type_, &self.body).map(|r| r.1),
-            Endianness::Little => ParsedBlock::from_slice::<LittleEndian>(self.type_, &self.body).map(|r| r.1)
+        return res.map_err(overrun);
+
+        fn raw_options<B: ByteOrder>(type_: BlockType, body: &[u8]) -> Result<Vec<(u16, &[u8])>, PcapError> {
+            match options_offset::<B>(type_, body)? {
+                Some(offset) => opts_from_slice::<B, _, _>(&body[offset..], |value, code, _| Ok((code, value))).map(|(_, opts)| opts),
+                None => Ok(vec![])
+            }
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// Returns the offset of the options in the body of a block of type `type_`, or `None` if this type has no options.
+fn options_offset<B: ByteOrder>(type_: BlockType, body: &[u8]) -> Result<Option<usize>, PcapError> {
+
+    const TOO_SHORT: PcapError = PcapError::InvalidField("Block: body too short for its options");
+
+    let padded = |len: usize| len + (4 - (len % 4)) % 4;
+    let u32_at = |offset: usize| body.get(offset..offset + 4).map(|slice| B::read_u32(slice) as usize).ok_or(TOO_SHORT);
+
+    let offset = match type_ {
+        BlockType::SectionHeader => 16,
+        BlockType::InterfaceDescription => 8,
+        BlockType::InterfaceStatistics => 12,
+        BlockType::EnhancedPacket | BlockType::Packet => 20 + padded(u32_at(12)?),
+        BlockType::DecryptionSecrets => 8 + padded(u32_at(4)?),
+        BlockType::NameResolution => {
+
+            // The records end with an end of records marker
+            let mut offset = 0;
+            loop {
+                let record = body.get(offset..offset + 4).ok_or(TOO_SHORT)?;
+                offset += 4;
+
+                if B::read_u16(&record[..2]) == 0 {
+                    break offset;
+                }
+                offset += padded(B::read_u16(&record[2..]) as usize);
+            }
+        },
+        _ => return Ok(None)
+    };
+
+    if offset > body.len() {
+        return Err(TOO_SHORT);
+    }
+
+    Ok(Some(offset))
+}
+
+/// Length of a written block whose body is `body_len` bytes long: the body is padded to 32 bits
+/// and framed by the block type and the two block lengths.
+pub(crate) fn framed_len(body_len: usize) -> usize {
+    12 + body_len + (4 - (body_len % 4)) % 4
+}
+
+/// Converts the `IncompleteBuffer` error of a block body parser.
+///
+/// The body of a `Block` is always complete, so a field or an option which overruns it is invalid:
+/// more data would only be the next block, whose bytes must not be read as part of this one.
+fn overrun(err: PcapError) -> PcapError {
+    match err {
+        PcapError::IncompleteBuffer(_) => PcapError::InvalidField("Block: a field or an option overruns the block body"),
+        err => err
+    }
+}
+
+/// Appends exactly `len` bytes of `reader` to `body`.
+///
+/// The buffer grows with the data actually read, so a forged block length can't trigger
+/// a huge allocation on its own.
+#[cfg(feature = "std")]
+fn read_body<R: Read>(reader: &mut R, len: usize, body: &mut Vec<u8>) -> Result<(), PcapError> {
+
+    let expected = body.len() + len;
+    reader.take(len as u64).read_to_end(body)?;
+
+    if body.len() != expected {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
+}
+
+/// Type of a PcapNg block, the first field of its framing.
+///
+/// It can be read without decoding the body of the block, e.g. to filter or index the blocks.
+///
+/// Codes of the specification which are not decoded by this crate are kept as `Unknown`:
+/// - `0x00000000` is reserved,
+/// - `0x00000007` (IRIG Timestamp), `0x00000008` (ARINC 429), `0x00000101` to `0x00000108` (Hone Project)
+///   and `0x00000201` to `0x00000209` (Sysdig) are used by other tools,
+/// - `0x0A0D0A00` to `0x0A0D0AFF`, `0x000A0D0A` to `0xFF0A0D0A`, `0x000A0D0D` to `0xFF0A0D0D` and
+///   `0x0D0D0A00` to `0x0D0D0AFF` are reserved to detect the files corrupted by a newline conversion,
+/// - `0x80000000` to `0xFFFFFFFF` (the most significant bit set) are reserved for local use, see `is_local_use()`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum BlockType {
+    /// Section Header Block, `0x0A0D0D0A`
     SectionHeader,
+    /// Interface Description Block, `0x00000001`
     InterfaceDescription,
+    /// Packet Block (obsolete), `0x00000002`
     Packet,
+    /// Simple Packet Block, `0x00000003`
     SimplePacket,
+    /// Name Resolution Block, `0x00000004`
     NameResolution,
+    /// Interface Statistics Block, `0x00000005`
     InterfaceStatistics,
+    /// Enhanced Packet Block, `0x00000006`
     EnhancedPacket,
+    /// systemd Journal Export Block, `0x00000009`
     SystemdJournalExport,
+    /// Decryption Secrets Block, `0x0000000A`
+    DecryptionSecrets,
+    /// Custom block which can be copied, `0x00000BAD`
+    Custom,
+    /// Custom block which must not be copied, `0x40000BAD`
+    CustomNonCopyable,
+    /// Any other code
     Unknown(u32)
 }
 
-impl From<u32> for BlockType {
-    fn from(src: u32) -> Self {
+impl BlockType {
+
+    /// Returns the `BlockType` of a raw block type code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::pcapng::BlockType;
+    ///
+    /// assert_eq!(BlockType::from_u32(0x00000006), BlockType::EnhancedPacket);
+    /// assert_eq!(BlockType::from_u32(0x80000001), BlockType::Unknown(0x80000001));
+    /// assert_eq!(BlockType::SectionHeader.as_u32(), 0x0A0D0D0A);
+    /// ```
+    pub const fn from_u32(src: u32) -> BlockType {
         match src {
             0x0A0D0D0A => BlockType::SectionHeader,
             0x00000001 => BlockType::InterfaceDescription,
@@ -258,13 +562,55 @@ impl From<u32> for BlockType {
             0x00000005 => BlockType::InterfaceStatistics,
             0x00000006 => BlockType::EnhancedPacket,
             0x00000009 => BlockType::SystemdJournalExport,
+            0x0000000A => BlockType::DecryptionSecrets,
+            0x00000BAD => BlockType::Custom,
+            0x40000BAD => BlockType::CustomNonCopyable,
             _ => BlockType::Unknown(src),
         }
     }
+
+    /// Returns the raw code of the block type.
+    pub const fn as_u32(self) -> u32 {
+        match self {
+            BlockType::SectionHeader => 0x0A0D0D0A,
+            BlockType::InterfaceDescription => 0x00000001,
+            BlockType::Packet => 0x00000002,
+            BlockType::SimplePacket => 0x00000003,
+            BlockType::NameResolution => 0x00000004,
+            BlockType::InterfaceStatistics => 0x00000005,
+            BlockType::EnhancedPacket => 0x00000006,
+            BlockType::SystemdJournalExport => 0x00000009,
+            BlockType::DecryptionSecrets => 0x0000000A,
+            BlockType::Custom => 0x00000BAD,
+            BlockType::CustomNonCopyable => 0x40000BAD,
+            BlockType::Unknown(src) => src,
+        }
+    }
+
+    /// Returns true if the code is reserved for local use, i.e. its most significant bit is set.
+    pub const fn is_local_use(self) -> bool {
+        self.as_u32() & 0x80000000 != 0
+    }
 }
 
-/// PcapNg parsed blocks
-#[derive(Clone, Debug, IntoOwned)]
+impl From<u32> for BlockType {
+    fn from(src: u32) -> Self {
+        BlockType::from_u32(src)
+    }
+}
+
+impl From<BlockType> for u32 {
+    fn from(type_: BlockType) -> Self {
+        type_.as_u32()
+    }
+}
+
+/// PcapNg parsed blocks, one variant per block type.
+///
+/// Blocks whose type is not known by the parser are kept as `Unknown` with their raw body,
+/// so they can be written back as is.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum ParsedBlock<'a> {
     SectionHeader(SectionHeaderBlock<'a>),
     InterfaceDescription(InterfaceDescriptionBlock<'a>),
@@ -274,6 +620,8 @@ pub enum ParsedBlock<'a> {
     InterfaceStatistics(InterfaceStatisticsBlock<'a>),
     EnhancedPacket(EnhancedPacketBlock<'a>),
     SystemdJournalExport(SystemdJournalExportBlock<'a>),
+    DecryptionSecrets(DecryptionSecretsBlock<'a>),
+    Custom(CustomBlock<'a>),
     Unknown(UnknownBlock<'a>)
 }
 
@@ -281,19 +629,45 @@ impl<'a> ParsedBlock<'a> {
 
     /// Create a `ParsedBlock` from a slice
     pub fn from_slice<B: ByteOrder>(type_: BlockType, slice: &'a[u8]) -> Result<(&'a [u8], Self), PcapError> {
+        ParsedBlock::from_slice_with::<B>(type_, slice, Utf8Mode::Strict)
+    }
+
+    /// Reads a whole block from a reader, see `Block::from_reader()`, and parses it into an owned `ParsedBlock`.
+    ///
+    /// It is the entry point for the streams which are not read from a slice.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<ParsedBlock<'static>, PcapError> {
+        let block = Block::from_reader::<_, B>(reader)?;
+        Ok(block.parsed()?.into_owned())
+    }
+
+    /// Same as `from_slice` but the byte order is chosen at runtime.
+    pub fn from_slice_endian(type_: BlockType, slice: &'a[u8], endianness: Endianness) -> Result<(&'a [u8], Self), PcapError> {
+        match endianness {
+            Endianness::Big => ParsedBlock::from_slice::<BigEndian>(type_, slice),
+            Endianness::Little => ParsedBlock::from_slice::<LittleEndian>(type_, slice)
+        }
+    }
+
+    /// Create a `ParsedBlock` from a slice, the string options are decoded according to `mode`.
+    ///
+    /// With `Utf8Mode::Lossy` a block whose strings are not valid UTF-8 is still parsed.
+    /// The options of an InterfaceStatisticsBlock are checked with `LengthMode::Lenient`,
+    /// use `InterfaceStatisticsBlock::from_slice_with_lengths()` to check them strictly.
+    pub fn from_slice_with<B: ByteOrder>(type_: BlockType, slice: &'a[u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
 
         match type_ {
 
             BlockType::SectionHeader => {
-                let (rem, block) = SectionHeaderBlock::from_slice(slice)?;
+                let (rem, block) = SectionHeaderBlock::from_slice_with(slice, mode)?;
                 Ok((rem, ParsedBlock::SectionHeader(block)))
             },
             BlockType::InterfaceDescription => {
-                let (rem, block) = InterfaceDescriptionBlock::from_slice::<B>(slice)?;
+                let (rem, block) = InterfaceDescriptionBlock::from_slice_with::<B>(slice, mode)?;
                 Ok((rem, ParsedBlock::InterfaceDescription(block)))
             },
             BlockType::Packet => {
-                let (rem, block) = PacketBlock::from_slice::<B>(slice)?;
+                let (rem, block) = PacketBlock::from_slice_with::<B>(slice, mode)?;
                 Ok((rem, ParsedBlock::Packet(block)))
             },
             BlockType::SimplePacket => {
@@ -301,22 +675,87 @@ impl<'a> ParsedBlock<'a> {
                 Ok((rem, ParsedBlock::SimplePacket(block)))
             },
             BlockType::NameResolution => {
-                let (rem, block) = NameResolutionBlock::from_slice::<B>(slice)?;
+                let (rem, block) = NameResolutionBlock::from_slice_with::<B>(slice, mode)?;
                 Ok((rem, ParsedBlock::NameResolution(block)))
             },
             BlockType::InterfaceStatistics => {
-                let (rem, block) = InterfaceStatisticsBlock::from_slice::<B>(slice)?;
+                let (rem, block) = InterfaceStatisticsBlock::from_slice_with::<B>(slice, mode, LengthMode::Lenient)?;
                 Ok((rem, ParsedBlock::InterfaceStatistics(block)))
             },
             BlockType::EnhancedPacket => {
-                let (rem, block) = EnhancedPacketBlock::from_slice::<B>(slice)?;
+                let (rem, block) = EnhancedPacketBlock::from_slice_with::<B>(slice, mode)?;
                 Ok((rem, ParsedBlock::EnhancedPacket(block)))
             },
             BlockType::SystemdJournalExport => {
                 let (rem, block) = SystemdJournalExportBlock::from_slice::<B>(slice)?;
                 Ok((rem, ParsedBlock::SystemdJournalExport(block)))
+            },
+            BlockType::DecryptionSecrets => {
+                let (rem, block) = DecryptionSecretsBlock::from_slice_with::<B>(slice, mode)?;
+                Ok((rem, ParsedBlock::DecryptionSecrets(block)))
+            },
+            BlockType::Custom | BlockType::CustomNonCopyable => {
+                let (rem, block) = CustomBlock::from_slice::<B>(slice, type_ == BlockType::Custom)?;
+                Ok((rem, ParsedBlock::Custom(block)))
             }
-            _ => Ok((slice, ParsedBlock::Unknown(UnknownBlock::new(type_, slice.len() as u32, slice))))
+            // The whole body is borrowed as is
+            _ => Ok((&slice[slice.len()..], ParsedBlock::Unknown(UnknownBlock::new(type_, slice.len() as u32, slice))))
+        }
+    }
+
+    /// Returns the type of the block.
+    pub fn block_type(&self) -> BlockType {
+        match self {
+            ParsedBlock::SectionHeader(_) => BlockType::SectionHeader,
+            ParsedBlock::InterfaceDescription(_) => BlockType::InterfaceDescription,
+            ParsedBlock::Packet(_) => BlockType::Packet,
+            ParsedBlock::SimplePacket(_) => BlockType::SimplePacket,
+            ParsedBlock::NameResolution(_) => BlockType::NameResolution,
+            ParsedBlock::InterfaceStatistics(_) => BlockType::InterfaceStatistics,
+            ParsedBlock::EnhancedPacket(_) => BlockType::EnhancedPacket,
+            ParsedBlock::SystemdJournalExport(_) => BlockType::SystemdJournalExport,
+            ParsedBlock::DecryptionSecrets(_) => BlockType::DecryptionSecrets,
+            ParsedBlock::Custom(block) if block.copyable => BlockType::Custom,
+            ParsedBlock::Custom(_) => BlockType::CustomNonCopyable,
+            ParsedBlock::Unknown(block) => block.type_
+        }
+    }
+
+    /// Write the body of the block, without the block type and the block lengths.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+        match self {
+            ParsedBlock::SectionHeader(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::InterfaceDescription(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::Packet(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::SimplePacket(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::NameResolution(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::InterfaceStatistics(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::EnhancedPacket(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::SystemdJournalExport(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::DecryptionSecrets(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::Custom(block) => block.write_to::<_, B>(writer),
+            ParsedBlock::Unknown(block) => block.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes written for the block by a `PcapNgWriter`, framing included,
+    /// computed from its fields without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        match self {
+            ParsedBlock::SectionHeader(block) => block.serialized_len(),
+            ParsedBlock::InterfaceDescription(block) => block.serialized_len(),
+            ParsedBlock::Packet(block) => block.serialized_len(),
+            ParsedBlock::SimplePacket(block) => block.serialized_len(),
+            ParsedBlock::NameResolution(block) => block.serialized_len(),
+            ParsedBlock::InterfaceStatistics(block) => block.serialized_len(),
+            ParsedBlock::EnhancedPacket(block) => block.serialized_len(),
+            ParsedBlock::SystemdJournalExport(block) => block.serialized_len(),
+            ParsedBlock::DecryptionSecrets(block) => block.serialized_len(),
+            ParsedBlock::Custom(block) => block.serialized_len(),
+            ParsedBlock::Unknown(block) => block.serialized_len()
         }
     }
 
@@ -335,10 +774,16 @@ impl<'a> ParsedBlock<'a> {
     }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Block whose type is not known by the parser, its body is preserved as is.
+///
+/// When parsed from a slice, the body is borrowed without being copied,
+/// so experimental block types can be decoded by the user with `raw_body()`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct UnknownBlock<'a> {
     pub type_: BlockType,
     pub length: u32,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub value: Cow<'a, [u8]>
 }
 
@@ -350,54 +795,44 @@ impl<'a> UnknownBlock<'a> {
             value: Cow::Borrowed(value)
         }
     }
-}
 
-/// Parse all options in a block
-pub(crate) fn opts_from_slice<'a, B, F, O>(mut slice: &'a [u8], func: F) -> Result<(&'a [u8], Vec<O>), PcapError>
-    where B: ByteOrder,
-          F: Fn(&'a [u8], u16, u16) -> Result<O, PcapError>
-
-{
-    let mut options = vec![];
-
-    // If there is nothing left in the slice, it means that there is no option
-    if slice.is_empty() {
-        return Ok((slice, options))
+    /// Returns the body of the block, between the block lengths, padding included.
+    ///
+    /// Its byte order is the one of the section of the block.
+    pub fn raw_body(&self) -> &[u8] {
+        &self.value
     }
 
-    loop {
-
-        if slice.len() < 4 {
-            return Err(PcapError::InvalidField("Option: slice.len() < 4"));
-        }
-
-        let code = slice.read_u16::<B>()?;
-        let length = slice.read_u16::<B>()? as usize;
-        let pad_len = (4 - (length % 4)) % 4;
-
-        if code == 0 {
-            return Ok((slice, options));
-        }
-
-        if slice.len() < length + pad_len {
-            return Err(PcapError::InvalidField("Option: length + pad.len() > slice.len()"));
-        }
-
-        let mut tmp_slice = &slice[..length];
-        let opt = func(&mut tmp_slice, code, length as u16)?;
-
-        // Jump over the padding
-        slice = &slice[length+pad_len..];
+    /// Write the body of the block as is and returns the number of bytes written
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+        writer.write_all(&self.value)?;
+        Ok(self.value.len())
+    }
 
-        options.push(opt);
+    /// Returns the length of the written block: its body copied as is, padded and framed.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(self.value.len())
     }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Option whose code is not known by the parser, its value is preserved as is
+///
+/// The option is written back with its original code and value, so a block read then written
+/// is identical byte for byte, the padding being zeroed as required by the specification.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct UnknownOption<'a> {
-    code: u16,
-    length: u16,
-    value: Cow<'a, [u8]>
+
+    /// Option code
+    pub code: u16,
+
+    /// Length of the value declared in the option header, padding excluded
+    pub length: u16,
+
+    /// Raw value of the option, without its padding
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub value: Cow<'a, [u8]>
 }
 impl<'a> UnknownOption<'a> {
     pub fn new(code: u16, length: u16, value: &'a[u8]) -> Self {
@@ -407,16 +842,58 @@ impl<'a> UnknownOption<'a> {
             value: Cow::Borrowed(value)
         }
     }
+
+    /// Write the option as is and returns the number of bytes written
+    ///
+    /// The length written is the length of `value`.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+        opt_to_writer::<_, B>(writer, self.code, &self.value)
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header and the padded value.
+    pub fn serialized_len(&self) -> usize {
+        opt_len(self.value.len())
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Custom option containing binary octets, identified by a Private Enterprise Number
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct CustomBinaryOption<'a> {
-    code: u16,
-    pen: u32,
-    value: Cow<'a, [u8]>
+
+    /// Option code: 2989 if the option can be copied, 19373 otherwise
+    pub code: u16,
+
+    /// Private Enterprise Number of the organization owning the option
+    pub pen: u32,
+
+    /// Custom data, without the PEN
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub value: Cow<'a, [u8]>
 }
 impl<'a> CustomBinaryOption<'a> {
+
+    /// Creates a new option, `copyable` selects the code 2989 instead of 19373.
+    pub fn new(pen: u32, value: &'a [u8], copyable: bool) -> Self {
+        CustomBinaryOption {
+            code: if copyable { OPT_CUSTOM_BINARY_COPYABLE } else { OPT_CUSTOM_BINARY_NON_COPYABLE },
+            pen,
+            value: Cow::Borrowed(value)
+        }
+    }
+
+    /// Returns true if the option can be copied to a new file when the block is rewritten.
+    pub fn is_copyable(&self) -> bool {
+        self.code == OPT_CUSTOM_BINARY_COPYABLE
+    }
+
     pub fn from_slice<B: ByteOrder>(code: u16, mut src: &'a [u8]) -> Result<Self, PcapError> {
+
+        if src.len() < 4 {
+            return Err(PcapError::InvalidField("CustomBinaryOption: length < 4"));
+        }
+
         let pen = src.read_u32::<B>()?;
 
         let opt = CustomBinaryOption {
@@ -427,24 +904,87 @@ impl<'a> CustomBinaryOption<'a> {
 
         Ok(opt)
     }
+
+    /// Write the option and returns the number of bytes written
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+        let mut value = Vec::with_capacity(4 + self.value.len());
+        value.write_u32::<B>(self.pen)?;
+        value.extend_from_slice(&self.value);
+
+        opt_to_writer::<_, B>(writer, self.code, &value)
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header, the PEN and the padded custom data.
+    pub fn serialized_len(&self) -> usize {
+        opt_len(4 + self.value.len())
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Custom option containing a UTF-8 string, identified by a Private Enterprise Number
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct CustomUtf8Option<'a> {
-    code: u16,
-    pen: u32,
-    value: Cow<'a, str>
+
+    /// Option code: 2988 if the option can be copied, 19372 otherwise
+    pub code: u16,
+
+    /// Private Enterprise Number of the organization owning the option
+    pub pen: u32,
+
+    /// Custom string, without the PEN
+    pub value: Cow<'a, str>
 }
 impl<'a> CustomUtf8Option<'a> {
-    pub fn from_slice<B: ByteOrder>(code: u16, mut src: &'a [u8]) -> Result<Self, PcapError> {
+
+    /// Creates a new option, `copyable` selects the code 2988 instead of 19372.
+    pub fn new(pen: u32, value: &'a str, copyable: bool) -> Self {
+        CustomUtf8Option {
+            code: if copyable { OPT_CUSTOM_UTF8_COPYABLE } else { OPT_CUSTOM_UTF8_NON_COPYABLE },
+            pen,
+            value: Cow::Borrowed(value)
+        }
+    }
+
+    /// Returns true if the option can be copied to a new file when the block is rewritten.
+    pub fn is_copyable(&self) -> bool {
+        self.code == OPT_CUSTOM_UTF8_COPYABLE
+    }
+
+    pub fn from_slice<B: ByteOrder>(code: u16, src: &'a [u8]) -> Result<Self, PcapError> {
+        Self::from_slice_with::<B>(code, src, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the value is decoded according to `mode`.
+    pub(crate) fn from_slice_with<B: ByteOrder>(code: u16, mut src: &'a [u8], mode: Utf8Mode) -> Result<Self, PcapError> {
+
+        if src.len() < 4 {
+            return Err(PcapError::InvalidField("CustomUtf8Option: length < 4"));
+        }
+
         let pen = src.read_u32::<B>()?;
 
         let opt = CustomUtf8Option {
             code,
             pen,
-            value: Cow::Borrowed(std::str::from_utf8(src)?)
+            value: str_from_slice(src, mode)?
         };
 
         Ok(opt)
     }
+
+    /// Write the option and returns the number of bytes written
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+        let mut value = Vec::with_capacity(4 + self.value.len());
+        value.write_u32::<B>(self.pen)?;
+        value.extend_from_slice(self.value.as_bytes());
+
+        opt_to_writer::<_, B>(writer, self.code, &value)
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header, the PEN and the padded string.
+    pub fn serialized_len(&self) -> usize {
+        opt_len(4 + self.value.len())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+use crate::errors::PcapError;
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::framed_len;
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
+use std::borrow::Cow;
+use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+
+/// The Custom Block (CB) contains vendor specific data, identified by a Private Enterprise Number.
+///
+/// The block type is 0x00000BAD if the block can be copied to a new file when the file is rewritten
+/// and 0x40000BAD otherwise.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
+pub struct CustomBlock<'a> {
+
+    /// Private Enterprise Number of the organization owning the block
+    pub pen: u32,
+
+    /// True if the block type is 0x00000BAD, false if it is 0x40000BAD
+    pub copyable: bool,
+
+    /// Custom data, its padding included because its length is only known by the vendor
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub data: Cow<'a, [u8]>
+}
+
+impl<'a> CustomBlock<'a> {
+
+    /// Reads a whole `CustomBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type. Both the copyable and the non copyable custom blocks are accepted.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<CustomBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::Custom(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("CustomBlock::from_reader: not a CustomBlock"))
+        }
+    }
+
+    pub fn from_slice<B: ByteOrder>(mut slice: &'a [u8], copyable: bool) -> Result<(&'a [u8], Self), PcapError> {
+
+        if slice.len() < 4 {
+            return Err(PcapError::InvalidField("CustomBlock: block length < 4"));
+        }
+
+        let pen = slice.read_u32::<B>()?;
+
+        let block = CustomBlock {
+            pen,
+            copyable,
+            data: Cow::Borrowed(slice)
+        };
+
+        Ok((&[], block))
+    }
+
+    /// Write the body of the block: the PEN and the padded custom data.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let pad_len = (4 - (self.data.len() % 4)) % 4;
+
+        writer.write_u32::<B>(self.pen)?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        Ok(4 + self.data.len() + pad_len)
+    }
+
+    /// Returns the number of bytes written for the block by a `PcapNgWriter`: the framing, the PEN and the padded data.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(4 + self.data.len())
+    }
+
+    /// Convert a borrowed `CustomBlock` to an owned one.
+    pub fn to_owned(&self) -> CustomBlock<'static> {
+        self.clone().into_owned()
+    }
+}
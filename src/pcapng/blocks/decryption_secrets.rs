@@ -0,0 +1,237 @@
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
+use crate::errors::PcapError;
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::{CustomBinaryOption, CustomUtf8Option, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
+use std::borrow::Cow;
+use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+
+/// The Decryption Secrets Block (DSB) stores the keys needed to decrypt the captured traffic
+/// (e.g. a TLS key log) and it is optional.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
+pub struct DecryptionSecretsBlock<'a> {
+
+    /// Format of the secrets, see the `*_SECRETS` constants.
+    pub secrets_type: u32,
+
+    /// The secrets, without the padding.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub secrets_data: Cow<'a, [u8]>,
+
+    /// Options
+    pub options: Vec<DecryptionSecretsOption<'a>>
+}
+
+impl<'a> DecryptionSecretsBlock<'a> {
+
+    /// Reads a whole `DecryptionSecretsBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<DecryptionSecretsBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::DecryptionSecrets(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("DecryptionSecretsBlock::from_reader: not a DecryptionSecretsBlock"))
+        }
+    }
+
+    /// TLS Key Log, in the NSS key log format
+    pub const TLS_KEY_LOG_SECRETS: u32 = 0x544c534b;
+
+    /// WireGuard Key Log
+    pub const WIREGUARD_KEY_LOG_SECRETS: u32 = 0x57474b4c;
+
+    /// ZigBee NWK Key
+    pub const ZIGBEE_NWK_KEY_SECRETS: u32 = 0x5a4e574b;
+
+    /// ZigBee APS Key
+    pub const ZIGBEE_APS_KEY_SECRETS: u32 = 0x5a415053;
+
+    pub fn from_slice<B: ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B: ByteOrder>(mut slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
+
+        if slice.len() < 8 {
+            return Err(PcapError::InvalidField("DecryptionSecretsBlock: block length < 8"));
+        }
+
+        let secrets_type = slice.read_u32::<B>()?;
+        let secrets_len = slice.read_u32::<B>()? as usize;
+
+        let pad_len = (4 - (secrets_len % 4)) % 4;
+        let tot_len = secrets_len + pad_len;
+
+        if slice.len() < tot_len {
+            return Err(PcapError::InvalidField("DecryptionSecretsBlock: secrets_len + padding > block length"));
+        }
+
+        let secrets_data = &slice[..secrets_len];
+        slice = &slice[tot_len..];
+
+        let (slice, options) = DecryptionSecretsOption::from_slice::<B>(slice, mode)?;
+        let block = DecryptionSecretsBlock {
+            secrets_type,
+            secrets_data: Cow::Borrowed(secrets_data),
+            options
+        };
+
+        Ok((slice, block))
+    }
+
+    /// Write the body of the block: the secrets type, the padded secrets and the options.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        if self.secrets_data.len() > u32::MAX as usize {
+            return Err(PcapError::InvalidField("DecryptionSecretsBlock: secrets_data.len() > u32::MAX"));
+        }
+
+        let pad_len = (4 - (self.secrets_data.len() % 4)) % 4;
+
+        writer.write_u32::<B>(self.secrets_type)?;
+        writer.write_u32::<B>(self.secrets_data.len() as u32)?;
+        writer.write_all(&self.secrets_data)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(8 + self.secrets_data.len() + pad_len + opt_len)
+    }
+
+    /// Returns the number of bytes written for the block by a `PcapNgWriter`:
+    /// the framing, the secrets type and length, the padded secrets and the options.
+    pub fn serialized_len(&self) -> usize {
+        let secrets_len = self.secrets_data.len() + (4 - (self.secrets_data.len() % 4)) % 4;
+        framed_len(8 + secrets_len + opts_len(&self.options, DecryptionSecretsOption::serialized_len))
+    }
+
+    /// Convert a borrowed `DecryptionSecretsBlock` to an owned one.
+    pub fn to_owned(&self) -> DecryptionSecretsBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Returns the format of the secrets.
+    pub fn secrets_type(&self) -> u32 {
+        self.secrets_type
+    }
+
+    /// Returns the raw secrets, whatever their type.
+    pub fn data(&self) -> &[u8] {
+        &self.secrets_data
+    }
+
+    /// Returns the secrets as a string if they are a TLS Key Log.
+    ///
+    /// Returns `None` if the secrets are not a TLS Key Log.
+    pub fn tls_key_log(&self) -> Option<Result<&str, PcapError>> {
+
+        if self.secrets_type != Self::TLS_KEY_LOG_SECRETS {
+            return None;
+        }
+
+        Some(std::str::from_utf8(&self.secrets_data).map_err(PcapError::from))
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
+pub enum DecryptionSecretsOption<'a> {
+
+    /// Comment associated with the current block
+    Comment(Cow<'a, str>),
+
+    /// Custom option containing binary octets in the Custom Data portion
+    CustomBinary(CustomBinaryOption<'a>),
+
+    /// Custom option containing a UTF-8 string in the Custom Data portion
+    CustomUtf8(CustomUtf8Option<'a>),
+
+    /// Unknown option
+    Unknown(UnknownOption<'a>)
+}
+
+impl<'a> DecryptionSecretsOption<'a> {
+
+    fn from_slice<B: ByteOrder>(slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Vec<Self>), PcapError> {
+
+        opts_from_slice::<B, _, _>(slice, |slice, code, length| {
+
+            let opt = match code {
+
+                1 => DecryptionSecretsOption::Comment(str_from_slice(slice, mode)?),
+
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => DecryptionSecretsOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => DecryptionSecretsOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+
+                _ => DecryptionSecretsOption::Unknown(UnknownOption::new(code, length, slice))
+            };
+
+            Ok(opt)
+        })
+    }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            DecryptionSecretsOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            DecryptionSecretsOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            DecryptionSecretsOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            DecryptionSecretsOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header and the padded value.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            DecryptionSecretsOption::Comment(a) => opt_len(a.len()),
+            DecryptionSecretsOption::CustomBinary(a) => a.serialized_len(),
+            DecryptionSecretsOption::CustomUtf8(a) => a.serialized_len(),
+            DecryptionSecretsOption::Unknown(a) => a.serialized_len()
+        }
+    }
+}
+
+impl<'a> HasOptions<'a> for DecryptionSecretsBlock<'a> {
+    type Option = DecryptionSecretsOption<'a>;
+
+    fn options(&self) -> &[DecryptionSecretsOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<DecryptionSecretsOption<'a>> {
+        &mut self.options
+    }
+}
+
+impl<'a> CommentOption<'a> for DecryptionSecretsOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            DecryptionSecretsOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        DecryptionSecretsOption::Comment(comment)
+    }
+}
@@ -1,12 +1,26 @@
-use crate::pcapng::blocks::opts_from_slice;
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
 use crate::errors::PcapError;
-use byteorder::{ByteOrder, ReadBytesExt};
-use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, UnknownOption};
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::{InterfaceDescriptionBlock, ParsedBlock};
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::{validate_against_linktype, DataLink, Warning};
 
 /// An Enhanced Packet Block (EPB) is the standard container for storing the packets coming from the network.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct EnhancedPacketBlock<'a> {
 
     /// It specifies the interface this packet comes from.
@@ -16,6 +30,10 @@ pub struct EnhancedPacketBlock<'a> {
 
     /// The timestamp is a single 64-bit unsigned integer that represents the number of units of time
     /// that have elapsed since 1970-01-01 00:00:00 UTC.
+    ///
+    /// It is stored in the file as two 32-bit words, the high one first.
+    /// The length of a unit of time is specified by the 'if_tsresol' option of the Interface Description Block
+    /// referenced by this packet, see `InterfaceDescriptionBlock::resolve_timestamp()`.
     pub timestamp: u64,
 
     /// Number of octets captured from the packet (i.e. the length of the Packet Data field).
@@ -25,6 +43,7 @@ pub struct EnhancedPacketBlock<'a> {
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub data: Cow<'a, [u8]>,
 
     /// Options
@@ -33,6 +52,160 @@ pub struct EnhancedPacketBlock<'a> {
 
 impl<'a> EnhancedPacketBlock<'a> {
 
+    /// Reads a whole `EnhancedPacketBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<EnhancedPacketBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::EnhancedPacket(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("EnhancedPacketBlock::from_reader: not an EnhancedPacketBlock"))
+        }
+    }
+
+    pub fn from_slice<B: ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B: ByteOrder>(slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
+
+        let (_, lazy) = LazyEnhancedPacketBlock::from_slice::<B>(slice)?;
+        let (slice, options) = EnhancedPacketOption::from_slice_with::<B>(lazy.raw_options, mode)?;
+
+        let block = EnhancedPacketBlock {
+            interface_id: lazy.interface_id,
+            timestamp: lazy.timestamp,
+            captured_len: lazy.captured_len,
+            original_len: lazy.original_len,
+            data: Cow::Borrowed(lazy.data),
+            options
+        };
+
+        Ok((slice, block))
+    }
+
+    /// Parses the fixed fields and the packet data of the block, leaving the options undecoded.
+    ///
+    /// See `LazyEnhancedPacketBlock`.
+    pub fn data_only_from_slice<B: ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], LazyEnhancedPacketBlock<'a>), PcapError> {
+        LazyEnhancedPacketBlock::from_slice::<B>(slice)
+    }
+
+    /// Write the body of the block: the header fields, the padded packet data and the options.
+    ///
+    /// The `captured_len` field is ignored and the length of `data` is written instead.
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        if self.data.len() > u32::MAX as usize {
+            return Err(PcapError::InvalidField("EnhancedPacketBlock: data.len() > u32::MAX"));
+        }
+
+        let pad_len = (4 - (self.data.len() % 4)) % 4;
+
+        writer.write_u32::<B>(self.interface_id)?;
+        writer.write_u32::<B>((self.timestamp >> 32) as u32)?;
+        writer.write_u32::<B>(self.timestamp as u32)?;
+        writer.write_u32::<B>(self.data.len() as u32)?;
+        writer.write_u32::<B>(self.original_len)?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(20 + self.data.len() + pad_len + opt_len)
+    }
+
+    /// Returns the number of bytes written for the packet by a `PcapNgWriter`, before any truncation to the snaplen:
+    /// the framing, the 20 bytes of fixed fields, the padded data and the options.
+    pub fn serialized_len(&self) -> usize {
+        let data_len = self.data.len() + (4 - (self.data.len() % 4)) % 4;
+        framed_len(20 + data_len + opts_len(&self.options, EnhancedPacketOption::serialized_len))
+    }
+
+    /// Convert a borrowed `EnhancedPacketBlock` to an owned one.
+    pub fn to_owned(&self) -> EnhancedPacketBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Checks the packet against the linktype of its interface, see `validate_against_linktype()`.
+    pub fn validate_against_linktype(&self, datalink: DataLink) -> Vec<Warning> {
+        validate_against_linktype(datalink, &self.data, self.original_len)
+    }
+
+    /// Returns the absolute capture time of the packet, `interface` being the interface referenced by `interface_id`.
+    ///
+    /// The raw `timestamp` field is resolved with the `IfTsResol` and `IfTsOffset` options of the interface,
+    /// see `InterfaceDescriptionBlock::resolve_timestamp()`, and added to the `UNIX_EPOCH`.
+    ///
+    /// The resolution itself can't overflow: a resolution finer than the nanosecond is truncated and an offset
+    /// too large saturates. Returns `None` if the resulting time is beyond the range of `SystemTime` on this platform.
+    #[cfg(feature = "std")]
+    pub fn timestamp(&self, interface: &InterfaceDescriptionBlock) -> Option<SystemTime> {
+        UNIX_EPOCH.checked_add(interface.resolve_timestamp(self.timestamp))
+    }
+
+    /// Checks the packet data against the first `Hash` option, see `EnhancedPacketOption::verify_hash`.
+    ///
+    /// Returns `None` if there is no `Hash` option or if its algorithm is not supported.
+    pub fn verify_hash(&self) -> Option<bool> {
+        self.options.iter()
+            .find(|opt| matches!(opt, EnhancedPacketOption::Hash(_)))
+            .and_then(|opt| opt.verify_hash(&self.data))
+    }
+
+    /// Returns true if the packet has been truncated, i.e. the length of `data` is less than `original_len`.
+    pub fn is_truncated(&self) -> bool {
+        (self.data.len() as u64) < self.original_len as u64
+    }
+
+    /// Returns the decoded `Flags` option.
+    ///
+    /// If the option is absent, all the fields are set to "not available".
+    pub fn flags(&self) -> DecodedEpbFlags {
+
+        let flags = self.options.iter().find_map(|opt| match opt {
+            EnhancedPacketOption::Flags(flags) => Some(*flags),
+            _ => None
+        });
+
+        DecodedEpbFlags::from(flags.unwrap_or(0))
+    }
+}
+
+/// An Enhanced Packet Block whose options are decoded on demand.
+///
+/// Decoding the options of every packet is wasted work when only the packet data is needed:
+/// this block only borrows the raw options, `options()` decodes them when called.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LazyEnhancedPacketBlock<'a> {
+
+    /// It specifies the interface this packet comes from.
+    pub interface_id: u32,
+
+    /// The timestamp of the packet, see `EnhancedPacketBlock::timestamp`.
+    pub timestamp: u64,
+
+    /// Number of octets captured from the packet (i.e. the length of the Packet Data field).
+    pub captured_len: u32,
+
+    /// Actual length of the packet when it was transmitted on the network.
+    pub original_len: u32,
+
+    /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub data: &'a [u8],
+
+    /// The undecoded options, end of options marker included.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    pub raw_options: &'a [u8]
+}
+
+impl<'a> LazyEnhancedPacketBlock<'a> {
+
     pub fn from_slice<B: ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 20 {
@@ -40,10 +213,16 @@ impl<'a> EnhancedPacketBlock<'a> {
         }
 
         let interface_id = slice.read_u32::<B>()?;
-        let timestamp = slice.read_u64::<B>()?;
+        let timestamp_high = slice.read_u32::<B>()? as u64;
+        let timestamp_low = slice.read_u32::<B>()? as u64;
+        let timestamp = (timestamp_high << 32) + timestamp_low;
         let captured_len = slice.read_u32::<B>()?;
         let original_len = slice.read_u32::<B>()?;
 
+        if captured_len as usize > slice.len() {
+            return Err(PcapError::InvalidField("EnhancedPacketBlock: captured_len > block length"));
+        }
+
         let pad_len = (4 - (captured_len as usize % 4)) % 4;
         let tot_len = captured_len as usize + pad_len;
 
@@ -52,23 +231,138 @@ impl<'a> EnhancedPacketBlock<'a> {
         }
 
         let data = &slice[..captured_len as usize];
-        slice = &slice[tot_len..];
+        let raw_options = &slice[tot_len..];
 
-        let (slice, options) = EnhancedPacketOption::from_slice::<B>(slice)?;
-        let block = EnhancedPacketBlock {
+        let block = LazyEnhancedPacketBlock {
             interface_id,
             timestamp,
             captured_len,
             original_len,
-            data: Cow::Borrowed(data),
-            options
+            data,
+            raw_options
         };
 
-        Ok((slice, block))
+        Ok((&[], block))
+    }
+
+    /// Returns true if the packet has been truncated, i.e. `captured_len` is less than `original_len`.
+    pub fn is_truncated(&self) -> bool {
+        self.captured_len < self.original_len
+    }
+
+    /// Decodes the options of the block.
+    ///
+    /// `B` must be the byte order of the section of the block.
+    pub fn options<B: ByteOrder>(&self) -> Result<Vec<EnhancedPacketOption<'a>>, PcapError> {
+        self.options_with::<B>(Utf8Mode::Strict)
+    }
+
+    /// Decodes the options of the block, the string options are decoded according to `mode`.
+    ///
+    /// `B` must be the byte order of the section of the block.
+    pub fn options_with<B: ByteOrder>(&self, mode: Utf8Mode) -> Result<Vec<EnhancedPacketOption<'a>>, PcapError> {
+        EnhancedPacketOption::from_slice_with::<B>(self.raw_options, mode).map(|(_, options)| options)
+    }
+
+    /// Decodes the `Flags` option of the block, see `EnhancedPacketBlock::flags()`.
+    ///
+    /// Only the `Flags` option is decoded, so it is cheaper than decoding all the options.
+    /// `B` must be the byte order of the section of the block.
+    pub fn flags<B: ByteOrder>(&self) -> Result<DecodedEpbFlags, PcapError> {
+
+        let (_, options) = opts_from_slice::<B, _, _>(self.raw_options, |slice, code, _| Ok((code, slice)))?;
+        let flags = options.into_iter().find(|(code, _)| *code == 2);
+
+        match flags {
+            Some((_, slice)) if slice.len() != 4 => Err(PcapError::InvalidOption { code: 2, reason: "EnhancedPacketOption: Flags length != 4" }),
+            Some((_, slice)) => Ok(DecodedEpbFlags::from(B::read_u32(slice))),
+            None => Ok(DecodedEpbFlags::from(0))
+        }
     }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Decoded value of the `Flags` option of an `EnhancedPacketBlock`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DecodedEpbFlags {
+
+    /// Direction of the packet, bits 0-1.
+    pub direction: PacketDirection,
+
+    /// Reception type of the packet, bits 2-4.
+    pub reception_type: ReceptionType,
+
+    /// Length of the Frame Check Sequence in octets, bits 5-8.
+    ///
+    /// A value of zero means that the information is not available.
+    pub fcs_len: u8,
+
+    /// Link-layer dependent errors, bits 16-31 of the flags word.
+    ///
+    /// Bit 15 is a symbol error, 14 a preamble error, 13 a start frame delimiter error, 12 an unaligned frame error,
+    /// 11 a wrong inter frame gap error, 10 a packet too short error, 9 a packet too long error and 8 a CRC error.
+    pub link_layer_errors: u16
+}
+
+impl From<u32> for DecodedEpbFlags {
+    fn from(flags: u32) -> Self {
+
+        let direction = match flags & 0b11 {
+            1 => PacketDirection::Inbound,
+            2 => PacketDirection::Outbound,
+            _ => PacketDirection::Unknown
+        };
+
+        let reception_type = match (flags >> 2) & 0b111 {
+            0 => ReceptionType::Unspecified,
+            1 => ReceptionType::Unicast,
+            2 => ReceptionType::Multicast,
+            3 => ReceptionType::Broadcast,
+            4 => ReceptionType::Promiscuous,
+            n => ReceptionType::Unknown(n as u8)
+        };
+
+        DecodedEpbFlags {
+            direction,
+            reception_type,
+            fcs_len: ((flags >> 5) & 0b1111) as u8,
+            link_layer_errors: (flags >> 16) as u16
+        }
+    }
+}
+
+/// Direction of a packet, decoded from the `Flags` option.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PacketDirection {
+    /// Information not available
+    Unknown,
+    /// Inbound packet
+    Inbound,
+    /// Outbound packet
+    Outbound
+}
+
+/// Reception type of a packet, decoded from the `Flags` option.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ReceptionType {
+    /// Not specified
+    Unspecified,
+    /// Unicast packet
+    Unicast,
+    /// Multicast packet
+    Multicast,
+    /// Broadcast packet
+    Broadcast,
+    /// Packet received in promiscuous mode
+    Promiscuous,
+    /// Reserved value
+    Unknown(u8)
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum EnhancedPacketOption<'a> {
 
     /// Comment associated with the current block
@@ -77,7 +371,9 @@ pub enum EnhancedPacketOption<'a> {
     /// 32-bit flags word containing link-layer information.
     Flags(u32),
 
-    /// Contains a hash of the packet.
+    /// Contains a hash of the packet: its first byte is the algorithm (0 2s complement, 1 XOR, 2 CRC32, 3 MD-5,
+    /// 4 SHA-1, 5 Toeplitz) and the rest is the hash, see `verify_hash`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     Hash(Cow<'a, [u8]>),
 
     /// 64-bit integer value specifying the number of packets lost
@@ -86,6 +382,18 @@ pub enum EnhancedPacketOption<'a> {
     /// and the start of the capture process.
     DropCount(u64),
 
+    /// 64-bit unsigned integer that uniquely identifies the packet.
+    /// The same packet seen by multiple interfaces will have the same packet id.
+    PacketId(u64),
+
+    /// 32-bit unsigned integer that identifies on which queue of the interface the packet was received.
+    Queue(u32),
+
+    /// Verdict of the packet: its first byte is the verdict type (0 hardware, 1 Linux eBPF TC, 2 Linux eBPF XDP)
+    /// and the rest is the verdict data.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
+    Verdict(Cow<'a, [u8]>),
+
     /// Custom option containing binary octets in the Custom Data portion
     CustomBinary(CustomBinaryOption<'a>),
 
@@ -100,12 +408,17 @@ pub enum EnhancedPacketOption<'a> {
 impl<'a> EnhancedPacketOption<'a> {
 
     pub fn from_slice<B:ByteOrder>(slice: &'a [u8]) -> Result<(&'a[u8], Vec<Self>), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a[u8], Vec<Self>), PcapError> {
 
         opts_from_slice::<B, _, _>(slice, |mut slice, code, length| {
 
             let opt = match code {
 
-                1 => EnhancedPacketOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                1 => EnhancedPacketOption::Comment(str_from_slice(slice, mode)?),
                 2 => {
                     if slice.len() != 4 {
                         return Err(PcapError::InvalidField("EnhancedPacketOption: Flags length != 4"))
@@ -119,9 +432,22 @@ impl<'a> EnhancedPacketOption<'a> {
                     }
                     EnhancedPacketOption::DropCount(slice.read_u64::<B>()?)
                 },
+                5 => {
+                    if slice.len() != 8 {
+                        return Err(PcapError::InvalidField("EnhancedPacketOption: PacketId length != 8"))
+                    }
+                    EnhancedPacketOption::PacketId(slice.read_u64::<B>()?)
+                },
+                6 => {
+                    if slice.len() != 4 {
+                        return Err(PcapError::InvalidField("EnhancedPacketOption: Queue length != 4"))
+                    }
+                    EnhancedPacketOption::Queue(slice.read_u32::<B>()?)
+                },
+                7 => EnhancedPacketOption::Verdict(Cow::Borrowed(slice)),
 
-                2988 | 19372 => EnhancedPacketOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => EnhancedPacketOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => EnhancedPacketOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => EnhancedPacketOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => EnhancedPacketOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -129,6 +455,134 @@ impl<'a> EnhancedPacketOption<'a> {
             Ok(opt)
         })
     }
+
+    /// CRC32 hash algorithm of the `Hash` option
+    pub const HASH_CRC32: u8 = 2;
+
+    /// MD-5 hash algorithm of the `Hash` option
+    pub const HASH_MD5: u8 = 3;
+
+    /// SHA-1 hash algorithm of the `Hash` option
+    pub const HASH_SHA1: u8 = 4;
+
+    /// Recomputes the hash of `data` with the algorithm of a `Hash` option and compares it with the stored one.
+    ///
+    /// Only CRC32 (IEEE 802.3, stored in network byte order) is supported.
+    /// Returns `None` if the option is not a `Hash` or if its algorithm is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use pcap_file::pcapng::EnhancedPacketOption;
+    ///
+    /// let hash = EnhancedPacketOption::Hash(Cow::Borrowed(&[2, 0xCB, 0xF4, 0x39, 0x26]));
+    ///
+    /// assert_eq!(hash.verify_hash(b"123456789"), Some(true));
+    /// assert_eq!(hash.verify_hash(b"12345678"), Some(false));
+    /// ```
+    pub fn verify_hash(&self, data: &[u8]) -> Option<bool> {
+
+        let hash = match self {
+            EnhancedPacketOption::Hash(hash) => hash,
+            _ => return None
+        };
+
+        match hash.split_first()? {
+            (&Self::HASH_CRC32, digest) => Some(digest == crc32(data).to_be_bytes()),
+            _ => None
+        }
+    }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            EnhancedPacketOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            EnhancedPacketOption::Flags(a) => {
+                let mut value = [0_u8; 4];
+                B::write_u32(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 2, &value)
+            },
+            EnhancedPacketOption::Hash(a) => opt_to_writer::<_, B>(writer, 3, a),
+            EnhancedPacketOption::DropCount(a) => {
+                let mut value = [0_u8; 8];
+                B::write_u64(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 4, &value)
+            },
+            EnhancedPacketOption::PacketId(a) => {
+                let mut value = [0_u8; 8];
+                B::write_u64(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 5, &value)
+            },
+            EnhancedPacketOption::Queue(a) => {
+                let mut value = [0_u8; 4];
+                B::write_u32(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 6, &value)
+            },
+            EnhancedPacketOption::Verdict(a) => opt_to_writer::<_, B>(writer, 7, a),
+            EnhancedPacketOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            EnhancedPacketOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            EnhancedPacketOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header and the padded value,
+    /// the integers taking their fixed size.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            EnhancedPacketOption::Comment(a) => opt_len(a.len()),
+            EnhancedPacketOption::Flags(_) => opt_len(4),
+            EnhancedPacketOption::Hash(a) => opt_len(a.len()),
+            EnhancedPacketOption::DropCount(_) => opt_len(8),
+            EnhancedPacketOption::PacketId(_) => opt_len(8),
+            EnhancedPacketOption::Queue(_) => opt_len(4),
+            EnhancedPacketOption::Verdict(a) => opt_len(a.len()),
+            EnhancedPacketOption::CustomBinary(a) => a.serialized_len(),
+            EnhancedPacketOption::CustomUtf8(a) => a.serialized_len(),
+            EnhancedPacketOption::Unknown(a) => a.serialized_len()
+        }
+    }
 }
 
+/// Computes the CRC32 (IEEE 802.3) of `data`.
+fn crc32(data: &[u8]) -> u32 {
 
+    let mut crc = 0xFFFFFFFF_u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB88320 & (crc & 1).wrapping_neg());
+        }
+    }
+
+    !crc
+}
+
+impl<'a> HasOptions<'a> for EnhancedPacketBlock<'a> {
+    type Option = EnhancedPacketOption<'a>;
+
+    fn options(&self) -> &[EnhancedPacketOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<EnhancedPacketOption<'a>> {
+        &mut self.options
+    }
+}
+
+impl<'a> CommentOption<'a> for EnhancedPacketOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            EnhancedPacketOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        EnhancedPacketOption::Comment(comment)
+    }
+}
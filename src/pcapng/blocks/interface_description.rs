@@ -1,16 +1,31 @@
 #![allow(clippy::cast_lossless)]
 
-use crate::pcapng::blocks::common::opts_from_slice;
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
 use crate::errors::PcapError;
+use crate::common::ReadSlice;
 use crate::DataLink;
-use byteorder::{ByteOrder, ReadBytesExt};
-use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, UnknownOption};
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
+use core::convert::TryFrom;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+use core::time::Duration;
+use core::net::{Ipv4Addr, Ipv6Addr};
 use derive_into_owned::IntoOwned;
 
 /// An Interface Description Block (IDB) is the container for information describing an interface
 /// on which packet data is captured.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct InterfaceDescriptionBlock<'a> {
 
     /// A value that defines the link layer type of this interface.
@@ -32,7 +47,26 @@ pub struct InterfaceDescriptionBlock<'a> {
 
 impl<'a> InterfaceDescriptionBlock<'a> {
 
-    pub fn from_slice<B:ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+    /// Reads a whole `InterfaceDescriptionBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<InterfaceDescriptionBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::InterfaceDescription(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("InterfaceDescriptionBlock::from_reader: not an InterfaceDescriptionBlock"))
+        }
+    }
+
+    /// Parses the body of an `InterfaceDescriptionBlock`.
+    ///
+    /// The options are accepted in any order and kept in the order of the block, see `canonicalize()`.
+    pub fn from_slice<B:ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(mut slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 8 {
             return Err(PcapError::InvalidField("InterfaceDescriptionBlock: block length < 8"));
@@ -41,7 +75,7 @@ impl<'a> InterfaceDescriptionBlock<'a> {
         let linktype = (slice.read_u16::<B>()? as u32).into();
         let reserved = slice.read_u16::<B>()?;
         let snaplen = slice.read_u32::<B>()?;
-        let (slice, options) = InterfaceDescriptionOption::from_slice::<B>(slice)?;
+        let (slice, options) = InterfaceDescriptionOption::from_slice::<B>(slice, mode)?;
 
         let block = InterfaceDescriptionBlock {
             linktype,
@@ -52,9 +86,375 @@ impl<'a> InterfaceDescriptionBlock<'a> {
 
         Ok((slice, block))
     }
+
+    /// Returns a builder to create a new `InterfaceDescriptionBlock`.
+    pub fn builder() -> InterfaceDescriptionBlockBuilder {
+        InterfaceDescriptionBlockBuilder::default()
+    }
+
+    /// Creates an interface of link type `linktype` capturing at most `snaplen` octets per packet, 0 meaning no limit.
+    ///
+    /// The interface has no option, so its timestamps have the default microsecond resolution.
+    pub fn from_linktype(linktype: DataLink, snaplen: u32) -> InterfaceDescriptionBlock<'static> {
+        InterfaceDescriptionBlock::builder()
+            .linktype(linktype)
+            .snaplen(snaplen)
+            .build()
+    }
+
+    /// Creates an `ETHERNET` interface with no snaplen limit and microsecond timestamps,
+    /// enough for the usual captures with a single interface.
+    ///
+    /// # Examples
+    ///
+    /// A minimal capture with one packet:
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use pcap_file::pcapng::{EnhancedPacketBlock, InterfaceDescriptionBlock, PcapNgReader, PcapNgWriter};
+    ///
+    /// let mut pcapng_writer = PcapNgWriter::new(vec![]).unwrap();
+    /// let interface_id = pcapng_writer.write_interface(&InterfaceDescriptionBlock::ethernet()).unwrap();
+    ///
+    /// let frame = [0_u8; 60];
+    /// let packet = EnhancedPacketBlock {
+    ///     interface_id,
+    ///     timestamp: 1_600_000_000_000_000,
+    ///     captured_len: frame.len() as u32,
+    ///     original_len: frame.len() as u32,
+    ///     data: Cow::Borrowed(&frame[..]),
+    ///     options: vec![]
+    /// };
+    /// pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    ///
+    /// let data = pcapng_writer.into_writer();
+    /// assert_eq!(PcapNgReader::new(&data[..]).unwrap().count(), 2);
+    /// ```
+    pub fn ethernet() -> InterfaceDescriptionBlock<'static> {
+        InterfaceDescriptionBlock::from_linktype(DataLink::ETHERNET, 0)
+    }
+
+    /// Write the body of the block: the link type, the snaplen and the options.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let linktype: u32 = self.linktype.into();
+        if linktype > u16::MAX as u32 {
+            return Err(PcapError::InvalidField("InterfaceDescriptionBlock: linktype > u16::MAX"));
+        }
+
+        writer.write_u16::<B>(linktype as u16)?;
+        writer.write_u16::<B>(self.reserved)?;
+        writer.write_u32::<B>(self.snaplen)?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(8 + opt_len)
+    }
+
+    /// Returns the number of bytes written for the interface by a `PcapNgWriter`:
+    /// the framing, the link type, the snaplen and the options.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(8 + opts_len(&self.options, InterfaceDescriptionOption::serialized_len))
+    }
+
+    /// Convert a borrowed `InterfaceDescriptionBlock` to an owned one.
+    pub fn to_owned(&self) -> InterfaceDescriptionBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Returns the link layer type of this interface.
+    pub fn linktype(&self) -> DataLink {
+        self.linktype
+    }
+
+    /// Returns the maximum number of octets captured from each packet.
+    ///
+    /// A value of zero means that there is no limit.
+    pub fn snaplen(&self) -> u32 {
+        self.snaplen
+    }
+
+    /// Returns the options of this interface.
+    pub fn options(&self) -> &[InterfaceDescriptionOption<'a>] {
+        &self.options[..]
+    }
+
+    /// Sets the maximum number of octets captured from each packet, zero meaning no limit.
+    pub fn set_snaplen(&mut self, snaplen: u32) {
+        self.snaplen = snaplen;
+    }
+
+    /// Appends an option to this interface.
+    ///
+    /// The length of the block is computed when it is written, so the options can be changed freely before.
+    pub fn push_option(&mut self, option: InterfaceDescriptionOption<'a>) {
+        self.options.push(option);
+    }
+
+    /// Removes all the options whose code is `code` and returns the number of options removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use pcap_file::pcapng::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+    ///
+    /// let mut idb = InterfaceDescriptionBlock::builder().name("eth0").speed(1_000_000_000).build();
+    ///
+    /// // Renames the interface
+    /// assert_eq!(idb.remove_option_by_code(2), 1);
+    /// idb.push_option(InterfaceDescriptionOption::IfName(Cow::Borrowed("eth1")));
+    ///
+    /// assert_eq!(idb.if_name(), Some("eth1"));
+    /// ```
+    pub fn remove_option_by_code(&mut self, code: u16) -> usize {
+        let len = self.options.len();
+        self.options.retain(|opt| opt.code() != code);
+        len - self.options.len()
+    }
+
+    /// Sorts the options by ascending code, so that equivalent interfaces are written with the same bytes.
+    ///
+    /// The sort is stable: the options sharing a code, e.g. several comments, keep their relative order.
+    /// Calling it again doesn't change the options.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::pcapng::InterfaceDescriptionBlock;
+    ///
+    /// let mut idb = InterfaceDescriptionBlock::builder().speed(1_000_000_000).name("eth0").build();
+    /// idb.canonicalize();
+    ///
+    /// let codes: Vec<u16> = idb.options.iter().map(|opt| opt.code()).collect();
+    /// assert_eq!(codes, [2, 8]);
+    /// ```
+    pub fn canonicalize(&mut self) {
+        self.options.sort_by_key(|opt| opt.code());
+    }
+
+    /// Returns the number of timestamp units per second of this interface, decoded from the `IfTsResol` option.
+    ///
+    /// Defaults to 1_000_000 (microsecond resolution) when the option is absent.
+    ///
+    /// Returns `None` if the resolution doesn't fit in a u64.
+    pub fn ts_units_per_second(&self) -> Option<u64> {
+
+        match self.if_tsresol() {
+            Some(tsresol) => InterfaceDescriptionOption::IfTsResol(tsresol).units_per_second(),
+            None => Some(1_000_000)
+        }
+    }
+
+    /// Converts a raw timestamp of a packet captured on this interface into a `Duration` since 1970-01-01 00:00:00 UTC.
+    ///
+    /// The raw value is scaled by the `IfTsResol` option (microseconds if absent),
+    /// then the `IfTsOffset` option (zero if absent) is added.
+    ///
+    /// The computation can't overflow, sub-nanosecond precision is truncated and
+    /// the result saturates to `Duration::MAX` if the offset is too large.
+    pub fn resolve_timestamp(&self, raw: u64) -> Duration {
+
+        let units_per_second = match self.if_tsresol() {
+            Some(tsresol) => tsresol_units_per_second(tsresol),
+            None => Some(1_000_000)
+        };
+
+        // A resolution that doesn't fit in a u128 is far below the nanosecond
+        let units_per_second = units_per_second.unwrap_or(u128::MAX);
+
+        let secs = (raw as u128 / units_per_second) as u64;
+        let nanos = ((raw as u128 % units_per_second) * 1_000_000_000 / units_per_second) as u32;
+
+        let timestamp = Duration::new(secs, nanos);
+        match self.if_tsoffset() {
+            Some(offset) => timestamp.checked_add(Duration::from_secs(offset)).unwrap_or(Duration::MAX),
+            None => timestamp
+        }
+    }
+
+    /// Returns the comments of this interface.
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.options.iter().filter_map(|opt| match opt {
+            InterfaceDescriptionOption::Comment(comment) => Some(comment.as_ref()),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfName` option.
+    pub fn if_name(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfName(name) => Some(name.as_ref()),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfDescription` option.
+    pub fn if_description(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfDescription(description) => Some(description.as_ref()),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfSpeed` option.
+    pub fn if_speed(&self) -> Option<u64> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfSpeed(speed) => Some(*speed),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfTsResol` option.
+    pub fn if_tsresol(&self) -> Option<u8> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfTsResol(tsresol) => Some(*tsresol),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfTsOffset` option.
+    pub fn if_tsoffset(&self) -> Option<u64> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfTsOffset(offset) => Some(*offset),
+            _ => None
+        })
+    }
+
+    /// Returns the raw value of the first `IfTzone` option.
+    pub fn if_tzone(&self) -> Option<u32> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfTzone(tzone) => Some(*tzone),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfTzone` option interpreted as a signed offset from GMT, in seconds.
+    ///
+    /// The format of if_tzone is not defined by the pcapng specification: this is only the interpretation
+    /// of some tools, other writers may use a different one. The raw value is available with `if_tzone()`
+    /// and is written back unchanged.
+    pub fn timezone_offset(&self) -> Option<i32> {
+        self.if_tzone().map(|tzone| tzone as i32)
+    }
+
+    /// Returns the first libpcap filter string of the `IfFilter` options, see `InterfaceDescriptionOption::as_bpf_string()`.
+    pub fn bpf_filter(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfFilter(_) => opt.as_bpf_string(),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfOs` option.
+    pub fn if_os(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfOs(os) => Some(os.as_ref()),
+            _ => None
+        })
+    }
+
+    /// Returns the first `IfHardware` option.
+    pub fn if_hardware(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            InterfaceDescriptionOption::IfHardware(hardware) => Some(hardware.as_ref()),
+            _ => None
+        })
+    }
+}
+
+/// Builder of an owned `InterfaceDescriptionBlock`.
+///
+/// Defaults to an `ETHERNET` interface with no snaplen limit and no option.
+///
+/// # Examples
+///
+/// ```rust
+/// use pcap_file::DataLink;
+/// use pcap_file::pcapng::InterfaceDescriptionBlock;
+///
+/// let idb = InterfaceDescriptionBlock::builder()
+///     .linktype(DataLink::ETHERNET)
+///     .snaplen(65535)
+///     .name("eth0")
+///     .tsresol(9)
+///     .build();
+///
+/// assert_eq!(idb.if_name(), Some("eth0"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct InterfaceDescriptionBlockBuilder {
+    linktype: DataLink,
+    snaplen: u32,
+    options: Vec<InterfaceDescriptionOption<'static>>
+}
+
+impl InterfaceDescriptionBlockBuilder {
+
+    /// Sets the link layer type of the interface.
+    pub fn linktype(mut self, linktype: DataLink) -> Self {
+        self.linktype = linktype;
+        self
+    }
+
+    /// Sets the maximum number of octets captured from each packet, 0 means no limit.
+    pub fn snaplen(mut self, snaplen: u32) -> Self {
+        self.snaplen = snaplen;
+        self
+    }
+
+    /// Adds an `IfName` option.
+    pub fn name(self, name: &str) -> Self {
+        self.option(InterfaceDescriptionOption::IfName(Cow::Owned(name.to_owned())))
+    }
+
+    /// Adds an `IfTsResol` option.
+    pub fn tsresol(self, tsresol: u8) -> Self {
+        self.option(InterfaceDescriptionOption::IfTsResol(tsresol))
+    }
+
+    /// Adds an `IfSpeed` option, in bits per second.
+    pub fn speed(self, speed: u64) -> Self {
+        self.option(InterfaceDescriptionOption::IfSpeed(speed))
+    }
+
+    /// Adds an `IfFilter` option containing a libpcap filter string, see `InterfaceDescriptionOption::bpf_string()`.
+    pub fn filter(self, filter: &str) -> Self {
+        self.option(InterfaceDescriptionOption::bpf_string(filter))
+    }
+
+    /// Adds any option.
+    pub fn option(mut self, option: InterfaceDescriptionOption<'static>) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Creates the `InterfaceDescriptionBlock`.
+    pub fn build(self) -> InterfaceDescriptionBlock<'static> {
+        InterfaceDescriptionBlock {
+            linktype: self.linktype,
+            reserved: 0,
+            snaplen: self.snaplen,
+            options: self.options
+        }
+    }
+}
+
+impl Default for InterfaceDescriptionBlockBuilder {
+    fn default() -> Self {
+        InterfaceDescriptionBlockBuilder {
+            linktype: DataLink::ETHERNET,
+            snaplen: 0,
+            options: vec![]
+        }
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum InterfaceDescriptionOption<'a> {
 
     Comment(Cow<'a, str>),
@@ -66,12 +466,21 @@ pub enum InterfaceDescriptionOption<'a> {
     IfDescription(Cow<'a, str>),
 
     /// The if_IPv4addr option is an IPv4 network address and corresponding netmask for the interface.
+    ///
+    /// The raw value is 8 bytes long: the address followed by the netmask, see `as_ipv4()`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     IfIpv4Addr(Cow<'a, [u8]>),
 
     /// The if_IPv6addr option is an IPv6 network address and corresponding prefix length for the interface.
+    ///
+    /// The raw value is 17 bytes long: the address followed by the prefix length, see `as_ipv6()`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     IfIpv6Addr(Cow<'a, [u8]>),
 
     /// The if_MACaddr option is the Interface Hardware MAC address (48 bits), if available.
+    ///
+    /// The parser rejects values that are not exactly 6 bytes long, see `as_mac()`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     IfMacAddr(Cow<'a, [u8]>),
 
     /// The if_EUIaddr option is the Interface Hardware EUI address (64 bits), if available.
@@ -81,12 +490,21 @@ pub enum InterfaceDescriptionOption<'a> {
     IfSpeed(u64),
 
     /// The if_tsresol option identifies the resolution of timestamps.
+    ///
+    /// If the most significant bit is 0 the remaining bits indicate the resolution as a negative power of 10,
+    /// otherwise as a negative power of 2, see `units_per_second()`.
     IfTsResol(u8),
 
     /// The if_tzone option identifies the time zone for GMT support.
+    ///
+    /// Its format is not defined by the specification, the raw value is kept as is,
+    /// see `InterfaceDescriptionBlock::timezone_offset()`.
     IfTzone(u32),
 
     /// The if_filter option identifies the filter (e.g. "capture only TCP traffic") used to capture traffic.
+    ///
+    /// The value starts with the filter type byte, see `as_filter()`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     IfFilter(Cow<'a, [u8]>),
 
     /// The if_os option is a UTF-8 string containing the name of the operating system
@@ -117,15 +535,15 @@ pub enum InterfaceDescriptionOption<'a> {
 
 impl<'a> InterfaceDescriptionOption<'a> {
 
-    fn from_slice<B:ByteOrder>(slice: &'a[u8]) -> Result<(&'a[u8], Vec<Self>), PcapError> {
+    fn from_slice<B:ByteOrder>(slice: &'a[u8], mode: Utf8Mode) -> Result<(&'a[u8], Vec<Self>), PcapError> {
 
         opts_from_slice::<B, _, _>(slice, |mut slice, code, length| {
 
             let opt = match code {
 
-                1 => InterfaceDescriptionOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                2 => InterfaceDescriptionOption::IfName(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                3 => InterfaceDescriptionOption::IfDescription(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                1 => InterfaceDescriptionOption::Comment(str_from_slice(slice, mode)?),
+                2 => InterfaceDescriptionOption::IfName(str_from_slice(slice, mode)?),
+                3 => InterfaceDescriptionOption::IfDescription(str_from_slice(slice, mode)?),
                 4 => {
                     if slice.len() != 8 {
                         return Err(PcapError::InvalidField("InterfaceDescriptionOption: IfIpv4Addr length != 8"))
@@ -163,8 +581,8 @@ impl<'a> InterfaceDescriptionOption<'a> {
                     InterfaceDescriptionOption::IfTsResol(slice.read_u8()?)
                 },
                 10 => {
-                    if slice.len() != 1 {
-                        return Err(PcapError::InvalidField("InterfaceDescriptionOption: IfTzone length != 1"))
+                    if slice.len() != 4 {
+                        return Err(PcapError::InvalidField("InterfaceDescriptionOption: IfTzone length != 4"))
                     }
                     InterfaceDescriptionOption::IfTzone(slice.read_u32::<B>()?)
                 },
@@ -174,7 +592,7 @@ impl<'a> InterfaceDescriptionOption<'a> {
                     }
                     InterfaceDescriptionOption::IfFilter(Cow::Borrowed(slice))
                 },
-                12 => InterfaceDescriptionOption::IfOs(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                12 => InterfaceDescriptionOption::IfOs(str_from_slice(slice, mode)?),
                 13 => {
                     if slice.len() != 1 {
                         return Err(PcapError::InvalidField("InterfaceDescriptionOption: IfFcsLen length != 1"))
@@ -187,10 +605,10 @@ impl<'a> InterfaceDescriptionOption<'a> {
                     }
                     InterfaceDescriptionOption::IfTsOffset(slice.read_u64::<B>()?)
                 },
-                15 => InterfaceDescriptionOption::IfHardware(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                15 => InterfaceDescriptionOption::IfHardware(str_from_slice(slice, mode)?),
 
-                2988 | 19372 => InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => InterfaceDescriptionOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => InterfaceDescriptionOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => InterfaceDescriptionOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -198,4 +616,310 @@ impl<'a> InterfaceDescriptionOption<'a> {
             Ok(opt)
         })
     }
+
+    /// Convert a borrowed `InterfaceDescriptionOption` to an owned one.
+    pub fn to_owned(&self) -> InterfaceDescriptionOption<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Creates an `IfFilter` option containing a libpcap filter string, e.g. `"tcp port 80"`.
+    ///
+    /// The filter type byte of `FilterKind::Libpcap` is prepended, it is the counterpart of `as_bpf_string()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pcap_file::pcapng::InterfaceDescriptionOption;
+    ///
+    /// let opt = InterfaceDescriptionOption::bpf_string("tcp port 80");
+    /// assert_eq!(opt.as_bpf_string(), Some("tcp port 80"));
+    /// ```
+    pub fn bpf_string(filter: &str) -> InterfaceDescriptionOption<'static> {
+        let mut value = Vec::with_capacity(1 + filter.len());
+        value.push(u8::from(FilterKind::Libpcap));
+        value.extend_from_slice(filter.as_bytes());
+        InterfaceDescriptionOption::IfFilter(Cow::Owned(value))
+    }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    ///
+    /// Strings are written as raw UTF-8 without a NUL terminator and integers are written in the `B` byte order.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            InterfaceDescriptionOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            InterfaceDescriptionOption::IfName(a) => opt_to_writer::<_, B>(writer, 2, a.as_bytes()),
+            InterfaceDescriptionOption::IfDescription(a) => opt_to_writer::<_, B>(writer, 3, a.as_bytes()),
+            InterfaceDescriptionOption::IfIpv4Addr(a) => opt_to_writer::<_, B>(writer, 4, a),
+            InterfaceDescriptionOption::IfIpv6Addr(a) => opt_to_writer::<_, B>(writer, 5, a),
+            InterfaceDescriptionOption::IfMacAddr(a) => opt_to_writer::<_, B>(writer, 6, a),
+            InterfaceDescriptionOption::IfEuIAddr(a) => {
+                let mut value = [0_u8; 8];
+                B::write_u64(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 7, &value)
+            },
+            InterfaceDescriptionOption::IfSpeed(a) => {
+                let mut value = [0_u8; 8];
+                B::write_u64(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 8, &value)
+            },
+            InterfaceDescriptionOption::IfTsResol(a) => opt_to_writer::<_, B>(writer, 9, &[*a]),
+            InterfaceDescriptionOption::IfTzone(a) => {
+                let mut value = [0_u8; 4];
+                B::write_u32(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 10, &value)
+            },
+            InterfaceDescriptionOption::IfFilter(a) => opt_to_writer::<_, B>(writer, 11, a),
+            InterfaceDescriptionOption::IfOs(a) => opt_to_writer::<_, B>(writer, 12, a.as_bytes()),
+            InterfaceDescriptionOption::IfFcsLen(a) => opt_to_writer::<_, B>(writer, 13, &[*a]),
+            InterfaceDescriptionOption::IfTsOffset(a) => {
+                let mut value = [0_u8; 8];
+                B::write_u64(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 14, &value)
+            },
+            InterfaceDescriptionOption::IfHardware(a) => opt_to_writer::<_, B>(writer, 15, a.as_bytes()),
+            InterfaceDescriptionOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            InterfaceDescriptionOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            InterfaceDescriptionOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header and the padded value,
+    /// the integers taking their fixed size.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            InterfaceDescriptionOption::Comment(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfName(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfDescription(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfIpv4Addr(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfIpv6Addr(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfMacAddr(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfEuIAddr(_) => opt_len(8),
+            InterfaceDescriptionOption::IfSpeed(_) => opt_len(8),
+            InterfaceDescriptionOption::IfTsResol(_) => opt_len(1),
+            InterfaceDescriptionOption::IfTzone(_) => opt_len(4),
+            InterfaceDescriptionOption::IfFilter(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfOs(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::IfFcsLen(_) => opt_len(1),
+            InterfaceDescriptionOption::IfTsOffset(_) => opt_len(8),
+            InterfaceDescriptionOption::IfHardware(a) => opt_len(a.len()),
+            InterfaceDescriptionOption::CustomBinary(a) => a.serialized_len(),
+            InterfaceDescriptionOption::CustomUtf8(a) => a.serialized_len(),
+            InterfaceDescriptionOption::Unknown(a) => a.serialized_len()
+        }
+    }
+
+    /// Returns the code of the option.
+    pub fn code(&self) -> u16 {
+
+        match self {
+            InterfaceDescriptionOption::Comment(_) => 1,
+            InterfaceDescriptionOption::IfName(_) => 2,
+            InterfaceDescriptionOption::IfDescription(_) => 3,
+            InterfaceDescriptionOption::IfIpv4Addr(_) => 4,
+            InterfaceDescriptionOption::IfIpv6Addr(_) => 5,
+            InterfaceDescriptionOption::IfMacAddr(_) => 6,
+            InterfaceDescriptionOption::IfEuIAddr(_) => 7,
+            InterfaceDescriptionOption::IfSpeed(_) => 8,
+            InterfaceDescriptionOption::IfTsResol(_) => 9,
+            InterfaceDescriptionOption::IfTzone(_) => 10,
+            InterfaceDescriptionOption::IfFilter(_) => 11,
+            InterfaceDescriptionOption::IfOs(_) => 12,
+            InterfaceDescriptionOption::IfFcsLen(_) => 13,
+            InterfaceDescriptionOption::IfTsOffset(_) => 14,
+            InterfaceDescriptionOption::IfHardware(_) => 15,
+            InterfaceDescriptionOption::CustomBinary(a) => a.code,
+            InterfaceDescriptionOption::CustomUtf8(a) => a.code,
+            InterfaceDescriptionOption::Unknown(a) => a.code
+        }
+    }
+
+    /// Returns the address and the netmask of an `IfIpv4Addr` option.
+    ///
+    /// Returns `None` if the option is not an `IfIpv4Addr` or if its value is not 8 bytes long.
+    pub fn as_ipv4(&self) -> Option<(Ipv4Addr, Ipv4Addr)> {
+
+        match self {
+            InterfaceDescriptionOption::IfIpv4Addr(value) if value.len() == 8 => {
+                let addr = Ipv4Addr::new(value[0], value[1], value[2], value[3]);
+                let netmask = Ipv4Addr::new(value[4], value[5], value[6], value[7]);
+                Some((addr, netmask))
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the address and the prefix length of an `IfIpv6Addr` option.
+    ///
+    /// Returns `None` if the option is not an `IfIpv6Addr` or if its value is not 17 bytes long.
+    pub fn as_ipv6(&self) -> Option<(Ipv6Addr, u8)> {
+
+        match self {
+            InterfaceDescriptionOption::IfIpv6Addr(value) if value.len() == 17 => {
+                let mut octets = [0_u8; 16];
+                octets.copy_from_slice(&value[..16]);
+                Some((Ipv6Addr::from(octets), value[16]))
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the MAC address of an `IfMacAddr` option.
+    ///
+    /// Returns `None` if the option is not an `IfMacAddr` or if its value is not 6 bytes long.
+    pub fn as_mac(&self) -> Option<[u8; 6]> {
+
+        match self {
+            InterfaceDescriptionOption::IfMacAddr(value) if value.len() == 6 => {
+                let mut mac = [0_u8; 6];
+                mac.copy_from_slice(value);
+                Some(mac)
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the kind and the data of an `IfFilter` option, without the filter type byte.
+    ///
+    /// Returns `None` if the option is not an `IfFilter` or if its value is empty.
+    pub fn as_filter(&self) -> Option<(FilterKind, &[u8])> {
+
+        match self {
+            InterfaceDescriptionOption::IfFilter(value) if !value.is_empty() => Some((FilterKind::from(value[0]), &value[1..])),
+            _ => None
+        }
+    }
+
+    /// Returns the expression of an `IfFilter` option containing a libpcap filter string, e.g. `"tcp port 80"`.
+    ///
+    /// A trailing NUL terminator, written by some tools, is removed.
+    ///
+    /// Returns `None` if the option is not an `IfFilter`, if its kind is not `FilterKind::Libpcap`
+    /// or if the expression is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use pcap_file::pcapng::InterfaceDescriptionOption;
+    ///
+    /// let opt = InterfaceDescriptionOption::IfFilter(Cow::Borrowed(b"\x00tcp port 80"));
+    /// assert_eq!(opt.as_bpf_string(), Some("tcp port 80"));
+    /// ```
+    pub fn as_bpf_string(&self) -> Option<&str> {
+
+        match self.as_filter() {
+            Some((FilterKind::Libpcap, data)) => {
+                let data = data.strip_suffix(&[0]).unwrap_or(data);
+                std::str::from_utf8(data).ok()
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the number of timestamp units per second of an `IfTsResol` option.
+    ///
+    /// Returns `None` if the option is not an `IfTsResol` or if the resolution doesn't fit in a u64.
+    pub fn units_per_second(&self) -> Option<u64> {
+
+        match self {
+            InterfaceDescriptionOption::IfTsResol(tsresol) => tsresol_units_per_second(*tsresol).and_then(|units| u64::try_from(units).ok()),
+            _ => None
+        }
+    }
+}
+
+/// Decodes an if_tsresol value: a negative power of 10 if the MSB is 0, otherwise a negative power of 2.
+///
+/// Returns `None` if the resolution doesn't fit in a u128.
+fn tsresol_units_per_second(tsresol: u8) -> Option<u128> {
+
+    let exponent = (tsresol & 0x7F) as u32;
+    if tsresol & 0x80 == 0 {
+        10_u128.checked_pow(exponent)
+    }
+    else {
+        2_u128.checked_pow(exponent)
+    }
+}
+
+/// Kind of the filter of an `IfFilter` option, given by its first byte.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FilterKind {
+
+    /// libpcap filter string, e.g. `"tcp port 80"` (type 0)
+    Libpcap,
+
+    /// BPF bytecode (type 1)
+    Bpf,
+
+    /// Filter type not defined by the specification
+    Unknown(u8)
+}
+
+impl From<u8> for FilterKind {
+    fn from(kind: u8) -> Self {
+        match kind {
+            0 => FilterKind::Libpcap,
+            1 => FilterKind::Bpf,
+            kind => FilterKind::Unknown(kind)
+        }
+    }
+}
+
+impl From<FilterKind> for u8 {
+    fn from(kind: FilterKind) -> Self {
+        match kind {
+            FilterKind::Libpcap => 0,
+            FilterKind::Bpf => 1,
+            FilterKind::Unknown(kind) => kind
+        }
+    }
+}
+
+/// A 48 bits MAC address.
+///
+/// Displayed as colon separated lowercase hexadecimal octets: `aa:bb:cc:dd:ee:ff`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let o = &self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", o[0], o[1], o[2], o[3], o[4], o[5])
+    }
+}
+
+impl<'a> HasOptions<'a> for InterfaceDescriptionBlock<'a> {
+    type Option = InterfaceDescriptionOption<'a>;
+
+    fn options(&self) -> &[InterfaceDescriptionOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<InterfaceDescriptionOption<'a>> {
+        &mut self.options
+    }
+}
+
+impl<'a> CommentOption<'a> for InterfaceDescriptionOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            InterfaceDescriptionOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        InterfaceDescriptionOption::Comment(comment)
+    }
 }
@@ -1,13 +1,24 @@
-use crate::pcapng::blocks::common::opts_from_slice;
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, LengthMode, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
 use crate::errors::PcapError;
-use byteorder::{ByteOrder, ReadBytesExt};
-use crate::pcapng::{UnknownOption, CustomUtf8Option, CustomBinaryOption};
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::{UnknownOption, CustomUtf8Option, CustomBinaryOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 
 /// The Interface Statistics Block contains the capture statistics for a given interface and it is optional.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct InterfaceStatisticsBlock<'a> {
 
     /// Specifies the interface these statistics refers to.
@@ -26,15 +37,41 @@ pub struct InterfaceStatisticsBlock<'a> {
 
 impl<'a> InterfaceStatisticsBlock<'a> {
 
-    pub fn from_slice<B:ByteOrder>(mut slice: &'a[u8]) -> Result<(&'a[u8], Self), PcapError> {
+    /// Reads a whole `InterfaceStatisticsBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<InterfaceStatisticsBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::InterfaceStatistics(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("InterfaceStatisticsBlock::from_reader: not an InterfaceStatisticsBlock"))
+        }
+    }
+
+    pub fn from_slice<B:ByteOrder>(slice: &'a[u8]) -> Result<(&'a[u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict, LengthMode::Lenient)
+    }
+
+    /// Same as `from_slice` but the length of the counters and timestamps options is checked according to `lengths`.
+    ///
+    /// With `LengthMode::Strict` an option whose value is not 8 bytes long is rejected instead of being truncated,
+    /// like the fixed-size options of the other blocks always are.
+    pub fn from_slice_with_lengths<B:ByteOrder>(slice: &'a[u8], lengths: LengthMode) -> Result<(&'a[u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict, lengths)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(mut slice: &'a[u8], mode: Utf8Mode, lengths: LengthMode) -> Result<(&'a[u8], Self), PcapError> {
 
         if slice.len() < 12 {
             return Err(PcapError::InvalidField("InterfaceStatisticsBlock: block length < 12"));
         }
 
-        let interface_id = slice.read_u32::<B>()? as u32;
-        let timestamp = slice.read_u64::<B>()?;
-        let (slice, options) = InterfaceStatisticsOption::from_slice::<B>(slice)?;
+        let interface_id = slice.read_u32::<B>()?;
+        let timestamp_high = slice.read_u32::<B>()? as u64;
+        let timestamp_low = slice.read_u32::<B>()? as u64;
+        let timestamp = (timestamp_high << 32) + timestamp_low;
+        let (slice, options) = InterfaceStatisticsOption::from_slice::<B>(slice, mode, lengths)?;
 
         let block = InterfaceStatisticsBlock {
             interface_id,
@@ -44,9 +81,36 @@ impl<'a> InterfaceStatisticsBlock<'a> {
 
         Ok((slice, block))
     }
+
+    /// Write the body of the block: the interface id, the timestamp and the options.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        writer.write_u32::<B>(self.interface_id)?;
+        writer.write_u32::<B>((self.timestamp >> 32) as u32)?;
+        writer.write_u32::<B>(self.timestamp as u32)?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(12 + opt_len)
+    }
+
+    /// Returns the number of bytes written for the statistics by a `PcapNgWriter`:
+    /// the framing, the interface id, the timestamp and the options.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(12 + opts_len(&self.options, InterfaceStatisticsOption::serialized_len))
+    }
+
+    /// Convert a borrowed `InterfaceStatisticsBlock` to an owned one.
+    pub fn to_owned(&self) -> InterfaceStatisticsBlock<'static> {
+        self.clone().into_owned()
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum InterfaceStatisticsOption<'a> {
 
     /// The opt_comment option is a UTF-8 string containing human-readable comment text
@@ -54,9 +118,15 @@ pub enum InterfaceStatisticsOption<'a> {
     Comment(Cow<'a, str>),
 
     /// The isb_starttime option specifies the time the capture started.
+    ///
+    /// Like the block timestamp, it is stored as two 32-bit words, the high one first, and its unit is given
+    /// by the 'if_tsresol' option of the referenced interface: the resolution must be applied by the caller,
+    /// see `InterfaceDescriptionBlock::resolve_timestamp()`.
     IsbStartTime(u64),
 
     /// The isb_endtime option specifies the time the capture ended.
+    ///
+    /// It is stored and scaled like `IsbStartTime`.
     IsbEndTime(u64),
 
     /// The isb_ifrecv option specifies the 64-bit unsigned integer number of packets received from the physical interface
@@ -91,23 +161,23 @@ pub enum InterfaceStatisticsOption<'a> {
 
 impl<'a> InterfaceStatisticsOption<'a> {
 
-    fn from_slice<B:ByteOrder>(slice: &'a[u8]) -> Result<(&'a [u8], Vec<Self>), PcapError> {
+    fn from_slice<B:ByteOrder>(slice: &'a[u8], mode: Utf8Mode, lengths: LengthMode) -> Result<(&'a [u8], Vec<Self>), PcapError> {
 
-        opts_from_slice::<B, _, _>(slice, |mut slice, code, length| {
+        opts_from_slice::<B, _, _>(slice, |slice, code, length| {
 
             let opt = match code {
 
-                1 => InterfaceStatisticsOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                2 => InterfaceStatisticsOption::IsbStartTime(slice.read_u64::<B>()?),
-                3 => InterfaceStatisticsOption::IsbEndTime(slice.read_u64::<B>()?),
-                4 => InterfaceStatisticsOption::IsbIfRecv(slice.read_u64::<B>()?),
-                5 => InterfaceStatisticsOption::IsbIfDrop(slice.read_u64::<B>()?),
-                6 => InterfaceStatisticsOption::IsbFilterAccept(slice.read_u64::<B>()?),
-                7 => InterfaceStatisticsOption::IsbOsDrop(slice.read_u64::<B>()?),
-                8 => InterfaceStatisticsOption::IsbUsrDeliv(slice.read_u64::<B>()?),
+                1 => InterfaceStatisticsOption::Comment(str_from_slice(slice, mode)?),
+                2 => InterfaceStatisticsOption::IsbStartTime(timestamp_from_value::<B>(u64_value(slice, lengths)?)),
+                3 => InterfaceStatisticsOption::IsbEndTime(timestamp_from_value::<B>(u64_value(slice, lengths)?)),
+                4 => InterfaceStatisticsOption::IsbIfRecv(B::read_u64(u64_value(slice, lengths)?)),
+                5 => InterfaceStatisticsOption::IsbIfDrop(B::read_u64(u64_value(slice, lengths)?)),
+                6 => InterfaceStatisticsOption::IsbFilterAccept(B::read_u64(u64_value(slice, lengths)?)),
+                7 => InterfaceStatisticsOption::IsbOsDrop(B::read_u64(u64_value(slice, lengths)?)),
+                8 => InterfaceStatisticsOption::IsbUsrDeliv(B::read_u64(u64_value(slice, lengths)?)),
 
-                2988 | 19372 => InterfaceStatisticsOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => InterfaceStatisticsOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => InterfaceStatisticsOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => InterfaceStatisticsOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => InterfaceStatisticsOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -115,5 +185,91 @@ impl<'a> InterfaceStatisticsOption<'a> {
             Ok(opt)
         })
     }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            InterfaceStatisticsOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            InterfaceStatisticsOption::IsbStartTime(a) => write_timestamp::<_, B>(writer, 2, *a),
+            InterfaceStatisticsOption::IsbEndTime(a) => write_timestamp::<_, B>(writer, 3, *a),
+            InterfaceStatisticsOption::IsbIfRecv(a) => write_u64::<_, B>(writer, 4, *a),
+            InterfaceStatisticsOption::IsbIfDrop(a) => write_u64::<_, B>(writer, 5, *a),
+            InterfaceStatisticsOption::IsbFilterAccept(a) => write_u64::<_, B>(writer, 6, *a),
+            InterfaceStatisticsOption::IsbOsDrop(a) => write_u64::<_, B>(writer, 7, *a),
+            InterfaceStatisticsOption::IsbUsrDeliv(a) => write_u64::<_, B>(writer, 8, *a),
+            InterfaceStatisticsOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            InterfaceStatisticsOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            InterfaceStatisticsOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`, all the counters and timestamps taking 8 bytes.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            InterfaceStatisticsOption::Comment(a) => opt_len(a.len()),
+            InterfaceStatisticsOption::CustomBinary(a) => a.serialized_len(),
+            InterfaceStatisticsOption::CustomUtf8(a) => a.serialized_len(),
+            InterfaceStatisticsOption::Unknown(a) => a.serialized_len(),
+            _ => opt_len(8)
+        }
+    }
 }
 
+/// Returns the 8 bytes of the value of a counter or timestamp option, checked according to `lengths`.
+fn u64_value(slice: &[u8], lengths: LengthMode) -> Result<&[u8], PcapError> {
+
+    match (slice.len(), lengths) {
+        (8, _) => Ok(slice),
+        (len, LengthMode::Lenient) if len > 8 => Ok(&slice[..8]),
+        _ => Err(PcapError::InvalidField("InterfaceStatisticsOption: value length != 8"))
+    }
+}
+
+/// Decodes a timestamp option, written as its high then its low 32 bits
+fn timestamp_from_value<B: ByteOrder>(value: &[u8]) -> u64 {
+    ((B::read_u32(&value[..4]) as u64) << 32) + B::read_u32(&value[4..8]) as u64
+}
+
+#[cfg(feature = "std")]
+fn write_timestamp<W: Write, B: ByteOrder>(writer: &mut W, code: u16, timestamp: u64) -> Result<usize, PcapError> {
+    let mut value = [0_u8; 8];
+    B::write_u32(&mut value[..4], (timestamp >> 32) as u32);
+    B::write_u32(&mut value[4..], timestamp as u32);
+    opt_to_writer::<_, B>(writer, code, &value)
+}
+
+#[cfg(feature = "std")]
+fn write_u64<W: Write, B: ByteOrder>(writer: &mut W, code: u16, value: u64) -> Result<usize, PcapError> {
+    let mut bytes = [0_u8; 8];
+    B::write_u64(&mut bytes, value);
+    opt_to_writer::<_, B>(writer, code, &bytes)
+}
+
+impl<'a> HasOptions<'a> for InterfaceStatisticsBlock<'a> {
+    type Option = InterfaceStatisticsOption<'a>;
+
+    fn options(&self) -> &[InterfaceStatisticsOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<InterfaceStatisticsOption<'a>> {
+        &mut self.options
+    }
+}
+
+impl<'a> CommentOption<'a> for InterfaceStatisticsOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            InterfaceStatisticsOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        InterfaceStatisticsOption::Comment(comment)
+    }
+}
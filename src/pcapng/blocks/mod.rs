@@ -24,3 +24,9 @@ pub use systemd_journal_export::*;
 
 pub(crate) mod packet;
 pub use packet::*;
+
+pub(crate) mod decryption_secrets;
+pub use decryption_secrets::*;
+
+pub(crate) mod custom;
+pub use custom::*;
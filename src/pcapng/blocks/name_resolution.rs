@@ -1,14 +1,25 @@
-use crate::pcapng::blocks::common::opts_from_slice;
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
 use crate::errors::PcapError;
-use byteorder::{ByteOrder, ReadBytesExt};
-use crate::pcapng::{CustomBinaryOption, CustomUtf8Option, UnknownOption};
+use crate::common::ReadSlice;
+use byteorder::{ByteOrder};
+use crate::pcapng::{CustomBinaryOption, CustomUtf8Option, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
+use core::convert::TryInto;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+use core::net::{Ipv4Addr, Ipv6Addr};
 
 
 /// The Name Resolution Block (NRB) is used to support the correlation of numeric addresses
 /// (present in the captured packets) and their corresponding canonical names and it is optional.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct NameResolutionBlock<'a> {
 
     /// Records
@@ -20,12 +31,28 @@ pub struct NameResolutionBlock<'a> {
 
 impl<'a> NameResolutionBlock<'a> {
 
-    pub fn from_slice<B:ByteOrder>(mut slice: &'a[u8]) -> Result<(&'a [u8], Self), PcapError> {
+    /// Reads a whole `NameResolutionBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<NameResolutionBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::NameResolution(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("NameResolutionBlock::from_reader: not a NameResolutionBlock"))
+        }
+    }
+
+    pub fn from_slice<B:ByteOrder>(slice: &'a[u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the names and the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(mut slice: &'a[u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
 
         let mut records = Vec::new();
 
         loop {
-            let (slice_tmp, record) = Record::from_slice::<B>(slice)?;
+            let (slice_tmp, record) = Record::from_slice_with::<B>(slice, mode)?;
             slice = slice_tmp;
 
             match record {
@@ -34,7 +61,7 @@ impl<'a> NameResolutionBlock<'a> {
             }
         }
 
-        let (rem, options) = NameResolutionOption::from_slice::<B>(slice)?;
+        let (rem, options) = NameResolutionOption::from_slice::<B>(slice, mode)?;
 
         let block = NameResolutionBlock {
             records,
@@ -43,26 +70,81 @@ impl<'a> NameResolutionBlock<'a> {
 
         Ok((rem, block))
     }
+
+    /// Write the body of the block: the records, the end of records marker and the options.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let mut len = 0;
+        for record in &self.records {
+            len += record.write_to::<_, B>(writer)?;
+        }
+        len += Record::End.write_to::<_, B>(writer)?;
+
+        len += opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(len)
+    }
+
+    /// Returns the number of bytes written for the block by a `PcapNgWriter`:
+    /// the framing, the records, the end of records marker and the options.
+    pub fn serialized_len(&self) -> usize {
+        let records_len: usize = self.records.iter().map(Record::serialized_len).sum();
+        framed_len(records_len + Record::End.serialized_len() + opts_len(&self.options, NameResolutionOption::serialized_len))
+    }
+
+    /// Returns the records of the block, the end of records marker excluded.
+    pub fn records(&self) -> &[Record<'a>] {
+        &self.records[..]
+    }
+
+    /// Returns the options of the block.
+    pub fn options(&self) -> &[NameResolutionOption<'a>] {
+        &self.options[..]
+    }
+
+    /// Convert a borrowed `NameResolutionBlock` to an owned one.
+    pub fn to_owned(&self) -> NameResolutionBlock<'static> {
+        self.clone().into_owned()
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+/// Record of a Name Resolution Block
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum Record<'a> {
+    /// End of the records, nrb_record_end (type 0)
     End,
+    /// IPv4 address and its names, nrb_record_ipv4 (type 1)
     Ipv4(Ipv4Record<'a>),
+    /// IPv6 address and its names, nrb_record_ipv6 (type 2)
     Ipv6(Ipv6Record<'a>),
+    /// Record whose type is not known by the parser
     Unknown(UnknownRecord<'a>)
 }
 
 impl<'a> Record<'a> {
 
-    pub fn from_slice<B:ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a[u8], Self), PcapError> {
+    pub fn from_slice<B:ByteOrder>(slice: &'a [u8]) -> Result<(&'a[u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the names are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(mut slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a[u8], Self), PcapError> {
+
+        if slice.len() < 4 {
+            return Err(PcapError::InvalidField("NameResolutionBlock: Record header len < 4"));
+        }
 
         let type_ = slice.read_u16::<B>()?;
         let length = slice.read_u16::<B>()?;
         let pad_len = (4 - length % 4) % 4;
+        let len = length as usize + pad_len as usize;
 
-        if slice.len() < length as usize {
-            return Err(PcapError::InvalidField("NameResolutionBlock: Record length > slice.len()"));
+        if slice.len() < len {
+            return Err(PcapError::InvalidField("NameResolutionBlock: Record length + padding > slice.len()"));
         }
         let value = &slice[..length as usize];
 
@@ -74,11 +156,11 @@ impl<'a> Record<'a> {
                 Record::End
             },
             1 => {
-                let record = Ipv4Record::from_slice(value)?;
+                let record = Ipv4Record::from_slice_with(value, mode)?;
                 Record::Ipv4(record)
             },
             2 => {
-                let record = Ipv6Record::from_slice(value)?;
+                let record = Ipv6Record::from_slice_with(value, mode)?;
                 Record::Ipv6(record)
             },
             _=> {
@@ -87,23 +169,75 @@ impl<'a> Record<'a> {
             }
         };
 
-        let len = length as usize + pad_len as usize;
-
         Ok((&slice[len..], record))
     }
+
+    /// Write the record, its padding included, and returns the number of bytes written.
+    ///
+    /// Each name is written followed by a NUL terminator.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let (type_, value) = match self {
+            Record::End => (0, vec![]),
+            Record::Ipv4(record) => (1, names_to_vec(&record.ip_addr, &record.names)),
+            Record::Ipv6(record) => (2, names_to_vec(&record.ip_addr, &record.names)),
+            Record::Unknown(record) => (record.type_, record.value.to_vec())
+        };
+
+        // Records share the option framing
+        opt_to_writer::<_, B>(writer, type_, &value)
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header, the address, the NUL terminated names
+    /// and the padding.
+    pub fn serialized_len(&self) -> usize {
+
+        let value_len = match self {
+            Record::End => 0,
+            Record::Ipv4(record) => names_len(&record.ip_addr, &record.names),
+            Record::Ipv6(record) => names_len(&record.ip_addr, &record.names),
+            Record::Unknown(record) => record.value.len()
+        };
+
+        opt_len(value_len)
+    }
+}
+
+fn names_len(ip_addr: &[u8], names: &[Cow<str>]) -> usize {
+    ip_addr.len() + names.iter().map(|name| name.len() + 1).sum::<usize>()
+}
+
+#[cfg(feature = "std")]
+fn names_to_vec(ip_addr: &[u8], names: &[Cow<str>]) -> Vec<u8> {
+
+    let mut value = ip_addr.to_vec();
+    for name in names {
+        value.extend_from_slice(name.as_bytes());
+        value.push(0);
+    }
+
+    value
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct Ipv4Record<'a> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub ip_addr: Cow<'a, [u8]>,
     pub names: Vec<Cow<'a, str>>
 }
 
 impl<'a> Ipv4Record<'a> {
 
-    pub fn from_slice(mut slice: &'a [u8]) -> Result<Self, PcapError> {
+    pub fn from_slice(slice: &'a [u8]) -> Result<Self, PcapError> {
+        Self::from_slice_with(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the names are decoded according to `mode`.
+    pub(crate) fn from_slice_with(mut slice: &'a [u8], mode: Utf8Mode) -> Result<Self, PcapError> {
 
-        if slice.len() < 6 as usize {
+        if slice.len() < 6 {
             return Err(PcapError::InvalidField("NameResolutionBlock: Ipv4Record len < 6"));
         }
 
@@ -112,9 +246,9 @@ impl<'a> Ipv4Record<'a> {
 
         let mut names = vec![];
         while !slice.is_empty() {
-            let (slice_tmp, name) = str_from_u8_null_terminated(slice)?;
+            let (slice_tmp, name) = name_from_slice(slice, mode)?;
             slice = slice_tmp;
-            names.push(Cow::Borrowed(name));
+            names.push(name);
         }
 
         let record = Ipv4Record {
@@ -124,19 +258,32 @@ impl<'a> Ipv4Record<'a> {
 
         Ok(record)
     }
+
+    /// Returns the IPv4 address of the record, or `None` if `ip_addr` is not 4 bytes long.
+    pub fn addr(&self) -> Option<Ipv4Addr> {
+        let addr: [u8; 4] = self.ip_addr[..].try_into().ok()?;
+        Some(Ipv4Addr::from(addr))
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct Ipv6Record<'a> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub ip_addr: Cow<'a, [u8]>,
     pub names: Vec<Cow<'a, str>>
 }
 
 impl<'a> Ipv6Record<'a> {
 
-    pub fn from_slice(mut slice: &'a[u8]) -> Result<Self, PcapError> {
+    pub fn from_slice(slice: &'a[u8]) -> Result<Self, PcapError> {
+        Self::from_slice_with(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the names are decoded according to `mode`.
+    pub(crate) fn from_slice_with(mut slice: &'a[u8], mode: Utf8Mode) -> Result<Self, PcapError> {
 
-        if slice.len() < 18 as usize {
+        if slice.len() < 18 {
             return Err(PcapError::InvalidField("NameResolutionBlock: Ipv6Record len < 18"));
         }
 
@@ -145,9 +292,9 @@ impl<'a> Ipv6Record<'a> {
 
         let mut names = vec![];
         while !slice.is_empty() {
-            let (slice_tmp, name) = str_from_u8_null_terminated(slice)?;
+            let (slice_tmp, name) = name_from_slice(slice, mode)?;
             slice = slice_tmp;
-            names.push(Cow::Borrowed(name));
+            names.push(name);
         }
 
         let record = Ipv6Record {
@@ -157,12 +304,20 @@ impl<'a> Ipv6Record<'a> {
 
         Ok(record)
     }
+
+    /// Returns the IPv6 address of the record, or `None` if `ip_addr` is not 16 bytes long.
+    pub fn addr(&self) -> Option<Ipv6Addr> {
+        let addr: [u8; 16] = self.ip_addr[..].try_into().ok()?;
+        Some(Ipv6Addr::from(addr))
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct UnknownRecord<'a> {
     pub type_: u16,
     pub length: u16,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub value: Cow<'a, [u8]>
 }
 
@@ -177,7 +332,8 @@ impl<'a> UnknownRecord<'a> {
     }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum NameResolutionOption<'a> {
     /// The opt_comment option is a UTF-8 string containing human-readable comment text
     /// that is associated to the current block.
@@ -187,9 +343,11 @@ pub enum NameResolutionOption<'a> {
     NsDnsName(Cow<'a, str>),
 
     /// The ns_dnsIP4addr option specifies the IPv4 address of the DNS server.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     NsDnsIpv4Addr(Cow<'a, [u8]>),
 
     /// The ns_dnsIP6addr option specifies the IPv6 address of the DNS server.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     NsDnsIpv6Addr(Cow<'a, [u8]>),
 
     /// Custom option containing binary octets in the Custom Data portion
@@ -205,14 +363,14 @@ pub enum NameResolutionOption<'a> {
 
 impl<'a> NameResolutionOption<'a> {
 
-    fn from_slice<B:ByteOrder>(slice: &'a[u8]) -> Result<(&'a[u8], Vec<Self>), PcapError> {
+    fn from_slice<B:ByteOrder>(slice: &'a[u8], mode: Utf8Mode) -> Result<(&'a[u8], Vec<Self>), PcapError> {
 
         opts_from_slice::<B, _, _>(slice, |slice, code, length| {
 
             let opt = match code {
 
-                1 => NameResolutionOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                2 => NameResolutionOption::NsDnsName(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                1 => NameResolutionOption::Comment(str_from_slice(slice, mode)?),
+                2 => NameResolutionOption::NsDnsName(str_from_slice(slice, mode)?),
                 3 => {
                     if slice.len() != 4 {
                         return Err(PcapError::InvalidField("NameResolutionOption: NsDnsIpv4Addr length != 4"))
@@ -226,8 +384,8 @@ impl<'a> NameResolutionOption<'a> {
                     NameResolutionOption::NsDnsIpv6Addr(Cow::Borrowed(slice))
                 },
 
-                2988 | 19372 => NameResolutionOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => NameResolutionOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => NameResolutionOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => NameResolutionOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => NameResolutionOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -235,6 +393,47 @@ impl<'a> NameResolutionOption<'a> {
             Ok(opt)
         })
     }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            NameResolutionOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            NameResolutionOption::NsDnsName(a) => opt_to_writer::<_, B>(writer, 2, a.as_bytes()),
+            NameResolutionOption::NsDnsIpv4Addr(a) => opt_to_writer::<_, B>(writer, 3, a),
+            NameResolutionOption::NsDnsIpv6Addr(a) => opt_to_writer::<_, B>(writer, 4, a),
+            NameResolutionOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            NameResolutionOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            NameResolutionOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`: the header and the padded value.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            NameResolutionOption::Comment(a) => opt_len(a.len()),
+            NameResolutionOption::NsDnsName(a) => opt_len(a.len()),
+            NameResolutionOption::NsDnsIpv4Addr(a) => opt_len(a.len()),
+            NameResolutionOption::NsDnsIpv6Addr(a) => opt_len(a.len()),
+            NameResolutionOption::CustomBinary(a) => a.serialized_len(),
+            NameResolutionOption::CustomUtf8(a) => a.serialized_len(),
+            NameResolutionOption::Unknown(a) => a.serialized_len()
+        }
+    }
+}
+
+/// Splits a NUL terminated name from the slice and decodes it according to `mode`.
+fn name_from_slice(src: &[u8], mode: Utf8Mode) -> Result<(&[u8], Cow<'_, str>), PcapError> {
+
+    let nul_pos = src.iter()
+        .position(|&c| c == b'\0')
+        .ok_or(PcapError::InvalidField("Non null terminated string"))?;
+
+    let name = str_from_slice(&src[..nul_pos], mode)?;
+
+    Ok((&src[(nul_pos + 1).min(src.len())..], name))
 }
 
 pub fn str_from_u8_null_terminated(src: &[u8]) -> Result<(&[u8], &str), PcapError> {
@@ -255,3 +454,28 @@ pub fn str_from_u8_null_terminated(src: &[u8]) -> Result<(&[u8], &str), PcapErro
     Ok((rem, s))
 }
 
+impl<'a> HasOptions<'a> for NameResolutionBlock<'a> {
+    type Option = NameResolutionOption<'a>;
+
+    fn options(&self) -> &[NameResolutionOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<NameResolutionOption<'a>> {
+        &mut self.options
+    }
+}
+
+impl<'a> CommentOption<'a> for NameResolutionOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            NameResolutionOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        NameResolutionOption::Comment(comment)
+    }
+}
@@ -1,14 +1,28 @@
-use crate::pcapng::blocks::opts_from_slice;
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::prelude::*;
 use crate::errors::PcapError;
-use byteorder::{ByteOrder, ReadBytesExt};
-use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, UnknownOption};
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::{CustomUtf8Option, CustomBinaryOption, DecodedEpbFlags, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 
 /// The Packet Block is obsolete, and MUST NOT be used in new files.
 /// Use the Enhanced Packet Block or Simple Packet Block instead.
-#[derive(Clone, Debug, IntoOwned)]
+///
+/// It is still parsed, so that the files written by old tools can be read, and can be written back as is,
+/// but new captures should be written with `EnhancedPacketBlock`s.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct PacketBlock<'a> {
 
     /// It specifies the interface this packet comes from.
@@ -21,6 +35,10 @@ pub struct PacketBlock<'a> {
 
     /// The timestamp is a single 64-bit unsigned integer that represents the number of units of time
     /// that have elapsed since 1970-01-01 00:00:00 UTC.
+    ///
+    /// It is stored in the file as two 32-bit words, the high one first.
+    /// The length of a unit of time is specified by the 'if_tsresol' option of the Interface Description Block
+    /// referenced by this packet, see `InterfaceDescriptionBlock::resolve_timestamp()`.
     pub timestamp: u64,
 
     /// Number of octets captured from the packet (i.e. the length of the Packet Data field).
@@ -30,6 +48,7 @@ pub struct PacketBlock<'a> {
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub data: Cow<'a, [u8]>,
 
     /// Options
@@ -38,29 +57,51 @@ pub struct PacketBlock<'a> {
 
 impl<'a> PacketBlock<'a> {
 
-    pub fn from_slice<B: ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+    /// Reads a whole `PacketBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<PacketBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::Packet(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("PacketBlock::from_reader: not a PacketBlock"))
+        }
+    }
+
+    pub fn from_slice<B: ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B: ByteOrder>(mut slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 20 {
-            return Err(PcapError::InvalidField("EnhancedPacketBlock: block length length < 20"));
+            return Err(PcapError::InvalidField("PacketBlock: block length < 20"));
         }
 
         let interface_id = slice.read_u16::<B>()?;
         let drop_count = slice.read_u16::<B>()?;
-        let timestamp = slice.read_u64::<B>()?;
+        let timestamp_high = slice.read_u32::<B>()? as u64;
+        let timestamp_low = slice.read_u32::<B>()? as u64;
+        let timestamp = (timestamp_high << 32) + timestamp_low;
         let captured_len = slice.read_u32::<B>()?;
         let original_len = slice.read_u32::<B>()?;
 
+        if captured_len as usize > slice.len() {
+            return Err(PcapError::InvalidField("PacketBlock: captured_len > block length"));
+        }
+
         let pad_len = (4 - (captured_len as usize % 4)) % 4;
         let tot_len = captured_len as usize + pad_len;
 
         if slice.len() < tot_len {
-            return Err(PcapError::InvalidField("EnhancedPacketBlock: captured_len + padding > block length"));
+            return Err(PcapError::InvalidField("PacketBlock: captured_len + padding > block length"));
         }
 
         let data = &slice[..captured_len as usize];
         slice = &slice[tot_len..];
 
-        let (slice, options) = PacketOption::from_slice::<B>(slice)?;
+        let (slice, options) = PacketOption::from_slice_with::<B>(slice, mode)?;
         let block = PacketBlock {
             interface_id,
             drop_count,
@@ -73,9 +114,57 @@ impl<'a> PacketBlock<'a> {
 
         Ok((slice, block))
     }
+
+    /// Write the body of the block: the header fields, the padded packet data and the options.
+    ///
+    /// The `captured_len` field is ignored and the length of `data` is written instead.
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        if self.data.len() > u32::MAX as usize {
+            return Err(PcapError::InvalidField("PacketBlock: data.len() > u32::MAX"));
+        }
+
+        let pad_len = (4 - (self.data.len() % 4)) % 4;
+
+        writer.write_u16::<B>(self.interface_id)?;
+        writer.write_u16::<B>(self.drop_count)?;
+        writer.write_u32::<B>((self.timestamp >> 32) as u32)?;
+        writer.write_u32::<B>(self.timestamp as u32)?;
+        writer.write_u32::<B>(self.data.len() as u32)?;
+        writer.write_u32::<B>(self.original_len)?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(20 + self.data.len() + pad_len + opt_len)
+    }
+
+    /// Returns the number of bytes written for the packet by a `PcapNgWriter`:
+    /// the framing, the 20 bytes of fixed fields, the padded data and the options.
+    pub fn serialized_len(&self) -> usize {
+        let data_len = self.data.len() + (4 - (self.data.len() % 4)) % 4;
+        framed_len(20 + data_len + opts_len(&self.options, PacketOption::serialized_len))
+    }
+
+    /// Returns the decoded `Flags` option, which has the same layout as the one of an `EnhancedPacketBlock`.
+    ///
+    /// If the option is absent, all the fields are set to "not available".
+    pub fn flags(&self) -> DecodedEpbFlags {
+
+        let flags = self.options.iter().find_map(|opt| match opt {
+            PacketOption::Flags(flags) => Some(*flags),
+            _ => None
+        });
+
+        DecodedEpbFlags::from(flags.unwrap_or(0))
+    }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum PacketOption<'a> {
 
     /// Comment associated with the current block
@@ -85,6 +174,7 @@ pub enum PacketOption<'a> {
     Flags(u32),
 
     /// Contains a hash of the packet.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     Hash(Cow<'a, [u8]>),
 
     /// Custom option containing binary octets in the Custom Data portion
@@ -101,12 +191,17 @@ pub enum PacketOption<'a> {
 impl<'a> PacketOption<'a> {
 
     pub fn from_slice<B:ByteOrder>(slice: &'a [u8]) -> Result<(&'a[u8], Vec<Self>), PcapError> {
+        Self::from_slice_with::<B>(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with<B:ByteOrder>(slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a[u8], Vec<Self>), PcapError> {
 
         opts_from_slice::<B, _, _>(slice, |mut slice, code, length| {
 
             let opt = match code {
 
-                1 => PacketOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                1 => PacketOption::Comment(str_from_slice(slice, mode)?),
                 2 => {
                     if slice.len() != 4 {
                         return Err(PcapError::InvalidField("PacketOption: Flags length != 4"))
@@ -115,8 +210,8 @@ impl<'a> PacketOption<'a> {
                 },
                 3 => PacketOption::Hash(Cow::Borrowed(slice)),
 
-                2988 | 19372 => PacketOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => PacketOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => PacketOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => PacketOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => PacketOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -124,6 +219,61 @@ impl<'a> PacketOption<'a> {
             Ok(opt)
         })
     }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            PacketOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            PacketOption::Flags(a) => {
+                let mut value = [0_u8; 4];
+                B::write_u32(&mut value, *a);
+                opt_to_writer::<_, B>(writer, 2, &value)
+            },
+            PacketOption::Hash(a) => opt_to_writer::<_, B>(writer, 3, a),
+            PacketOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            PacketOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            PacketOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`, the flags taking 4 bytes.
+    pub fn serialized_len(&self) -> usize {
+
+        match self {
+            PacketOption::Comment(a) => opt_len(a.len()),
+            PacketOption::Flags(_) => opt_len(4),
+            PacketOption::Hash(a) => opt_len(a.len()),
+            PacketOption::CustomBinary(a) => a.serialized_len(),
+            PacketOption::CustomUtf8(a) => a.serialized_len(),
+            PacketOption::Unknown(a) => a.serialized_len()
+        }
+    }
+}
+
+impl<'a> HasOptions<'a> for PacketBlock<'a> {
+    type Option = PacketOption<'a>;
+
+    fn options(&self) -> &[PacketOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<PacketOption<'a>> {
+        &mut self.options
+    }
 }
 
+impl<'a> CommentOption<'a> for PacketOption<'a> {
 
+    fn comment(&self) -> Option<&str> {
+        match self {
+            PacketOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
+
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        PacketOption::Comment(comment)
+    }
+}
@@ -1,13 +1,24 @@
+use crate::prelude::*;
 use crate::errors::PcapError;
-use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use crate::common::ReadSlice;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
 use crate::Endianness;
-use crate::pcapng::blocks::common::opts_from_slice;
-use crate::pcapng::{CustomBinaryOption, CustomUtf8Option, UnknownOption};
+use crate::pcapng::options::{opt_len, opts_len, opts_from_slice, str_from_slice, CommentOption, HasOptions, Utf8Mode, OPT_CUSTOM_BINARY_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE, OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_UTF8_NON_COPYABLE};
+#[cfg(feature = "std")]
+use crate::pcapng::options::{opt_to_writer, opts_to_writer};
+use crate::pcapng::{CustomBinaryOption, CustomUtf8Option, UnknownOption, framed_len};
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 ///Section Header Block: it defines the most important characteristics of the capture file.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct SectionHeaderBlock<'a> {
 
     /// Magic number, whose value is 0x1A2B3C4D.
@@ -35,7 +46,27 @@ pub struct SectionHeaderBlock<'a> {
 
 impl<'a> SectionHeaderBlock<'a> {
 
-    pub fn from_slice(mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+    /// Reads a whole `SectionHeaderBlock`, its framing included, and returns it owned.
+    ///
+    /// The byte order is given by the magic number of the block.
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<SectionHeaderBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, BigEndian>(reader)? {
+            ParsedBlock::SectionHeader(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("SectionHeaderBlock::from_reader: not a SectionHeaderBlock"))
+        }
+    }
+
+    /// Parses the body of a `SectionHeaderBlock`, whose magic number gives the byte order.
+    ///
+    /// Returns `PcapError::UnsupportedVersion` if the major version is not 1, whatever the minor version.
+    pub fn from_slice(slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
+        Self::from_slice_with(slice, Utf8Mode::Strict)
+    }
+
+    /// Same as `from_slice` but the string options are decoded according to `mode`.
+    pub(crate) fn from_slice_with(mut slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 16 {
             return Err(PcapError::InvalidField("SectionHeaderBlock: block length < 16"));
@@ -45,10 +76,10 @@ impl<'a> SectionHeaderBlock<'a> {
 
         let (rem, major_version, minor_version, section_length, options) = match magic {
 
-            0x1A2B3C4D => parse_inner::<BigEndian>(slice)?,
-            0x4D3C2B1A => parse_inner::<LittleEndian>(slice)?,
+            0x1A2B3C4D => parse_inner::<BigEndian>(slice, mode)?,
+            0x4D3C2B1A => parse_inner::<LittleEndian>(slice, mode)?,
 
-            _ => unreachable!()
+            _ => return Err(PcapError::InvalidField("SectionHeaderBlock: invalid magic number"))
         };
 
         let block = SectionHeaderBlock {
@@ -62,30 +93,139 @@ impl<'a> SectionHeaderBlock<'a> {
         return Ok((rem, block));
 
         #[allow(clippy::type_complexity)]
-        fn parse_inner<B: ByteOrder>(mut slice: &[u8]) -> Result<(&[u8], u16, u16, i64, Vec<SectionHeaderOption>), PcapError> {
+        fn parse_inner<B: ByteOrder>(mut slice: &[u8], mode: Utf8Mode) -> Result<(&[u8], u16, u16, i64, Vec<SectionHeaderOption<'_>>), PcapError> {
 
             let maj_ver = slice.read_u16::<B>()?;
             let min_ver = slice.read_u16::<B>()?;
+
+            // A new minor version must stay readable by the parsers of the previous ones
+            if maj_ver != 1 {
+                return Err(PcapError::UnsupportedVersion { major: maj_ver, minor: min_ver });
+            }
+
             let sec_len = slice.read_i64::<B>()?;
-            let (rem, opts) = SectionHeaderOption::from_slice::<B>(slice)?;
+            let (rem, opts) = SectionHeaderOption::from_slice::<B>(slice, mode)?;
 
             Ok((rem, maj_ver, min_ver, sec_len, opts))
         }
     }
 
+    /// Write the body of the block: the magic, the versions, the section length and the options.
+    ///
+    /// The magic is always written as 0x1A2B3C4D in the `B` byte order, whatever the value of the `magic` field.
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        writer.write_u32::<B>(0x1A2B3C4D)?;
+        writer.write_u16::<B>(self.major_version)?;
+        writer.write_u16::<B>(self.minor_version)?;
+        writer.write_i64::<B>(self.section_length)?;
+
+        let opt_len = opts_to_writer::<_, B, _, _>(writer, &self.options, |writer, opt| opt.write_to::<_, B>(writer))?;
+
+        Ok(16 + opt_len)
+    }
+
+    /// Returns the number of bytes written for the section header by a `PcapNgWriter`:
+    /// the framing, the 16 bytes of fixed fields and the options.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(16 + opts_len(&self.options, SectionHeaderOption::serialized_len))
+    }
+
+    /// Convert a borrowed `SectionHeaderBlock` to an owned one.
+    pub fn to_owned(&self) -> SectionHeaderBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Returns the byte order of the section, detected from the magic number.
+    ///
+    /// `from_slice` rejects invalid magic numbers, any other value set by hand is treated as big endian.
     pub fn endianness(&self) -> Endianness {
 
         match self.magic {
-
-            0x1A2B3C4D => Endianness::Big,
             0x4D3C2B1A => Endianness::Little,
-            _ => unreachable!()
+            _ => Endianness::Big
+        }
+    }
+
+    /// Change the endianness of the magic number.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+
+        if self.endianness() != endianness {
+            self.magic = match endianness {
+                Endianness::Big => 0x1A2B3C4D,
+                Endianness::Little => 0x4D3C2B1A
+            };
+        }
+    }
+
+    /// Returns the major version of the format.
+    pub fn major_version(&self) -> u16 {
+        self.major_version
+    }
+
+    /// Returns the minor version of the format.
+    pub fn minor_version(&self) -> u16 {
+        self.minor_version
+    }
+
+    /// Returns the length in bytes of the section, this block excluded.
+    ///
+    /// Returns `None` if the length is unspecified (-1).
+    pub fn section_length(&self) -> Option<u64> {
+        if self.section_length < 0 {
+            None
+        }
+        else {
+            Some(self.section_length as u64)
+        }
+    }
+
+    /// Returns the options of the section.
+    pub fn options(&self) -> &[SectionHeaderOption<'a>] {
+        &self.options[..]
+    }
+
+    /// Returns the value of the shb_hardware option.
+    pub fn hardware(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            SectionHeaderOption::Hardware(a) => Some(a.as_ref()),
+            _ => None
+        })
+    }
 
+    /// Returns the value of the shb_os option.
+    pub fn os(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            SectionHeaderOption::OS(a) => Some(a.as_ref()),
+            _ => None
+        })
+    }
+
+    /// Returns the value of the shb_userappl option.
+    pub fn user_application(&self) -> Option<&str> {
+        self.options.iter().find_map(|opt| match opt {
+            SectionHeaderOption::UserApplication(a) => Some(a.as_ref()),
+            _ => None
+        })
+    }
+}
+
+impl Default for SectionHeaderBlock<'static> {
+    fn default() -> Self {
+        SectionHeaderBlock {
+            magic: 0x1A2B3C4D,
+            major_version: 1,
+            minor_version: 0,
+            section_length: -1,
+            options: vec![]
         }
     }
 }
 
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub enum SectionHeaderOption<'a> {
 
     /// Comment associated with the current block
@@ -113,19 +253,19 @@ pub enum SectionHeaderOption<'a> {
 
 impl<'a> SectionHeaderOption<'a> {
 
-    fn from_slice<B:ByteOrder>(slice: &'a [u8]) -> Result<(&'a [u8], Vec<Self>), PcapError> {
+    fn from_slice<B:ByteOrder>(slice: &'a [u8], mode: Utf8Mode) -> Result<(&'a [u8], Vec<Self>), PcapError> {
 
         opts_from_slice::<B, _, _>(slice, |slice, code, length| {
 
             let opt = match code {
 
-                1 => SectionHeaderOption::Comment(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                2 => SectionHeaderOption::Hardware(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                3 => SectionHeaderOption::OS(Cow::Borrowed(std::str::from_utf8(slice)?)),
-                4 => SectionHeaderOption::UserApplication(Cow::Borrowed(std::str::from_utf8(slice)?)),
+                1 => SectionHeaderOption::Comment(str_from_slice(slice, mode)?),
+                2 => SectionHeaderOption::Hardware(str_from_slice(slice, mode)?),
+                3 => SectionHeaderOption::OS(str_from_slice(slice, mode)?),
+                4 => SectionHeaderOption::UserApplication(str_from_slice(slice, mode)?),
 
-                2988 | 19372 => SectionHeaderOption::CustomUtf8(CustomUtf8Option::from_slice::<B>(code, slice)?),
-                2989 | 19373 => SectionHeaderOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
+                OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE => SectionHeaderOption::CustomUtf8(CustomUtf8Option::from_slice_with::<B>(code, slice, mode)?),
+                OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE => SectionHeaderOption::CustomBinary(CustomBinaryOption::from_slice::<B>(code, slice)?),
 
                 _ => SectionHeaderOption::Unknown(UnknownOption::new(code, length, slice))
             };
@@ -133,6 +273,58 @@ impl<'a> SectionHeaderOption<'a> {
             Ok(opt)
         })
     }
+
+    /// Write the option, its padding included, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        match self {
+            SectionHeaderOption::Comment(a) => opt_to_writer::<_, B>(writer, 1, a.as_bytes()),
+            SectionHeaderOption::Hardware(a) => opt_to_writer::<_, B>(writer, 2, a.as_bytes()),
+            SectionHeaderOption::OS(a) => opt_to_writer::<_, B>(writer, 3, a.as_bytes()),
+            SectionHeaderOption::UserApplication(a) => opt_to_writer::<_, B>(writer, 4, a.as_bytes()),
+            SectionHeaderOption::CustomBinary(a) => a.write_to::<_, B>(writer),
+            SectionHeaderOption::CustomUtf8(a) => a.write_to::<_, B>(writer),
+            SectionHeaderOption::Unknown(a) => a.write_to::<_, B>(writer)
+        }
+    }
+
+    /// Returns the number of bytes returned by `write_to()`, whatever the byte order.
+    pub fn serialized_len(&self) -> usize {
+        match self {
+            SectionHeaderOption::Comment(a) => opt_len(a.len()),
+            SectionHeaderOption::Hardware(a) => opt_len(a.len()),
+            SectionHeaderOption::OS(a) => opt_len(a.len()),
+            SectionHeaderOption::UserApplication(a) => opt_len(a.len()),
+            SectionHeaderOption::CustomBinary(a) => a.serialized_len(),
+            SectionHeaderOption::CustomUtf8(a) => a.serialized_len(),
+            SectionHeaderOption::Unknown(a) => a.serialized_len()
+        }
+    }
+}
+
+impl<'a> HasOptions<'a> for SectionHeaderBlock<'a> {
+    type Option = SectionHeaderOption<'a>;
+
+    fn options(&self) -> &[SectionHeaderOption<'a>] {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut Vec<SectionHeaderOption<'a>> {
+        &mut self.options
+    }
 }
 
+impl<'a> CommentOption<'a> for SectionHeaderOption<'a> {
+
+    fn comment(&self) -> Option<&str> {
+        match self {
+            SectionHeaderOption::Comment(comment) => Some(comment),
+            _ => None
+        }
+    }
 
+    fn from_comment(comment: Cow<'a, str>) -> Self {
+        SectionHeaderOption::Comment(comment)
+    }
+}
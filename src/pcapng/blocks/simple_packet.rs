@@ -1,23 +1,47 @@
 use crate::errors::PcapError;
-use byteorder::{ByteOrder, ReadBytesExt};
+use crate::common::ReadSlice;
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::pcapng::framed_len;
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 
 /// The Simple Packet Block (SPB) is a lightweight container for storing the packets coming from the network.
 /// Its presence is optional.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct SimplePacketBlock<'a> {
 
     /// Actual length of the packet when it was transmitted on the network.
     pub original_len: u32,
 
     /// The data coming from the network, including link-layer headers.
+    ///
+    /// Its length is the minimum of `original_len` and the space available in the block,
+    /// the snaplen of the interface is not applied.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub data: Cow<'a, [u8]>
 }
 
 impl<'a> SimplePacketBlock<'a> {
 
+    /// Reads a whole `SimplePacketBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<SimplePacketBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::SimplePacket(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("SimplePacketBlock::from_reader: not a SimplePacketBlock"))
+        }
+    }
+
     pub fn from_slice<B: ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a [u8], Self), PcapError> {
 
         if slice.len() < 4 {
@@ -25,11 +49,56 @@ impl<'a> SimplePacketBlock<'a> {
         }
         let original_len = slice.read_u32::<B>()?;
 
+        // The captured length is not stored: the data is either the whole packet followed by its padding
+        // or a packet truncated to the snaplen which fills the block
+        let captured_len = core::cmp::min(original_len as usize, slice.len());
+
         let packet = SimplePacketBlock {
             original_len,
-            data: Cow::Borrowed(slice)
+            data: Cow::Borrowed(&slice[..captured_len])
         };
 
         Ok((&[], packet))
     }
+
+    /// Returns the actual length of the packet when it was transmitted on the network.
+    pub fn original_len(&self) -> u32 {
+        self.original_len
+    }
+
+    /// Returns the captured data, without the padding.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns true if the packet has been truncated, i.e. the captured data is shorter than `original_len`.
+    pub fn is_truncated(&self) -> bool {
+        (self.data.len() as u64) < self.original_len as u64
+    }
+
+    /// Convert a borrowed `SimplePacketBlock` to an owned one.
+    pub fn to_owned(&self) -> SimplePacketBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Write the body of the block: the original length and the padded packet data.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let pad_len = (4 - (self.data.len() % 4)) % 4;
+
+        writer.write_u32::<B>(self.original_len)?;
+        writer.write_all(&self.data)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        Ok(4 + self.data.len() + pad_len)
+    }
+
+    /// Returns the number of bytes written for the packet by a `PcapNgWriter`, before any truncation to the snaplen:
+    /// the framing, the original length and the padded data.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(4 + self.data.len())
+    }
 }
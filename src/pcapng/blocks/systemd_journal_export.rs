@@ -1,20 +1,42 @@
 use crate::errors::PcapError;
-use byteorder::ByteOrder;
+use byteorder::{ByteOrder, LittleEndian};
+use crate::pcapng::framed_len;
+#[cfg(feature = "std")]
+use crate::pcapng::ParsedBlock;
 use std::borrow::Cow;
 use derive_into_owned::IntoOwned;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
 
 /// The systemd Journal Export Block is a lightweight containter for systemd Journal Export Format entry data.
-#[derive(Clone, Debug, IntoOwned)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[derive(Clone, Debug, Eq, PartialEq, IntoOwned)]
 pub struct SystemdJournalExportBlock<'a> {
 
-    /// A journal entry as described in the Journal Export Format documentation.
+    /// A journal entry as described in the Journal Export Format documentation, without the padding.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_impl::hex"))]
     pub journal_entry: Cow<'a, [u8]>,
 }
 
 impl<'a> SystemdJournalExportBlock<'a> {
 
-    pub fn from_slice<B: ByteOrder>(slice: &'a [u8]) -> Result<(&'a[u8], Self), PcapError> {
+    /// Reads a whole `SystemdJournalExportBlock`, its framing included, and returns it owned.
+    ///
+    /// Returns `PcapError::InvalidField` if the block read is of another type.
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read, B: ByteOrder>(reader: &mut R) -> Result<SystemdJournalExportBlock<'static>, PcapError> {
+        match ParsedBlock::from_reader::<_, B>(reader)? {
+            ParsedBlock::SystemdJournalExport(block) => Ok(block),
+            _ => Err(PcapError::InvalidField("SystemdJournalExportBlock::from_reader: not a SystemdJournalExportBlock"))
+        }
+    }
+
+    pub fn from_slice<B: ByteOrder>(mut slice: &'a [u8]) -> Result<(&'a[u8], Self), PcapError> {
+
+        // An entry always ends with a newline, the trailing zeros are the padding
+        let pad_len = slice.iter().rev().take(3).take_while(|&&b| b == 0).count();
+        slice = &slice[..slice.len() - pad_len];
 
         let packet = SystemdJournalExportBlock {
             journal_entry: Cow::Borrowed(slice),
@@ -22,4 +44,84 @@ impl<'a> SystemdJournalExportBlock<'a> {
 
         Ok((&[], packet))
     }
+
+    /// Write the body of the block: the padded journal entry.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write, B: ByteOrder>(&self, writer: &mut W) -> Result<usize, PcapError> {
+
+        let pad_len = (4 - (self.journal_entry.len() % 4)) % 4;
+
+        writer.write_all(&self.journal_entry)?;
+        writer.write_all(&[0_u8; 3][..pad_len])?;
+
+        Ok(self.journal_entry.len() + pad_len)
+    }
+
+    /// Returns the number of bytes written for the block by a `PcapNgWriter`: the framing and the padded entry.
+    pub fn serialized_len(&self) -> usize {
+        framed_len(self.journal_entry.len())
+    }
+
+    /// Convert a borrowed `SystemdJournalExportBlock` to an owned one.
+    pub fn to_owned(&self) -> SystemdJournalExportBlock<'static> {
+        self.clone().into_owned()
+    }
+
+    /// Returns an iterator over the records of the journal export, each record being separated by an empty line.
+    ///
+    /// The records are returned without their trailing empty line. The binary fields,
+    /// whose value is prefixed by its 64-bit little endian length, can contain empty lines and are skipped as a whole.
+    pub fn entries(&self) -> impl Iterator<Item = &[u8]> {
+
+        let mut rem = &self.journal_entry[..];
+
+        core::iter::from_fn(move || {
+
+            if rem.is_empty() {
+                return None;
+            }
+
+            let (entry, next) = split_entry(rem);
+            rem = next;
+
+            Some(entry)
+        })
+    }
+}
+
+/// Splits the first record of a journal export and returns it with the remainder.
+fn split_entry(data: &[u8]) -> (&[u8], &[u8]) {
+
+    let mut pos = 0;
+
+    while pos < data.len() {
+
+        // An empty line ends the record
+        if data[pos] == b'\n' {
+            return (&data[..pos], &data[pos + 1..]);
+        }
+
+        let line_end = data[pos..].iter()
+            .position(|&b| b == b'\n')
+            .map(|i| pos + i)
+            .unwrap_or(data.len());
+
+        if data[pos..line_end].contains(&b'=') {
+            // Text field: FIELD=value\n
+            pos = line_end + 1;
+        }
+        else {
+            // Binary field: FIELD\n followed by the length, the value and a newline
+            let len_start = line_end + 1;
+            let len = data.get(len_start..len_start + 8)
+                .map(LittleEndian::read_u64)
+                .unwrap_or(u64::MAX);
+
+            pos = (len_start as u64 + 8).saturating_add(len).saturating_add(1).min(data.len() as u64) as usize;
+        }
+    }
+
+    (data, &[])
 }
@@ -0,0 +1,72 @@
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use crate::errors::PcapError;
+use crate::TsResolution;
+use crate::pcap::PcapReader;
+use crate::pcapng::{EnhancedPacketBlock, InterfaceDescriptionBlock, PcapNgWriter, SectionHeaderBlock};
+
+/// Converts a Pcap stream to a PcapNg stream.
+///
+/// The PcapNg stream contains a SectionHeaderBlock in the endianness of the Pcap stream,
+/// a single InterfaceDescriptionBlock with the datalink and the snaplen of the Pcap header
+/// and one EnhancedPacketBlock per packet.
+///
+/// The `if_tsresol` option of the interface matches the timestamp resolution of the Pcap stream
+/// (6 for microseconds, 9 for nanoseconds) so the timestamps are preserved exactly.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::pcapng::pcap_to_pcapng;
+///
+/// let file_in = File::open("test.pcap").expect("Error opening file");
+/// let file_out = File::create("test.pcapng").expect("Error creating file");
+///
+/// pcap_to_pcapng(file_in, file_out).unwrap();
+/// ```
+pub fn pcap_to_pcapng<R: Read, W: Write>(reader: R, writer: W) -> Result<(), PcapError> {
+
+    let pcap_reader = PcapReader::new(reader)?;
+    let header = pcap_reader.header;
+
+    let mut section = SectionHeaderBlock::default();
+    section.set_endianness(header.endianness());
+
+    let mut pcapng_writer = PcapNgWriter::with_section_header(section, writer)?;
+
+    let (tsresol, units_per_second) = match header.ts_resolution() {
+        TsResolution::MicroSecond => (6, 1_000_000),
+        TsResolution::NanoSecond => (9, 1_000_000_000)
+    };
+
+    let interface = InterfaceDescriptionBlock::builder()
+        .linktype(header.datalink)
+        .snaplen(header.snaplen)
+        .tsresol(tsresol)
+        .build();
+
+    let interface_id = pcapng_writer.write_interface(&interface)?;
+
+    for packet in pcap_reader {
+
+        let packet = packet?;
+
+        // The reader always converts the fractional part to nanoseconds
+        let fraction = packet.header.ts_nsec as u64 / (1_000_000_000 / units_per_second);
+        let timestamp = packet.header.ts_sec as u64 * units_per_second + fraction;
+
+        let block = EnhancedPacketBlock {
+            interface_id,
+            timestamp,
+            captured_len: packet.data.len() as u32,
+            original_len: packet.header.orig_len,
+            data: Cow::Borrowed(&packet.data),
+            options: vec![]
+        };
+
+        pcapng_writer.write_enhanced_packet(&block)?;
+    }
+
+    Ok(())
+}
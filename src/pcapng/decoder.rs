@@ -0,0 +1,160 @@
+use byteorder::{BigEndian, ByteOrder};
+use crate::errors::PcapError;
+use crate::Endianness;
+use crate::pcapng::reader::update_section;
+use crate::pcapng::{Block, BlockType, InterfaceDescriptionBlock, PcapNgReader, SectionHeaderBlock};
+
+/// Push-style decoder of a PcapNg formated stream, for data received in chunks, e.g. from a message bus.
+///
+/// The chunks are given to `push()` as they arrive, in any size, and buffered until they contain a whole block.
+/// `next_block()` then returns the complete blocks one by one, or `Ok(None)` when more data is needed.
+/// The length of each block is known from its header, so the decoder never has to guess where a block ends.
+///
+/// As for the `PcapNgReader`, the first block must be a SectionHeaderBlock, each SectionHeaderBlock sets the byte order
+/// of the blocks of its section, and blocks longer than `max_block_len` are rejected with a `PcapError::BlockTooLarge`
+/// as soon as their header is received.
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the block which failed.
+/// A block which fails is not consumed, so the following calls return the same error.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pcap_file::pcapng::PcapNgDecoder;
+///
+/// # let messages: Vec<Vec<u8>> = vec![];
+/// let mut decoder = PcapNgDecoder::new();
+///
+/// for message in messages {
+///
+///     decoder.push(&message);
+///
+///     // Decode all the blocks completed by the message
+///     while let Some(block) = decoder.next_block().unwrap() {
+///
+///         //Parse block content
+///         let parsed_block = block.parsed().unwrap();
+///
+///         //Do something
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PcapNgDecoder {
+    buffer: Vec<u8>,
+    section: Option<SectionHeaderBlock<'static>>,
+    interfaces: Vec<InterfaceDescriptionBlock<'static>>,
+    offset: u64,
+    max_block_len: u32
+}
+
+impl PcapNgDecoder {
+
+    /// Creates a new `PcapNgDecoder`.
+    ///
+    /// The blocks are limited to `PcapNgReader::DEFAULT_MAX_BLOCK_LEN` bytes.
+    pub fn new() -> PcapNgDecoder {
+        PcapNgDecoder::with_max_block_len(PcapNgReader::<&[u8]>::DEFAULT_MAX_BLOCK_LEN)
+    }
+
+    /// Creates a new `PcapNgDecoder` which rejects the blocks longer than `max_block_len` bytes, framing included.
+    pub fn with_max_block_len(max_block_len: u32) -> PcapNgDecoder {
+        PcapNgDecoder {
+            buffer: vec![],
+            section: None,
+            interfaces: vec![],
+            offset: 0,
+            max_block_len
+        }
+    }
+
+    /// Appends data received from the stream to the buffer of the decoder.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Returns the next complete block of the buffer, or `Ok(None)` if more data is needed.
+    ///
+    /// A SectionHeaderBlock starts a new section: its endianness is used for the following blocks
+    /// and the current interfaces are cleared.
+    pub fn next_block(&mut self) -> Result<Option<Block<'static>>, PcapError> {
+
+        let offset = self.offset;
+        self.next_impl().map_err(|err| err.at_offset(offset))
+    }
+
+    fn next_impl(&mut self) -> Result<Option<Block<'static>>, PcapError> {
+
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+
+        let is_section = BigEndian::read_u32(&self.buffer[..4]) == BlockType::SectionHeader.as_u32();
+        let endianness = match &self.section {
+            Some(section) => section.endianness(),
+            None if is_section => Endianness::Big,
+            None => return Err(PcapError::InvalidField("SectionHeader missing"))
+        };
+
+        // The framing is the only part decoded, so an incomplete buffer means the block is not fully received
+        let block = match Block::from_slice_endian(&self.buffer, endianness) {
+            Ok((_, block)) => block.into_owned(),
+            Err(PcapError::IncompleteBuffer(needed)) => {
+                let len = self.buffer.len() + needed;
+                if len > self.max_block_len as usize {
+                    return Err(PcapError::BlockTooLarge { len: len as u32, max: self.max_block_len });
+                }
+                return Ok(None);
+            },
+            Err(err) => return Err(err)
+        };
+
+        if block.initial_len > self.max_block_len {
+            return Err(PcapError::BlockTooLarge { len: block.initial_len, max: self.max_block_len });
+        }
+
+        match &mut self.section {
+            Some(section) => update_section(section, &mut self.interfaces, &block)?,
+            None => {
+                let mut section = SectionHeaderBlock::default();
+                update_section(&mut section, &mut self.interfaces, &block)?;
+                self.section = Some(section);
+            }
+        }
+
+        self.buffer.drain(..block.initial_len as usize);
+        self.offset += block.initial_len as u64;
+
+        Ok(Some(block))
+    }
+
+    /// Returns the maximum length of a block accepted by the decoder
+    pub fn max_block_len(&self) -> u32 {
+        self.max_block_len
+    }
+
+    /// Returns the number of bytes received but not decoded yet
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the current SectionHeaderBlock, or `None` if the first one is not decoded yet
+    pub fn section(&self) -> Option<&SectionHeaderBlock<'static>> {
+        self.section.as_ref()
+    }
+
+    /// Returns the current interfaces
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
+        &self.interfaces[..]
+    }
+
+    /// Returns the InterfaceDescriptionBlock of the current section whose id is `interface_id`.
+    pub fn interface(&self, interface_id: u32) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interfaces.get(interface_id as usize)
+    }
+}
+
+impl Default for PcapNgDecoder {
+    fn default() -> Self {
+        PcapNgDecoder::new()
+    }
+}
@@ -0,0 +1,160 @@
+use std::io::{Read, Seek, SeekFrom};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use crate::errors::PcapError;
+use crate::Endianness;
+use crate::pcapng::{Block, BlockType};
+
+/// Position of a block in a PcapNg stream.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BlockIndexEntry {
+
+    /// Offset of the first byte of the block from the start of the stream
+    pub offset: u64,
+
+    /// Raw type of the block
+    pub type_: u32,
+
+    /// Total length of the block, framing included
+    pub len: u32
+}
+
+impl BlockIndexEntry {
+
+    /// Returns the type of the block
+    pub fn block_type(&self) -> BlockType {
+        self.type_.into()
+    }
+}
+
+/// Builds an index of the blocks of a PcapNg stream.
+///
+/// Only the block types and lengths are read, the bodies are skipped without being parsed or stored.
+/// The leading and trailing block lengths are checked to be equal.
+///
+/// # Errors
+///
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the block which failed.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::Endianness;
+/// use pcap_file::pcapng::{index, read_block_at};
+///
+/// let mut file_in = File::open("test.pcapng").expect("Error opening file");
+/// let entries = index(&mut file_in).unwrap();
+///
+/// // Jump to the last block, assuming the file contains a single section
+/// let last = entries.last().unwrap();
+/// let section = read_block_at(&mut file_in, entries[0].offset, Endianness::Big).unwrap();
+/// let block = read_block_at(&mut file_in, last.offset, section.endianness()).unwrap();
+/// ```
+pub fn index<R: Read>(reader: &mut R) -> Result<Vec<BlockIndexEntry>, PcapError> {
+
+    let mut entries = vec![];
+    let mut endianness = Endianness::Big;
+    let mut offset = 0;
+
+    loop {
+
+        let mut type_ = [0_u8; 4];
+        if read_or_eof(reader, &mut type_).map_err(|err| PcapError::from(err).at_offset(offset))? {
+            break;
+        }
+
+        let entry = index_block(reader, type_, &mut endianness, offset).map_err(|err| err.at_offset(offset))?;
+        offset += entry.len as u64;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Reads the block starting at `offset`.
+///
+/// `endianness` is the endianness of the section containing the block,
+/// it is ignored if the block is a SectionHeaderBlock.
+///
+/// # Errors
+///
+/// Return an error if the reader can't be seeked or if the block is invalid.
+pub fn read_block_at<R: Read + Seek>(reader: &mut R, offset: u64, endianness: Endianness) -> Result<Block<'static>, PcapError> {
+
+    reader.seek(SeekFrom::Start(offset))?;
+
+    match endianness {
+        Endianness::Big => Block::from_reader::<_, BigEndian>(reader),
+        Endianness::Little => Block::from_reader::<_, LittleEndian>(reader)
+    }
+}
+
+/// Reads the length of the block, skips its body and checks its trailing length.
+///
+/// A SectionHeaderBlock defines the endianness of its section.
+fn index_block<R: Read>(reader: &mut R, type_: [u8; 4], endianness: &mut Endianness, offset: u64) -> Result<BlockIndexEntry, PcapError> {
+
+    let mut len = [0_u8; 4];
+    reader.read_exact(&mut len)?;
+
+    let mut skipped = 0;
+    if BigEndian::read_u32(&type_) == BlockType::SectionHeader.as_u32() {
+
+        *endianness = match reader.read_u32::<BigEndian>()? {
+            0x1A2B3C4D => Endianness::Big,
+            0x4D3C2B1A => Endianness::Little,
+            _ => return Err(PcapError::InvalidField("SectionHeaderBlock: invalid magic number"))
+        };
+
+        skipped = 4;
+    }
+
+    match *endianness {
+        Endianness::Big => skip_block::<_, BigEndian>(reader, type_, len, skipped, offset),
+        Endianness::Little => skip_block::<_, LittleEndian>(reader, type_, len, skipped, offset)
+    }
+}
+
+fn skip_block<R: Read, B: ByteOrder>(reader: &mut R, type_: [u8; 4], len: [u8; 4], skipped: u64, offset: u64) -> Result<BlockIndexEntry, PcapError> {
+
+    let type_ = B::read_u32(&type_);
+    let len = B::read_u32(&len);
+
+    if (len % 4) != 0 {
+        return Err(PcapError::InvalidField("Block: (initial_len % 4) != 0"));
+    }
+
+    if (len as u64) < 12 + skipped {
+        return Err(PcapError::InvalidField("Block: initial_len < 12"));
+    }
+
+    let body_len = len as u64 - 12 - skipped;
+    if std::io::copy(&mut reader.by_ref().take(body_len), &mut std::io::sink())? != body_len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let trailer_len = reader.read_u32::<B>()?;
+    if len != trailer_len {
+        return Err(PcapError::BlockLengthMismatch { start: len, end: trailer_len });
+    }
+
+    Ok(BlockIndexEntry { offset, type_, len })
+}
+
+/// Fills `buf`, returns `true` if the reader was already at the end of the stream.
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<bool> {
+
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(true),
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {},
+            Err(err) => return Err(err)
+        }
+    }
+
+    Ok(false)
+}
@@ -0,0 +1,281 @@
+use std::io::{Read, Write};
+use std::time::Duration;
+use byteorder::BigEndian;
+use crate::errors::PcapError;
+use crate::pcapng::{BlockType, DecryptionSecretsOption, EnhancedPacketBlock, EnhancedPacketOption, InterfaceDescriptionBlock, InterfaceDescriptionOption, InterfaceStatisticsOption, NameResolutionOption, PacketOption, ParsedBlock, PcapNgReader, PcapNgWriter};
+use crate::pcapng::options::HasOptions;
+
+/// Concatenates several PcapNg streams into a single section, like `mergecap -a`.
+///
+/// The interfaces of all the inputs are gathered in a single interface table and the `interface_id`
+/// of the packet and statistics blocks is rewritten to reference it. An interface identical to one
+/// already written by a previous input is reused instead of being written again, the interfaces
+/// of a same input section always stay distinct.
+///
+/// The output is written in the native endianness of the CPU with a default SectionHeaderBlock,
+/// the SectionHeaderBlocks of the inputs are dropped.
+/// A SimplePacketBlock, which implicitly references the first interface of its section,
+/// is converted to an EnhancedPacketBlock with a null timestamp.
+/// The custom blocks which must not be copied (`BlockType::CustomNonCopyable`) and the custom options
+/// which must not be copied (codes 19372 and 19373) are dropped, the other blocks are copied as is.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::pcapng::merge_pcapng;
+///
+/// let inputs = vec![
+///     File::open("first.pcapng").expect("Error opening file"),
+///     File::open("second.pcapng").expect("Error opening file")
+/// ];
+/// let file_out = File::create("merged.pcapng").expect("Error creating file");
+///
+/// merge_pcapng(inputs, file_out).unwrap();
+/// ```
+pub fn merge_pcapng<I, R, W>(inputs: I, output: W) -> Result<(), PcapError>
+    where I: IntoIterator<Item = R>,
+          R: Read,
+          W: Write
+{
+    let mut pcapng_writer = PcapNgWriter::new(output)?;
+
+    // Encoded body of each written interface, to find the identical ones
+    let mut written: Vec<Vec<u8>> = vec![];
+
+    for input in inputs {
+
+        let mut pcapng_reader = PcapNgReader::new(input)?;
+        let mut ids = vec![];
+
+        while let Some(block) = pcapng_reader.next_block() {
+
+            let block = block?;
+            if let Some(parsed) = merge_block(&mut pcapng_writer, &mut written, &mut ids, block.type_, block.parsed()?)? {
+                pcapng_writer.write_block(&parsed)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Merges several PcapNg streams into a single section, interleaving their packets in ascending timestamp order,
+/// like `mergecap`.
+///
+/// The interfaces and the blocks are handled as in `merge_pcapng`. The timestamp of each EnhancedPacketBlock
+/// and PacketBlock is resolved to an absolute time with the `if_tsresol` and `if_tsoffset` options
+/// of its interface, see `InterfaceDescriptionBlock::resolve_timestamp()`, so inputs with different resolutions
+/// or offsets are compared on the same scale, to the nanosecond. The raw timestamps are written unchanged,
+/// the output interfaces keep the resolution and the offset of their input.
+///
+/// Each input is expected to be sorted: the packets of an input are written in their order of the input.
+/// When two packets have the same time, the packet of the first input is written first.
+/// The other blocks (interfaces, statistics, SimplePacketBlocks which have no timestamp...)
+/// are written as soon as they are read, before the next packet of their input.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::pcapng::merge_pcapng_chronological;
+///
+/// let inputs = vec![
+///     File::open("sensor_a.pcapng").expect("Error opening file"),
+///     File::open("sensor_b.pcapng").expect("Error opening file")
+/// ];
+/// let file_out = File::create("merged.pcapng").expect("Error creating file");
+///
+/// merge_pcapng_chronological(inputs, file_out).unwrap();
+/// ```
+pub fn merge_pcapng_chronological<I, R, W>(inputs: I, output: W) -> Result<(), PcapError>
+    where I: IntoIterator<Item = R>,
+          R: Read,
+          W: Write
+{
+    let mut pcapng_writer = PcapNgWriter::new(output)?;
+    let mut written: Vec<Vec<u8>> = vec![];
+
+    let mut inputs = inputs.into_iter()
+        .map(|input| Ok(Input { pcapng_reader: PcapNgReader::new(input)?, ids: vec![], next_packet: None }))
+        .collect::<Result<Vec<_>, PcapError>>()?;
+
+    for input in inputs.iter_mut() {
+        input.advance(&mut pcapng_writer, &mut written)?;
+    }
+
+    // k-way merge: the first input with the earliest pending packet is written then advanced
+    loop {
+
+        let next = inputs.iter_mut()
+            .filter(|input| input.next_packet.is_some())
+            .min_by_key(|input| input.next_packet.as_ref().map(|(time, _)| *time));
+
+        let input = match next {
+            Some(input) => input,
+            None => break
+        };
+
+        let (_, packet) = input.next_packet.take().unwrap();
+        pcapng_writer.write_block(&packet)?;
+
+        input.advance(&mut pcapng_writer, &mut written)?;
+    }
+
+    Ok(())
+}
+
+/// Input of `merge_pcapng_chronological`
+struct Input<R: Read> {
+    pcapng_reader: PcapNgReader<R>,
+
+    /// Output id of each interface of the current input section
+    ids: Vec<u32>,
+
+    /// Next packet of the input, already remapped, with its absolute time
+    next_packet: Option<(Duration, ParsedBlock<'static>)>
+}
+
+impl<R: Read> Input<R> {
+
+    /// Reads the input up to its next timestamped packet, writing the other blocks on the way.
+    fn advance<W: Write>(&mut self, pcapng_writer: &mut PcapNgWriter<W>, written: &mut Vec<Vec<u8>>) -> Result<(), PcapError> {
+
+        while let Some(block) = self.pcapng_reader.next_block() {
+
+            let block = block?;
+            let parsed = block.parsed()?;
+
+            let raw_time = match &parsed {
+                ParsedBlock::EnhancedPacket(packet) => Some((packet.interface_id, packet.timestamp)),
+                ParsedBlock::Packet(packet) => Some((packet.interface_id as u32, packet.timestamp)),
+                _ => None
+            };
+
+            let time = match raw_time {
+                Some((interface_id, timestamp)) => match self.pcapng_reader.interface(interface_id) {
+                    Some(interface) => Some(interface.resolve_timestamp(timestamp)),
+                    None => return Err(PcapError::InvalidField("merge_pcapng: interface_id does not reference an interface of the section"))
+                },
+                None => None
+            };
+
+            let parsed = match merge_block(pcapng_writer, written, &mut self.ids, block.type_, parsed)? {
+                Some(parsed) => parsed,
+                None => continue
+            };
+
+            match time {
+                Some(time) => {
+                    self.next_packet = Some((time, parsed.into_owned()));
+                    return Ok(());
+                },
+                None => pcapng_writer.write_block(&parsed)?
+            };
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the custom options of a block which must not be copied to a new file
+macro_rules! retain_copyable {
+    ($block:expr, $option:ident) => {
+        $block.options_mut().retain(|opt| match opt {
+            $option::CustomBinary(opt) => opt.is_copyable(),
+            $option::CustomUtf8(opt) => opt.is_copyable(),
+            _ => true
+        })
+    };
+}
+
+/// Updates the interfaces with a block of an input and returns the block to write, with its `interface_id` remapped.
+///
+/// `ids` is the output id of each interface of the current input section.
+/// Returns `None` if the block must not be written.
+fn merge_block<'b, W: Write>(pcapng_writer: &mut PcapNgWriter<W>, written: &mut Vec<Vec<u8>>, ids: &mut Vec<u32>, type_: BlockType, parsed: ParsedBlock<'b>) -> Result<Option<ParsedBlock<'b>>, PcapError> {
+
+    let parsed = match parsed {
+        ParsedBlock::SectionHeader(_) => {
+            ids.clear();
+            return Ok(None);
+        },
+        ParsedBlock::InterfaceDescription(mut interface) => {
+            retain_copyable!(interface, InterfaceDescriptionOption);
+            let id = merge_interface(pcapng_writer, written, ids, &interface)?;
+            ids.push(id);
+            return Ok(None);
+        },
+        ParsedBlock::EnhancedPacket(mut packet) => {
+            retain_copyable!(packet, EnhancedPacketOption);
+            packet.interface_id = output_id(ids, packet.interface_id)?;
+            ParsedBlock::EnhancedPacket(packet)
+        },
+        ParsedBlock::Packet(mut packet) => {
+            retain_copyable!(packet, PacketOption);
+            let id = output_id(ids, packet.interface_id as u32)?;
+            if id > u16::MAX as u32 {
+                return Err(PcapError::InvalidField("merge_pcapng: PacketBlock interface_id > u16::MAX"));
+            }
+            packet.interface_id = id as u16;
+            ParsedBlock::Packet(packet)
+        },
+        ParsedBlock::SimplePacket(packet) => {
+            let packet = EnhancedPacketBlock {
+                interface_id: output_id(ids, 0)?,
+                timestamp: 0,
+                captured_len: packet.data.len() as u32,
+                original_len: packet.original_len,
+                data: packet.data,
+                options: vec![]
+            };
+            ParsedBlock::EnhancedPacket(packet)
+        },
+        ParsedBlock::InterfaceStatistics(mut stats) => {
+            retain_copyable!(stats, InterfaceStatisticsOption);
+            stats.interface_id = output_id(ids, stats.interface_id)?;
+            ParsedBlock::InterfaceStatistics(stats)
+        },
+        ParsedBlock::Custom(_) if type_ == BlockType::CustomNonCopyable => return Ok(None),
+        ParsedBlock::NameResolution(mut resolution) => {
+            retain_copyable!(resolution, NameResolutionOption);
+            ParsedBlock::NameResolution(resolution)
+        },
+        ParsedBlock::DecryptionSecrets(mut secrets) => {
+            retain_copyable!(secrets, DecryptionSecretsOption);
+            ParsedBlock::DecryptionSecrets(secrets)
+        },
+        parsed => parsed
+    };
+
+    Ok(Some(parsed))
+}
+
+/// Returns the output id of `interface`, writing it if no identical interface can be reused.
+///
+/// `ids` are the output ids already used by the current input section, which can't be reused.
+fn merge_interface<W: Write>(pcapng_writer: &mut PcapNgWriter<W>, written: &mut Vec<Vec<u8>>, ids: &[u32], interface: &InterfaceDescriptionBlock) -> Result<u32, PcapError> {
+
+    let mut body = vec![];
+    interface.write_to::<_, BigEndian>(&mut body)?;
+
+    let identical = written.iter()
+        .enumerate()
+        .find(|(id, written)| **written == body && !ids.contains(&(*id as u32)))
+        .map(|(id, _)| id);
+
+    match identical {
+        Some(id) => Ok(id as u32),
+        None => {
+            written.push(body);
+            pcapng_writer.write_interface(interface)
+        }
+    }
+}
+
+fn output_id(ids: &[u32], interface_id: u32) -> Result<u32, PcapError> {
+    ids.get(interface_id as usize)
+        .copied()
+        .ok_or(PcapError::InvalidField("merge_pcapng: interface_id does not reference an interface of the section"))
+}
+
@@ -1,8 +1,54 @@
+#[cfg(feature = "tokio")]
+pub(crate) mod async_reader;
+#[cfg(feature = "tokio")]
+pub use async_reader::*;
+
 pub(crate) mod blocks;
 pub use blocks::*;
 
+#[cfg(feature = "std")]
+pub(crate) mod convert;
+#[cfg(feature = "std")]
+pub use convert::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod decoder;
+#[cfg(feature = "std")]
+pub use decoder::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod index;
+#[cfg(feature = "std")]
+pub use index::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod merge;
+#[cfg(feature = "std")]
+pub use merge::*;
+
+pub mod options;
+
+#[cfg(feature = "rayon")]
+pub(crate) mod parallel;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+
+#[cfg(feature = "std")]
 pub(crate) mod parser;
+#[cfg(feature = "std")]
 pub use parser::*;
 
+#[cfg(feature = "std")]
 pub(crate) mod reader;
-pub use reader::*;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub use reader::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod split;
+#[cfg(feature = "std")]
+pub use split::*;
+
+#[cfg(feature = "std")]
+pub(crate) mod writer;
+#[cfg(feature = "std")]
+pub use writer::*;
\ No newline at end of file
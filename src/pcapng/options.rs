@@ -0,0 +1,318 @@
+//! Helpers to read and write the options of the PcapNg blocks.
+//!
+//! All the PcapNg options share the same encoding:
+//!
+//! ```text
+//!  0                   1                   2                   3
+//!  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! |      Option Code              |         Option Length         |
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! /                       Option Value                            /
+//! /              variable length, padded to 32 bits               /
+//! +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//! ```
+
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+use crate::prelude::*;
+use crate::errors::{need, PcapError};
+use crate::common::ReadSlice;
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+/// How the UTF-8 string options (comments, interface names...) are decoded.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Utf8Mode {
+
+    /// An invalid UTF-8 string is an error which aborts the parsing of its block
+    #[default]
+    Strict,
+
+    /// The invalid UTF-8 sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`,
+    /// which allows to read captures with e.g. Latin-1 device names
+    Lossy
+}
+
+/// How the length of the counter and timestamp options of an `InterfaceStatisticsBlock` is checked,
+/// see `InterfaceStatisticsBlock::from_slice_with_lengths()`.
+///
+/// `ParsedBlock` and `Block::parsed()` always use `LengthMode::Lenient`, which was the historical behavior of these
+/// options. The fixed-size options of the other blocks, e.g. `IfTsResol` or `IfSpeed`, are always checked strictly:
+/// a value whose length is not the fixed size is an error.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum LengthMode {
+
+    /// A value longer than its fixed size is decoded from its first bytes, a shorter one is an error
+    #[default]
+    Lenient,
+
+    /// A value whose length is not its fixed size is an error which aborts the parsing of its block
+    Strict
+}
+
+/// Code of the end of options marker, which ends the options of a block
+pub const OPT_ENDOFOPT: u16 = 0;
+
+/// Code of the comment option, shared by all the blocks
+pub const OPT_COMMENT: u16 = 1;
+
+/// Code of a custom option containing a UTF-8 string, which can be copied to a new file
+pub const OPT_CUSTOM_UTF8_COPYABLE: u16 = 2988;
+
+/// Code of a custom option containing binary octets, which can be copied to a new file
+pub const OPT_CUSTOM_BINARY_COPYABLE: u16 = 2989;
+
+/// Code of a custom option containing a UTF-8 string, which must not be copied to a new file
+pub const OPT_CUSTOM_UTF8_NON_COPYABLE: u16 = 19372;
+
+/// Code of a custom option containing binary octets, which must not be copied to a new file
+pub const OPT_CUSTOM_BINARY_NON_COPYABLE: u16 = 19373;
+
+/// Returns true if `code` is one of the four custom option codes, which are shared by all the blocks.
+///
+/// # Examples
+///
+/// ```rust
+/// use pcap_file::pcapng::options::{is_custom_option, OPT_COMMENT, OPT_CUSTOM_UTF8_COPYABLE};
+///
+/// assert!(is_custom_option(OPT_CUSTOM_UTF8_COPYABLE));
+/// assert!(!is_custom_option(OPT_COMMENT));
+/// ```
+pub fn is_custom_option(code: u16) -> bool {
+    matches!(code, OPT_CUSTOM_UTF8_COPYABLE | OPT_CUSTOM_BINARY_COPYABLE | OPT_CUSTOM_UTF8_NON_COPYABLE | OPT_CUSTOM_BINARY_NON_COPYABLE)
+}
+
+/// Decodes a UTF-8 string option according to `mode`.
+///
+/// The string is borrowed from the slice, unless it is invalid and decoded with `Utf8Mode::Lossy`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pcap_file::pcapng::options::{str_from_slice, Utf8Mode};
+///
+/// let latin1 = b"eth\xe9";
+/// assert!(str_from_slice(latin1, Utf8Mode::Strict).is_err());
+/// assert_eq!(str_from_slice(latin1, Utf8Mode::Lossy).unwrap(), "eth\u{FFFD}");
+/// ```
+pub fn str_from_slice(slice: &[u8], mode: Utf8Mode) -> Result<Cow<'_, str>, PcapError> {
+    match mode {
+        Utf8Mode::Strict => Ok(Cow::Borrowed(std::str::from_utf8(slice)?)),
+        Utf8Mode::Lossy => Ok(String::from_utf8_lossy(slice))
+    }
+}
+
+/// Parses all the options of a block.
+///
+/// Each option is encoded as a 16-bit code, a 16-bit length and a value of `length` bytes
+/// padded with zeros to a 32-bit boundary. The padding is not included in the length.
+/// Parsing stops at the end of options marker (code 0).
+///
+/// `func` is called with the value (without the padding), the code and the length of each option.
+/// The value is borrowed from `slice` so no allocation is needed to decode it.
+///
+/// The options end at the end of options marker (code 0), which is not returned, or at the end of the slice
+/// if the marker is omitted. An empty slice means that there is no option.
+/// Each value is followed by its padding to 32 bits, which is skipped; the padding of the option
+/// ending the slice can be omitted.
+///
+/// Returns the remainder after the options and the decoded options.
+///
+/// `slice` must end with the body of its block, so that an option can't be read past its block.
+///
+/// The `PcapError::InvalidField` errors returned by `func` are converted to a `PcapError::InvalidOption`
+/// containing the code of the option. The UTF-8 errors are kept as is, with their source.
+///
+/// Returns `PcapError::IncompleteBuffer` with the number of missing bytes
+/// if the length of an option overruns the slice. `Block::parsed()`, whose body is complete,
+/// reports it as a `PcapError::InvalidField` instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use byteorder::LittleEndian;
+/// use pcap_file::pcapng::options::opts_from_slice;
+///
+/// // A vendor specific option (code 0x8000) followed by the end of options marker
+/// let data = [0x00, 0x80, 0x03, 0x00, b'a', b'b', b'c', 0x00, 0x00, 0x00, 0x00, 0x00];
+///
+/// let (rem, opts) = opts_from_slice::<LittleEndian, _, _>(&data[..], |value, code, _length| {
+///     Ok((code, value))
+/// }).unwrap();
+///
+/// assert!(rem.is_empty());
+/// assert_eq!(opts, vec![(0x8000, &b"abc"[..])]);
+/// ```
+pub fn opts_from_slice<'a, B, F, O>(mut slice: &'a [u8], func: F) -> Result<(&'a [u8], Vec<O>), PcapError>
+    where B: ByteOrder,
+          F: Fn(&'a [u8], u16, u16) -> Result<O, PcapError>
+
+{
+    let mut options = vec![];
+
+    loop {
+
+        // The end of options marker can be omitted when the options end with the block
+        if slice.is_empty() {
+            return Ok((slice, options))
+        }
+
+        if slice.len() < 4 {
+            return Err(PcapError::InvalidField("Option: slice.len() < 4"));
+        }
+
+        let code = slice.read_u16::<B>()?;
+        let length = slice.read_u16::<B>()? as usize;
+        let pad_len = (4 - (length % 4)) % 4;
+
+        if code == OPT_ENDOFOPT {
+            return Ok((slice, options));
+        }
+
+        if slice.len() < length {
+            return Err(need(slice.len(), length + pad_len));
+        }
+
+        let tmp_slice = &slice[..length];
+        let opt = func(tmp_slice, code, length as u16).map_err(|err| invalid_option(code, err))?;
+
+        // Jump over the padding, which can be omitted by the last option of the slice
+        let pad_len = pad_len.min(slice.len() - length);
+        slice = &slice[length+pad_len..];
+
+        options.push(opt);
+    }
+}
+
+/// Adds the code of the option to the errors describing an invalid value
+fn invalid_option(code: u16, err: PcapError) -> PcapError {
+    match err {
+        PcapError::InvalidField(reason) => PcapError::InvalidOption { code, reason },
+        err => err
+    }
+}
+
+/// Writes one option, its padding included, and returns the number of bytes written.
+#[cfg(feature = "std")]
+pub fn opt_to_writer<W: Write, B: ByteOrder>(writer: &mut W, code: u16, value: &[u8]) -> Result<usize, PcapError> {
+
+    if value.len() > u16::MAX as usize {
+        return Err(PcapError::InvalidField("Option: value.len() > u16::MAX"));
+    }
+
+    let pad_len = (4 - (value.len() % 4)) % 4;
+
+    writer.write_u16::<B>(code)?;
+    writer.write_u16::<B>(value.len() as u16)?;
+    writer.write_all(value)?;
+    writer.write_all(&[0_u8; 3][..pad_len])?;
+
+    Ok(4 + value.len() + pad_len)
+}
+
+/// Length of a written option whose value is `value_len` bytes long: the 4 bytes header and the value padded to 32 bits.
+pub(crate) fn opt_len(value_len: usize) -> usize {
+    4 + value_len + (4 - (value_len % 4)) % 4
+}
+
+/// Length of the options written by `opts_to_writer()`, the end of options marker included,
+/// `func` returning the length of each option.
+pub(crate) fn opts_len<O, F: Fn(&O) -> usize>(opts: &[O], func: F) -> usize {
+
+    if opts.is_empty() {
+        return 0;
+    }
+
+    opts.iter().map(func).sum::<usize>() + opt_len(0)
+}
+
+/// Writes all the options of a block followed by the end of options marker
+/// and returns the number of bytes written.
+///
+/// `func` is called to write each option. Nothing is written if there is no option.
+#[cfg(feature = "std")]
+pub fn opts_to_writer<'o, W, B, O, F>(writer: &mut W, opts: &'o [O], func: F) -> Result<usize, PcapError>
+    where W: Write,
+          B: ByteOrder,
+          F: Fn(&mut W, &'o O) -> Result<usize, PcapError>
+{
+    if opts.is_empty() {
+        return Ok(0);
+    }
+
+    let mut len = 0;
+    for opt in opts {
+        len += func(writer, opt)?;
+    }
+
+    len += opt_to_writer::<_, B>(writer, OPT_ENDOFOPT, &[])?;
+
+    Ok(len)
+}
+
+/// An option type which has a `Comment` variant (option code 1), which is the case of the options of every block.
+pub trait CommentOption<'a>: Sized {
+
+    /// Returns the comment if the option is a `Comment`
+    fn comment(&self) -> Option<&str>;
+
+    /// Creates a `Comment` option
+    fn from_comment(comment: Cow<'a, str>) -> Self;
+}
+
+/// A block which carries a list of options.
+///
+/// Gives the same access to the comments of all the blocks.
+///
+/// # Examples
+///
+/// ```rust
+/// use pcap_file::pcapng::InterfaceDescriptionBlock;
+/// use pcap_file::pcapng::options::HasOptions;
+///
+/// let mut interface = InterfaceDescriptionBlock::builder().build();
+/// interface.add_comment("uplink");
+/// interface.add_comment("mirrored port");
+///
+/// assert_eq!(interface.comments().collect::<Vec<_>>(), ["uplink", "mirrored port"]);
+/// ```
+pub trait HasOptions<'a> {
+
+    /// Type of the options of the block
+    type Option: CommentOption<'a>;
+
+    /// Returns the options of the block
+    fn options(&self) -> &[Self::Option];
+
+    /// Returns a mutable reference to the options of the block
+    fn options_mut(&mut self) -> &mut Vec<Self::Option>;
+
+    /// Returns an iterator over the comments of the block, in the order of the options.
+    ///
+    /// A block can have several comments.
+    fn comments(&self) -> Comments<'_, Self::Option> {
+        Comments { options: self.options().iter() }
+    }
+
+    /// Appends a comment to the options of the block
+    fn add_comment(&mut self, comment: &str) {
+        self.options_mut().push(Self::Option::from_comment(Cow::Owned(comment.to_owned())));
+    }
+}
+
+/// Iterator over the comments of a block, returned by `HasOptions::comments()`.
+#[derive(Clone, Debug)]
+pub struct Comments<'o, O> {
+    options: std::slice::Iter<'o, O>
+}
+
+impl<'a, 'o, O: CommentOption<'a>> Iterator for Comments<'o, O> {
+    type Item = &'o str;
+
+    fn next(&mut self) -> Option<&'o str> {
+        self.options.by_ref().find_map(|opt| opt.comment())
+    }
+}
@@ -0,0 +1,107 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use rayon::prelude::*;
+use crate::errors::PcapError;
+use crate::Endianness;
+use crate::pcapng::{Block, BlockIndexEntry, BlockType, InterfaceDescriptionBlock};
+use crate::pcapng::options::Utf8Mode;
+
+/// Parses the blocks referenced by `index` in parallel.
+///
+/// `data` is the whole PcapNg stream and `index` its index, built with `index()`.
+/// The endianness of each section is found in a cheap sequential pass over the SectionHeaderBlocks,
+/// then the blocks are parsed independently, in the order of the index.
+///
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the block which failed.
+///
+/// Only available with the `rayon` feature.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use rayon::prelude::*;
+/// use pcap_file::pcapng::{index, par_blocks};
+///
+/// let data = std::fs::read("test.pcapng").expect("Error reading file");
+/// let entries = index(&mut &data[..]).unwrap();
+///
+/// let nb_errors = par_blocks(&data, &entries).filter(|block| block.is_err()).count();
+/// ```
+pub fn par_blocks<'a>(data: &'a [u8], index: &'a [BlockIndexEntry]) -> impl IndexedParallelIterator<Item = Result<Block<'a>, PcapError>> + 'a {
+
+    let mut endianness = Endianness::Big;
+    let endiannesses: Vec<Endianness> = index.iter()
+        .map(|entry| {
+            if entry.block_type() == BlockType::SectionHeader {
+                endianness = section_endianness(data, entry).unwrap_or(endianness);
+            }
+            endianness
+        })
+        .collect();
+
+    index.par_iter()
+        .zip(endiannesses)
+        .map(move |(entry, endianness)| block_at(data, entry, endianness))
+}
+
+/// Returns the interfaces of each section of the stream, in the order of the sections.
+///
+/// Only the SectionHeaderBlocks and the InterfaceDescriptionBlocks are parsed, with `Utf8Mode::Lossy`, so this pass is cheap
+/// and can be done before `par_blocks` to decode the blocks which depend on their interface.
+/// The section of an entry is the number of SectionHeaderBlocks preceding it in the index, itself included.
+///
+/// Only available with the `rayon` feature.
+pub fn section_interfaces<'a>(data: &'a [u8], index: &[BlockIndexEntry]) -> Result<Vec<Vec<InterfaceDescriptionBlock<'a>>>, PcapError> {
+
+    let mut sections: Vec<Vec<InterfaceDescriptionBlock<'a>>> = vec![];
+    let mut endianness = Endianness::Big;
+
+    for entry in index {
+        match entry.block_type() {
+            BlockType::SectionHeader => {
+                let block = block_at(data, entry, endianness)?;
+                endianness = block.endianness();
+                sections.push(vec![]);
+            },
+            BlockType::InterfaceDescription => {
+                // Checks the framing of the block before parsing its body
+                let block = block_at(data, entry, endianness)?;
+
+                let start = entry.offset as usize;
+                let body = &data[start + 8..start + block.initial_len as usize - 4];
+                let res = match endianness {
+                    Endianness::Big => InterfaceDescriptionBlock::from_slice_with::<BigEndian>(body, Utf8Mode::Lossy),
+                    Endianness::Little => InterfaceDescriptionBlock::from_slice_with::<LittleEndian>(body, Utf8Mode::Lossy)
+                };
+                let (_, interface) = res.map_err(|err| err.at_offset(entry.offset))?;
+
+                match sections.last_mut() {
+                    Some(interfaces) => interfaces.push(interface),
+                    None => return Err(PcapError::InvalidField("SectionHeader missing").at_offset(entry.offset))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(sections)
+}
+
+fn section_endianness(data: &[u8], entry: &BlockIndexEntry) -> Option<Endianness> {
+
+    let start = entry.offset as usize + 8;
+    let magic = data.get(start..start + 4)?;
+
+    match BigEndian::read_u32(magic) {
+        0x1A2B3C4D => Some(Endianness::Big),
+        0x4D3C2B1A => Some(Endianness::Little),
+        _ => None
+    }
+}
+
+fn block_at<'a>(data: &'a [u8], entry: &BlockIndexEntry, endianness: Endianness) -> Result<Block<'a>, PcapError> {
+
+    let slice = data.get(entry.offset as usize..)
+        .ok_or_else(|| PcapError::IncompleteBuffer(entry.len as usize).at_offset(entry.offset))?;
+
+    Block::from_slice_endian(slice, endianness).map(|(_, block)| block).map_err(|err| err.at_offset(entry.offset))
+}
@@ -1,13 +1,16 @@
-use byteorder::{BigEndian, LittleEndian};
+use byteorder::BigEndian;
 use crate::errors::PcapError;
-use crate::pcapng::blocks::{ParsedBlock, EnhancedPacketBlock, InterfaceDescriptionBlock};
 use crate::Endianness;
+use crate::pcapng::blocks::{ParsedBlock, EnhancedPacketBlock, InterfaceDescriptionBlock};
 use crate::pcapng::{SectionHeaderBlock, Block, BlockType};
+use crate::pcapng::options::Utf8Mode;
 
 /// Parser for a PcapNg formated stream.
 ///
 /// You can match on PcapError::IncompleteBuffer to known if the parser need more data
 ///
+/// As for the `PcapNgReader`, each SectionHeaderBlock sets the byte order of the blocks of its section.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -57,7 +60,7 @@ impl PcapNgParser {
     pub fn new(src: &[u8]) -> Result<(&[u8], Self), PcapError> {
 
         let (rem, block) = Block::from_slice::<BigEndian>(src)?;
-        let section = block.parsed()?;
+        let section = block.parsed_with(Utf8Mode::Lossy)?;
 
         let section = match section {
             ParsedBlock::SectionHeader(section) => section.into_owned(),
@@ -77,19 +80,16 @@ impl PcapNgParser {
 
         // Read next Block
         let endianess = self.section.endianness();
-        let (rem, block) = match endianess {
-            Endianness::Big => Block::from_slice::<BigEndian>(src)?,
-            Endianness::Little => Block::from_slice::<LittleEndian>(src)?
-        };
+        let (rem, block) = Block::from_slice_endian(src, endianess)?;
 
         match block.type_ {
             BlockType::SectionHeader => {
 
-                self.section = block.parsed()?.into_section_header().unwrap().into_owned();
+                self.section = block.parsed_with(Utf8Mode::Lossy)?.into_section_header().unwrap().into_owned();
                 self.interfaces.clear();
             },
             BlockType::InterfaceDescription => {
-                self.interfaces.push(block.parsed()?.into_interface_description().unwrap().into_owned())
+                self.interfaces.push(block.parsed_with(Utf8Mode::Lossy)?.into_interface_description().unwrap().into_owned())
             },
             _ => {}
         }
@@ -102,13 +102,28 @@ impl PcapNgParser {
         &self.section
     }
 
+    /// Returns the byte order of the current section, given by the magic number of its SectionHeaderBlock.
+    ///
+    /// It is also the byte order of the blocks of the section, see `Block::endianness()`.
+    pub fn current_endianness(&self) -> Endianness {
+        self.section.endianness()
+    }
+
     /// Returns the current interfaces
     pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
         &self.interfaces[..]
     }
 
     /// Returns the InterfaceDescriptionBlock corresponding to the given packet
-    pub fn packet_interface(&self, packet: &EnhancedPacketBlock) -> Option<&InterfaceDescriptionBlock> {
-        self.interfaces.get(packet.interface_id as usize)
+    ///
+    /// Its linktype and timestamp resolution are the ones of the packet.
+    pub fn packet_interface(&self, packet: &EnhancedPacketBlock) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interface(packet.interface_id)
+    }
+
+    /// Returns the InterfaceDescriptionBlock of the current section whose id is `interface_id`,
+    /// e.g. the `interface_id` of a PacketBlock or an InterfaceStatisticsBlock.
+    pub fn interface(&self, interface_id: u32) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interfaces.get(interface_id as usize)
     }
 }
\ No newline at end of file
@@ -1,15 +1,32 @@
+use std::borrow::Cow;
 use std::io::Read;
-use byteorder::{BigEndian, LittleEndian};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
 use crate::errors::PcapError;
-use crate::pcapng::blocks::{ParsedBlock, EnhancedPacketBlock, InterfaceDescriptionBlock};
-use crate::Endianness;
+use crate::pcapng::blocks::{ParsedBlock, EnhancedPacketBlock, InterfaceDescriptionBlock, PacketDirection};
+use crate::{CapturedPacket, Endianness, PacketSource};
 use crate::peek_reader::PeekReader;
 use crate::pcapng::{Block, SectionHeaderBlock, BlockType};
+use crate::pcapng::options::Utf8Mode;
 
 /// Wraps another reader and uses it to read a PcapNg formated stream.
 ///
 /// It implements the Iterator trait in order to read one block at a time except the first SectionHeaderBlock
 ///
+/// The stream is never loaded in memory as a whole: each block is read in its own buffer,
+/// whose size is given by the block length. Blocks longer than `max_block_len`
+/// (`DEFAULT_MAX_BLOCK_LEN` by default) are rejected with a `PcapError::BlockTooLarge`
+/// before their body is read. The options are parsed from the body of their block,
+/// so an option can't be longer than its block either.
+/// The errors are wrapped in a `PcapError::AtOffset` containing the offset of the block which failed.
+///
+/// The stream can contain several sections with different byte orders, e.g. a little endian capture
+/// concatenated with a big endian one: the magic number of each SectionHeaderBlock sets the byte order
+/// of the blocks of its section.
+///
+/// The SectionHeaderBlocks and InterfaceDescriptionBlocks tracked by the reader are decoded with `Utf8Mode::Lossy`,
+/// so an interface name which is not valid UTF-8 doesn't abort the reading of the stream.
+/// The returned blocks are undecoded: use `Block::parsed_with()` to choose how their strings are decoded.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -34,82 +51,581 @@ use crate::pcapng::{Block, SectionHeaderBlock, BlockType};
 pub struct PcapNgReader<R: Read> {
     reader: PeekReader<R>,
     section: SectionHeaderBlock<'static>,
-    interfaces: Vec<InterfaceDescriptionBlock<'static>>
+    interfaces: Vec<InterfaceDescriptionBlock<'static>>,
+    /// Offset of the first byte read through `reader`, after the first SectionHeaderBlock
+    start_offset: u64,
+    max_block_len: u32,
+    buffer: Vec<u8>,
+    error_policy: ErrorPolicy,
+    skipped_blocks: u64
+}
+
+/// What a `PcapNgReader` does with a block which can't be decoded, see `PcapNgReader::set_error_policy()`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ErrorPolicy {
+
+    /// The error is returned, the caller decides whether to go on reading
+    #[default]
+    Stop,
+
+    /// The block is skipped using its length and the reading goes on with the next block,
+    /// which allows to salvage the packets of a partially corrupted capture
+    SkipBlock
 }
 
 impl<R: Read> PcapNgReader<R> {
 
+    /// Default maximum length of a block, framing included: 16 MiB
+    pub const DEFAULT_MAX_BLOCK_LEN: u32 = 16 * 1024 * 1024;
+
     /// Creates a new `PcapNgReader` from a reader.
     /// Parses the first block which must be a valid SectionHeaderBlock
-    pub fn new(mut reader: R) -> Result<PcapNgReader<R>, PcapError> {
+    ///
+    /// The blocks are limited to `DEFAULT_MAX_BLOCK_LEN` bytes.
+    pub fn new(reader: R) -> Result<PcapNgReader<R>, PcapError> {
+        PcapNgReader::with_max_block_len(Self::DEFAULT_MAX_BLOCK_LEN, reader)
+    }
+
+    /// Creates a new `PcapNgReader` from a reader which rejects the blocks longer than `max_block_len` bytes,
+    /// framing included.
+    /// Parses the first block which must be a valid SectionHeaderBlock
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::PcapNgReader;
+    ///
+    /// let file_in = File::open("test.pcapng").expect("Error opening file");
+    /// let pcapng_reader = PcapNgReader::with_max_block_len(1024 * 1024, file_in).unwrap();
+    /// ```
+    pub fn with_max_block_len(max_block_len: u32, mut reader: R) -> Result<PcapNgReader<R>, PcapError> {
 
-        let current_block = Block::from_reader::<_, BigEndian>(&mut reader)?;
-        let section = current_block.parsed()?;
+        let current_block = Block::from_reader_max::<_, BigEndian>(&mut reader, max_block_len).map_err(|err| err.at_offset(0))?;
+        let section = current_block.parsed_with(Utf8Mode::Lossy).map_err(|err| err.at_offset(0))?;
 
         let section = match section {
             ParsedBlock::SectionHeader(section) => section.into_owned(),
-            _ => return Err(PcapError::InvalidField("SectionHeader missing"))
+            _ => return Err(PcapError::InvalidField("SectionHeader missing").at_offset(0))
         };
 
         Ok(
             PcapNgReader {
                 reader: PeekReader::new(reader),
                 section,
-                interfaces: vec![]
+                interfaces: vec![],
+                start_offset: current_block.initial_len as u64,
+                max_block_len,
+                buffer: vec![],
+                error_policy: ErrorPolicy::Stop,
+                skipped_blocks: 0
             }
         )
     }
 
+    /// Returns the maximum length of a block accepted by the reader
+    pub fn max_block_len(&self) -> u32 {
+        self.max_block_len
+    }
+
+    /// Sets what the reader does with a block which can't be decoded, `ErrorPolicy::Stop` by default.
+    ///
+    /// With `ErrorPolicy::SkipBlock`, every block is decoded with `Utf8Mode::Lossy` before being returned
+    /// and the blocks which fail are skipped, as well as the blocks whose leading and trailing lengths differ.
+    /// The reader jumps to the end of the skipped block given by its leading length, and `skipped_blocks()`
+    /// counts them. This guarantees that the returned blocks can be parsed with `Block::parsed_with(Utf8Mode::Lossy)`,
+    /// at the cost of a second decoding if the caller parses them too. The strings are decoded lossily so that an
+    /// interface whose name is not valid UTF-8 is not skipped, which would shift the interface ids of the packets.
+    ///
+    /// The errors which make the length of a block unusable, e.g. a length lower than 12 bytes or greater than
+    /// `max_block_len`, as well as the IO errors, are always returned since there is no next block to go on with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::{ErrorPolicy, PcapNgReader};
+    /// use pcap_file::pcapng::options::Utf8Mode;
+    ///
+    /// let file_in = File::open("crashed.pcapng").expect("Error opening file");
+    /// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+    /// pcapng_reader.set_error_policy(ErrorPolicy::SkipBlock);
+    ///
+    /// while let Some(Ok(block)) = pcapng_reader.next_block() {
+    ///     let parsed_block = block.parsed_with(Utf8Mode::Lossy).unwrap();
+    /// }
+    ///
+    /// println!("{} corrupted blocks skipped", pcapng_reader.skipped_blocks());
+    /// ```
+    pub fn set_error_policy(&mut self, error_policy: ErrorPolicy) {
+        self.error_policy = error_policy;
+    }
+
+    /// Returns what the reader does with a block which can't be decoded
+    pub fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    /// Returns the number of blocks skipped because of `ErrorPolicy::SkipBlock`
+    pub fn skipped_blocks(&self) -> u64 {
+        self.skipped_blocks
+    }
+
     /// Returns the current SectionHeaderBlock
     pub fn section(&self) -> &SectionHeaderBlock<'static> {
         &self.section
     }
 
+    /// Returns the byte order of the current section, given by the magic number of its SectionHeaderBlock.
+    ///
+    /// It is also the byte order of the blocks of the section, see `Block::endianness()`.
+    pub fn current_endianness(&self) -> Endianness {
+        self.section.endianness()
+    }
+
     /// Returns the current interfaces
     pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
         &self.interfaces[..]
     }
 
     /// Returns the InterfaceDescriptionBlock corresponding to the given packet
-    pub fn packet_interface(&self, packet: &EnhancedPacketBlock) -> Option<&InterfaceDescriptionBlock> {
-        self.interfaces.get(packet.interface_id as usize)
+    ///
+    /// Its linktype and timestamp resolution are the ones of the packet.
+    pub fn packet_interface(&self, packet: &EnhancedPacketBlock) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interface(packet.interface_id)
+    }
+
+    /// Returns the InterfaceDescriptionBlock of the current section whose id is `interface_id`,
+    /// e.g. the `interface_id` of a PacketBlock or an InterfaceStatisticsBlock.
+    pub fn interface(&self, interface_id: u32) -> Option<&InterfaceDescriptionBlock<'static>> {
+        self.interfaces.get(interface_id as usize)
+    }
+
+    /// Returns the next block of the stream, or `None` if there is no more data.
+    ///
+    /// A SectionHeaderBlock starts a new section: its endianness is used for the following blocks
+    /// and the current interfaces are cleared.
+    pub fn next_block(&mut self) -> Option<Result<Block<'static>, PcapError>> {
+        loop {
+            match self.reader.is_empty() {
+                Ok(is_empty) if is_empty => return None,
+                Err(err) => return Some(Err(PcapError::from(err).at_offset(self.offset()))),
+                _ => {}
+            }
+
+            let offset = self.offset();
+            match self.next_impl() {
+                Ok(Some(block)) => return Some(Ok(block)),
+                Ok(None) => continue,
+                Err(err) => return Some(Err(err.at_offset(offset)))
+            }
+        }
+    }
+
+    /// Returns the next block of the stream, or `None` if there is no more data.
+    ///
+    /// Same as `next_block` but the body of the block is read in a buffer owned by the reader and reused
+    /// by the following calls, which avoids an allocation per block on captures with many small packets.
+    /// The buffer only grows when a block is longer than its capacity.
+    ///
+    /// This is a lending iterator: the returned block borrows the reader, so it is valid only until the next call
+    /// and two blocks can't be held at the same time. It can't implement the std `Iterator` trait, whose items
+    /// can't borrow the iterator, which is why `next_block()` and the `Iterator` implementation return owned blocks.
+    /// Use `Block::into_owned()` to keep a block longer.
+    ///
+    /// The borrow checker rejects keeping a block across calls:
+    ///
+    /// ```rust,compile_fail
+    /// use pcap_file::pcapng::PcapNgReader;
+    ///
+    /// # let data: Vec<u8> = vec![];
+    /// let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    ///
+    /// let first = pcapng_reader.next_block_ref();
+    /// let second = pcapng_reader.next_block_ref();
+    /// drop(first);
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::{ParsedBlock, PcapNgReader};
+    ///
+    /// let file_in = File::open("test.pcapng").expect("Error opening file");
+    /// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+    ///
+    /// let mut nb_bytes = 0;
+    /// while let Some(block) = pcapng_reader.next_block_ref() {
+    ///
+    ///     if let ParsedBlock::EnhancedPacket(packet) = block.unwrap().parsed().unwrap() {
+    ///         nb_bytes += packet.data.len();
+    ///     }
+    /// }
+    /// ```
+    pub fn next_block_ref(&mut self) -> Option<Result<Block<'_>, PcapError>> {
+
+        // The block is rebuilt from the buffer after the loop: a block borrowing the buffer can't be returned
+        // from a loop which borrows it again on the next iteration
+        let header = loop {
+            match self.reader.is_empty() {
+                Ok(is_empty) if is_empty => return None,
+                Err(err) => return Some(Err(PcapError::from(err).at_offset(self.offset()))),
+                _ => {}
+            }
+
+            let offset = self.offset();
+
+            let res = match self.section.endianness() {
+                Endianness::Big => Block::from_reader_in::<_, BigEndian>(&mut self.reader, self.max_block_len, &mut self.buffer),
+                Endianness::Little => Block::from_reader_in::<_, LittleEndian>(&mut self.reader, self.max_block_len, &mut self.buffer)
+            };
+
+            let block = match res {
+                Ok(block) => block,
+                Err(err) => match self.skip_framing(err) {
+                    Some(err) => return Some(Err(err.at_offset(offset))),
+                    None => continue
+                }
+            };
+
+            match accept(&mut self.section, &mut self.interfaces, self.error_policy, &block) {
+                Ok(true) => break (block.type_, block.initial_len, block.trailer_len, block.endianness()),
+                Ok(false) => self.skipped_blocks += 1,
+                Err(err) => return Some(Err(err.at_offset(offset)))
+            }
+        };
+
+        let (type_, initial_len, trailer_len, endianness) = header;
+        Some(Ok(Block::borrowed(type_, initial_len, &self.buffer, trailer_len, endianness)))
+    }
+
+    /// Returns an iterator over the blocks accepted by `predicate`, the other blocks are skipped.
+    ///
+    /// The predicate is called on the undecoded blocks, before their body is parsed:
+    /// `Block::lazy_enhanced_packet()` gives the interface and the length of a packet without decoding its options.
+    /// The errors are always returned. The reader keeps tracking the sections and the interfaces
+    /// of all the blocks, skipped or not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::PcapNgReader;
+    ///
+    /// let file_in = File::open("test.pcapng").expect("Error opening file");
+    /// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+    ///
+    /// // Only the packets of the first interface longer than 100 bytes
+    /// let packets = pcapng_reader.filtered_blocks(|block| match block.lazy_enhanced_packet() {
+    ///     Some(Ok(packet)) => packet.interface_id == 0 && packet.original_len > 100,
+    ///     _ => false
+    /// });
+    ///
+    /// for block in packets {
+    ///     let parsed_block = block.unwrap().parsed().unwrap();
+    /// }
+    /// ```
+    pub fn filtered_blocks<F: FnMut(&Block) -> bool>(&mut self, predicate: F) -> FilteredBlocks<'_, R, F> {
+        FilteredBlocks {
+            reader: self,
+            predicate
+        }
+    }
+
+    /// Returns an iterator over the EnhancedPacketBlocks and the PacketBlocks which are inbound,
+    /// according to their `Flags` option.
+    ///
+    /// The other blocks are skipped, as for `filtered_blocks()`. Only the `Flags` option of each EnhancedPacketBlock
+    /// is decoded, see `LazyEnhancedPacketBlock::flags()`. `include_unknown` tells if the packets whose direction
+    /// is not available are returned: the packets without a `Flags` option, as well as the packets whose header
+    /// or `Flags` option can't be decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::PcapNgReader;
+    ///
+    /// let file_in = File::open("test.pcapng").expect("Error opening file");
+    /// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+    ///
+    /// for block in pcapng_reader.inbound_only(false) {
+    ///     let parsed_block = block.unwrap().parsed().unwrap();
+    /// }
+    /// ```
+    pub fn inbound_only(&mut self, include_unknown: bool) -> FilteredBlocks<'_, R, impl FnMut(&Block) -> bool> {
+        self.filtered_blocks(move |block| has_direction(block, PacketDirection::Inbound, include_unknown))
     }
 
-    fn next_impl(&mut self) -> Result<Block<'static>, PcapError> {
+    /// Returns an iterator over the EnhancedPacketBlocks and the PacketBlocks which are outbound,
+    /// according to their `Flags` option.
+    ///
+    /// See `inbound_only()`.
+    pub fn outbound_only(&mut self, include_unknown: bool) -> FilteredBlocks<'_, R, impl FnMut(&Block) -> bool> {
+        self.filtered_blocks(move |block| has_direction(block, PacketDirection::Outbound, include_unknown))
+    }
+
+    /// Scans the stream forward, byte by byte, for a plausible next block, e.g. after an error caused by a block
+    /// whose length is garbage. It is a best-effort recovery of the blocks following a corruption.
+    ///
+    /// A plausible block starts with a known block type, followed by a length which is a multiple of 4, at least
+    /// 12 bytes and at most `max_block_len`, and repeated at the end of the block. A SectionHeaderBlock must also
+    /// have a valid magic number, which gives the byte order of its length; the other blocks are read in the byte order
+    /// of the current section.
+    ///
+    /// At most `max_scan` bytes are skipped. Returns the number of bytes skipped to reach the plausible block,
+    /// which is read by the next call to `next_block()`, or `None` if there is none within `max_scan` bytes.
+    /// In that case nothing is consumed. The scanned bytes are buffered, so the memory used is bounded by
+    /// `max_scan + max_block_len`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use pcap_file::pcapng::PcapNgReader;
+    ///
+    /// let file_in = File::open("corrupted.pcapng").expect("Error opening file");
+    /// let mut pcapng_reader = PcapNgReader::new(file_in).unwrap();
+    ///
+    /// let mut blocks = vec![];
+    /// loop {
+    ///     match pcapng_reader.next_block() {
+    ///         Some(Ok(block)) => blocks.push(block),
+    ///         // Look for the next block in the following MiB
+    ///         Some(Err(_)) => if pcapng_reader.resync(1024 * 1024).unwrap().is_none() { break },
+    ///         None => break
+    ///     }
+    /// }
+    /// ```
+    pub fn resync(&mut self, max_scan: usize) -> Result<Option<usize>, PcapError> {
+
+        for skipped in 0..=max_scan {
+
+            let header = self.reader.peek(skipped + 12)?;
+            if header.len() < skipped + 12 {
+                break;
+            }
+
+            // The code of the SectionHeaderBlock is the same in both byte orders
+            let header = &header[skipped..];
+            let type_ = match self.section.endianness() {
+                Endianness::Big => BlockType::from(BigEndian::read_u32(&header[..4])),
+                Endianness::Little => BlockType::from(LittleEndian::read_u32(&header[..4]))
+            };
+            let endianness = match type_ {
+                BlockType::Unknown(_) => continue,
+                BlockType::SectionHeader => match &header[8..12] {
+                    [0x1A, 0x2B, 0x3C, 0x4D] => Endianness::Big,
+                    [0x4D, 0x3C, 0x2B, 0x1A] => Endianness::Little,
+                    _ => continue
+                },
+                _ => self.section.endianness()
+            };
+
+            let read_u32 = |bytes: &[u8]| match endianness {
+                Endianness::Big => BigEndian::read_u32(bytes),
+                Endianness::Little => LittleEndian::read_u32(bytes)
+            };
+
+            let len = read_u32(&header[4..8]);
+            if len < 12 || len % 4 != 0 || len > self.max_block_len {
+                continue;
+            }
+
+            let block = self.reader.peek(skipped + len as usize)?;
+            if block.len() == skipped + len as usize && read_u32(&block[block.len() - 4..]) == len {
+                std::io::copy(&mut (&mut self.reader).take(skipped as u64), &mut std::io::sink())?;
+                return Ok(Some(skipped));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the offset in the stream of the next byte to read.
+    ///
+    /// It is given by the bytes actually consumed, so that after an error it is past the part of the block already read.
+    fn offset(&self) -> u64 {
+        self.start_offset + self.reader.position()
+    }
+
+    /// Reads the next block, returns `None` if it is skipped because of the error policy.
+    fn next_impl(&mut self) -> Result<Option<Block<'static>>, PcapError> {
 
         // Read next Block
         let endianess = self.section.endianness();
-        let block = match endianess {
-            Endianness::Big => Block::from_reader::<_, BigEndian>(&mut self.reader)?,
-            Endianness::Little => Block::from_reader::<_, LittleEndian>(&mut self.reader)?
+        let res = match endianess {
+            Endianness::Big => Block::from_reader_max::<_, BigEndian>(&mut self.reader, self.max_block_len),
+            Endianness::Little => Block::from_reader_max::<_, LittleEndian>(&mut self.reader, self.max_block_len)
+        };
+
+        let block = match res {
+            Ok(block) => block,
+            Err(err) => return match self.skip_framing(err) {
+                Some(err) => Err(err),
+                None => Ok(None)
+            }
         };
 
-        match block.type_ {
-            BlockType::SectionHeader => {
+        if accept(&mut self.section, &mut self.interfaces, self.error_policy, &block)? {
+            Ok(Some(block))
+        }
+        else {
+            self.skipped_blocks += 1;
+            Ok(None)
+        }
+    }
 
-                self.section = block.parsed()?.into_section_header().unwrap().into_owned();
-                self.interfaces.clear();
-            },
-            BlockType::InterfaceDescription => {
-                self.interfaces.push(block.parsed()?.into_interface_description().unwrap().into_owned())
+    /// Skips the block whose framing failed if the error policy allows it, otherwise returns the error.
+    ///
+    /// Only a length mismatch can be skipped: the whole block has been read, so the stream is at its end.
+    fn skip_framing(&mut self, err: PcapError) -> Option<PcapError> {
+        match err {
+            PcapError::BlockLengthMismatch { .. } if self.error_policy == ErrorPolicy::SkipBlock => {
+                self.skipped_blocks += 1;
+                None
             },
-            _ => {}
+            err => Some(err)
+        }
+    }
+}
+
+/// Updates the section with a block read by a `PcapNgReader` and returns false if the block must be skipped.
+///
+/// With `ErrorPolicy::SkipBlock` the block is decoded and skipped if it fails, otherwise the errors are returned.
+fn accept(section: &mut SectionHeaderBlock<'static>, interfaces: &mut Vec<InterfaceDescriptionBlock<'static>>, error_policy: ErrorPolicy, block: &Block) -> Result<bool, PcapError> {
+
+    match error_policy {
+        ErrorPolicy::Stop => update_section(section, interfaces, block).map(|_| true),
+        ErrorPolicy::SkipBlock => {
+            let decoded = block.parsed_with(Utf8Mode::Lossy).is_ok() && update_section(section, interfaces, block).is_ok();
+            Ok(decoded)
         }
+    }
+}
 
-        Ok(block)
+/// Returns true if the block is an EnhancedPacketBlock or a PacketBlock of this direction,
+/// or of an unknown one if `include_unknown`.
+fn has_direction(block: &Block, direction: PacketDirection, include_unknown: bool) -> bool {
+    match packet_direction(block) {
+        Some(PacketDirection::Unknown) => include_unknown,
+        Some(packet_direction) => packet_direction == direction,
+        None => false
     }
 }
 
-impl<R: Read> Iterator for PcapNgReader<R> {
+/// Returns the direction of an EnhancedPacketBlock or a PacketBlock, `None` for the other blocks.
+///
+/// The direction of a packet whose header or `Flags` option can't be decoded is unknown.
+fn packet_direction(block: &Block) -> Option<PacketDirection> {
+
+    let flags = match block.type_ {
+        BlockType::EnhancedPacket => block.lazy_enhanced_packet()?.and_then(|packet| match block.endianness() {
+            Endianness::Big => packet.flags::<BigEndian>(),
+            Endianness::Little => packet.flags::<LittleEndian>()
+        }),
+        BlockType::Packet => match block.parsed_with(Utf8Mode::Lossy) {
+            Ok(ParsedBlock::Packet(packet)) => Ok(packet.flags()),
+            Ok(_) => return None,
+            Err(err) => Err(err)
+        },
+        _ => return None
+    };
+
+    Some(flags.map_or(PacketDirection::Unknown, |flags| flags.direction))
+}
+
+/// Updates the current section and interfaces of a reader with a block read in the stream.
+///
+/// A SectionHeaderBlock starts a new section and an InterfaceDescriptionBlock adds an interface to the current one.
+pub(crate) fn update_section(section: &mut SectionHeaderBlock<'static>, interfaces: &mut Vec<InterfaceDescriptionBlock<'static>>, block: &Block) -> Result<(), PcapError> {
+
+    match block.type_ {
+        BlockType::SectionHeader => {
+
+            *section = block.parsed_with(Utf8Mode::Lossy)?.into_section_header().unwrap().into_owned();
+            interfaces.clear();
+        },
+        BlockType::InterfaceDescription => {
+            interfaces.push(block.parsed_with(Utf8Mode::Lossy)?.into_interface_description().unwrap().into_owned())
+        },
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Iterator over the blocks of a `PcapNgReader` accepted by a predicate, returned by `PcapNgReader::filtered_blocks()`.
+pub struct FilteredBlocks<'r, R: Read, F> {
+    reader: &'r mut PcapNgReader<R>,
+    predicate: F
+}
+
+impl<'r, R: Read, F: FnMut(&Block) -> bool> Iterator for FilteredBlocks<'r, R, F> {
     type Item = Result<Block<'static>, PcapError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.reader.is_empty() {
-            Ok(is_empty) if is_empty => return None,
-            Err(err) => return Some(Err(err.into())),
-            _ => {}
+        loop {
+            match self.reader.next_block()? {
+                Ok(block) if !(self.predicate)(&block) => continue,
+                res => return Some(res)
+            }
         }
+    }
+}
+
+/// The EnhancedPacketBlocks, SimplePacketBlocks and PacketBlocks are returned as packets, the other blocks are skipped.
+///
+/// The timestamp of a packet is resolved with the `if_tsresol` and `if_tsoffset` options of its interface,
+/// see `InterfaceDescriptionBlock::resolve_timestamp()`. A SimplePacketBlock, which has no timestamp,
+/// gets a null timestamp and the link type of the first interface of its section.
+impl<R: Read> PacketSource for PcapNgReader<R> {
+
+    fn next_packet(&mut self) -> Option<Result<CapturedPacket<'static>, PcapError>> {
 
-        Some(self.next_impl())
+        loop {
+
+            let offset = self.offset();
+            let block = match self.next_block()? {
+                Ok(block) => block,
+                Err(err) => return Some(Err(err))
+            };
+
+            let parsed = match block.parsed_with(Utf8Mode::Lossy) {
+                Ok(parsed) => parsed,
+                Err(err) => return Some(Err(err.at_offset(offset)))
+            };
+
+            // Interface id, raw timestamp, original length and data of the packet
+            let (interface_id, timestamp, orig_len, data) = match parsed {
+                ParsedBlock::EnhancedPacket(packet) => (packet.interface_id, Some(packet.timestamp), packet.original_len, packet.data),
+                ParsedBlock::SimplePacket(packet) => (0, None, packet.original_len, packet.data),
+                ParsedBlock::Packet(packet) => (packet.interface_id as u32, Some(packet.timestamp), packet.original_len, packet.data),
+                _ => continue
+            };
+
+            let interface = match self.interface(interface_id) {
+                Some(interface) => interface,
+                None => return Some(Err(PcapError::InvalidField("Packet: interface_id does not reference an interface of the section").at_offset(offset)))
+            };
+
+            return Some(Ok(
+                CapturedPacket {
+                    timestamp: timestamp.map(|raw| interface.resolve_timestamp(raw)).unwrap_or_default(),
+                    interface_linktype: interface.linktype,
+                    orig_len,
+                    data: Cow::Owned(data.into_owned())
+                }
+            ));
+        }
+    }
+}
+
+impl<R: Read> Iterator for PcapNgReader<R> {
+    type Item = Result<Block<'static>, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block()
     }
 }
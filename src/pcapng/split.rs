@@ -0,0 +1,264 @@
+use std::io::{Read, Write};
+use crate::errors::PcapError;
+use crate::pcapng::{EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, PcapNgReader, PcapNgWriter, SectionHeaderBlock};
+
+/// Bound of the fragments written by `split_pcapng` and of the outputs of a `RotatingPcapNgWriter`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum SplitLimit {
+
+    /// Maximum length of a fragment in bytes.
+    ///
+    /// A fragment always contains at least one packet, so it can be longer if a single packet doesn't fit.
+    Bytes(u64),
+
+    /// Maximum number of packets of a fragment
+    Packets(u64)
+}
+
+/// Splits a PcapNg stream into fragments bounded by `limit`, like `editcap -c`.
+///
+/// `sink_factory` is called with the index of each fragment, starting at 0, and returns the writer of the fragment.
+///
+/// Each fragment starts with a copy of the current SectionHeaderBlock and of all the InterfaceDescriptionBlocks
+/// of the current section, so every fragment is a valid stream on its own and the interface ids are unchanged.
+/// The section length of the copied SectionHeaderBlocks is set to unspecified (-1).
+/// The other blocks (name resolution, statistics, secrets...) are only written in the fragment where they appear.
+///
+/// Returns the number of fragments written.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::pcapng::{split_pcapng, SplitLimit};
+///
+/// let file_in = File::open("test.pcapng").expect("Error opening file");
+///
+/// // Fragments of 1000 packets named test_0.pcapng, test_1.pcapng...
+/// let nb_fragments = split_pcapng(file_in, SplitLimit::Packets(1000), |i| File::create(format!("test_{}.pcapng", i))).unwrap();
+/// ```
+pub fn split_pcapng<R, W, F>(reader: R, limit: SplitLimit, sink_factory: F) -> Result<usize, PcapError>
+    where R: Read,
+          W: Write,
+          F: FnMut(usize) -> std::io::Result<W>
+{
+    let mut pcapng_reader = PcapNgReader::new(reader)?;
+    let mut rotating_writer = RotatingPcapNgWriter::with_section_header(pcapng_reader.section().clone(), limit, sink_factory)?;
+
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block?;
+        rotating_writer.write_block(&block.parsed()?)?;
+    }
+
+    let nb_files = rotating_writer.nb_files();
+    rotating_writer.into_writer().flush()?;
+
+    Ok(nb_files)
+}
+
+/// Wraps a `PcapNgWriter` and starts a new output each time the current one reaches `limit`,
+/// like `tcpdump -C` or, with a factory reusing its outputs, `tcpdump -C -W`.
+///
+/// `sink_factory` is called with the index of each output, starting at 0, and returns its writer.
+/// The previous output is flushed before the next one is requested.
+///
+/// Each output starts with a copy of the current SectionHeaderBlock and of all the InterfaceDescriptionBlocks
+/// of the current section, so every output is a valid stream on its own and the interface ids are unchanged.
+/// The section length of all the written SectionHeaderBlocks is set to unspecified (-1).
+///
+/// An output always contains at least one packet, so it can be longer than a `SplitLimit::Bytes` if a single packet doesn't fit.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::borrow::Cow;
+/// use std::fs::File;
+/// use pcap_file::pcapng::{EnhancedPacketBlock, InterfaceDescriptionBlock, RotatingPcapNgWriter, SplitLimit};
+///
+/// // Ring buffer of 10 files of at most 1 MB: capture_0.pcapng to capture_9.pcapng
+/// let limit = SplitLimit::Bytes(1_000_000);
+/// let mut pcapng_writer = RotatingPcapNgWriter::new(limit, |i| File::create(format!("capture_{}.pcapng", i % 10))).unwrap();
+///
+/// let interface_id = pcapng_writer.write_interface(&InterfaceDescriptionBlock::ethernet()).unwrap();
+/// let packet = EnhancedPacketBlock {
+///     interface_id,
+///     timestamp: 0,
+///     captured_len: 4,
+///     original_len: 4,
+///     data: Cow::Borrowed(&[0, 1, 2, 3]),
+///     options: vec![]
+/// };
+/// pcapng_writer.write_enhanced_packet(&packet).unwrap();
+/// ```
+pub struct RotatingPcapNgWriter<W, F>
+    where W: Write,
+          F: FnMut(usize) -> std::io::Result<W>
+{
+    writer: PcapNgWriter<W>,
+    sink_factory: F,
+    limit: SplitLimit,
+    nb_files: usize,
+    len: u64,
+    nb_packets: u64
+}
+
+impl<W, F> RotatingPcapNgWriter<W, F>
+    where W: Write,
+          F: FnMut(usize) -> std::io::Result<W>
+{
+    /// Creates a new `RotatingPcapNgWriter` with a default SectionHeaderBlock
+    /// in the native endianness of the CPU, see `PcapNgWriter::new()`.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the first output can't be created or written to.
+    pub fn new(limit: SplitLimit, sink_factory: F) -> Result<RotatingPcapNgWriter<W, F>, PcapError> {
+        let section = PcapNgWriter::new(std::io::sink())?.section().clone();
+        RotatingPcapNgWriter::with_section_header(section, limit, sink_factory)
+    }
+
+    /// Creates a new `RotatingPcapNgWriter` with a user defined SectionHeaderBlock.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the first output can't be created or written to.
+    pub fn with_section_header(section: SectionHeaderBlock<'static>, limit: SplitLimit, mut sink_factory: F) -> Result<RotatingPcapNgWriter<W, F>, PcapError> {
+
+        let section = unspecified_length(section);
+        let len = section.serialized_len() as u64;
+        let writer = PcapNgWriter::with_section_header(section, sink_factory(0)?)?;
+
+        Ok(RotatingPcapNgWriter { writer, sink_factory, limit, nb_files: 1, len, nb_packets: 0 })
+    }
+
+    /// Writes a block and returns the number of bytes written, framing included, see `PcapNgWriter::write_block()`.
+    ///
+    /// A new output is started first if the block is a packet which doesn't fit in the current one.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the block can't be written or if the next output can't be created.
+    pub fn write_block(&mut self, block: &ParsedBlock) -> Result<usize, PcapError> {
+
+        let is_packet = matches!(block, ParsedBlock::EnhancedPacket(_) | ParsedBlock::SimplePacket(_) | ParsedBlock::Packet(_));
+        if is_packet && self.is_full(block.serialized_len() as u64) {
+            self.rotate()?;
+        }
+
+        let len = match block {
+            ParsedBlock::SectionHeader(section) => {
+                let section = unspecified_length(section.clone().into_owned());
+                self.writer.write_block(&ParsedBlock::SectionHeader(section))?
+            },
+            block => self.writer.write_block(block)?
+        };
+
+        self.len += len as u64;
+        if is_packet {
+            self.nb_packets += 1;
+        }
+
+        Ok(len)
+    }
+
+    /// Writes an InterfaceDescriptionBlock and returns its interface id, see `PcapNgWriter::write_interface()`.
+    ///
+    /// The interface is also written at the start of the next outputs of the current section.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to.
+    pub fn write_interface(&mut self, interface: &InterfaceDescriptionBlock) -> Result<u32, PcapError> {
+        self.len += interface.serialized_len() as u64;
+        self.writer.write_interface(interface)
+    }
+
+    /// Writes an EnhancedPacketBlock and returns the number of bytes written, framing included.
+    ///
+    /// A new output is started first if the packet doesn't fit in the current one.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the packet can't be written or if the next output can't be created.
+    pub fn write_enhanced_packet(&mut self, packet: &EnhancedPacketBlock) -> Result<usize, PcapError> {
+
+        if self.is_full(packet.serialized_len() as u64) {
+            self.rotate()?;
+        }
+
+        let len = self.writer.write_enhanced_packet(packet)?;
+        self.len += len as u64;
+        self.nb_packets += 1;
+
+        Ok(len)
+    }
+
+    /// Returns the number of outputs started, the current one included.
+    pub fn nb_files(&self) -> usize {
+        self.nb_files
+    }
+
+    /// Returns the number of bytes written in the current output.
+    pub fn file_len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns the current SectionHeaderBlock
+    pub fn section(&self) -> &SectionHeaderBlock<'static> {
+        self.writer.section()
+    }
+
+    /// Returns the interfaces written in the current section
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
+        self.writer.interfaces()
+    }
+
+    /// Consumes the `RotatingPcapNgWriter`, returning the writer of the current output.
+    pub fn into_writer(self) -> W {
+        self.writer.into_writer()
+    }
+
+    /// Gets a reference to the writer of the current output.
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Returns true if a packet block of `block_len` bytes doesn't fit in the current output
+    fn is_full(&self, block_len: u64) -> bool {
+
+        if self.nb_packets == 0 {
+            return false;
+        }
+
+        match self.limit {
+            SplitLimit::Bytes(max_bytes) => self.len + block_len > max_bytes,
+            SplitLimit::Packets(max_packets) => self.nb_packets >= max_packets
+        }
+    }
+
+    /// Flushes the current output and starts the next one with the current section and interfaces
+    fn rotate(&mut self) -> Result<(), PcapError> {
+
+        self.writer.get_mut().flush()?;
+
+        let section = self.writer.section().clone();
+        let mut len = section.serialized_len() as u64;
+        let mut writer = PcapNgWriter::with_section_header(section, (self.sink_factory)(self.nb_files)?)?;
+
+        for interface in self.writer.interfaces() {
+            len += writer.write_block(&ParsedBlock::InterfaceDescription(interface.clone()))? as u64;
+        }
+
+        self.writer = writer;
+        self.nb_files += 1;
+        self.len = len;
+        self.nb_packets = 0;
+
+        Ok(())
+    }
+}
+
+fn unspecified_length(mut section: SectionHeaderBlock<'static>) -> SectionHeaderBlock<'static> {
+    section.section_length = -1;
+    section
+}
@@ -0,0 +1,331 @@
+use std::borrow::Cow;
+use std::io::Write;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, NativeEndian, WriteBytesExt};
+use crate::errors::PcapError;
+use crate::Endianness;
+use crate::pcapng::{Block, BlockType, EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, SectionHeaderBlock, SimplePacketBlock};
+use crate::pcapng::options::Utf8Mode;
+
+/// Wraps another writer and uses it to write a PcapNg formated stream.
+///
+/// The framing of the blocks (block type, block lengths and padding) is handled by the writer.
+///
+/// The packets can be truncated to the snaplen of their interface, see `set_truncate_to_snaplen`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::pcapng::{PcapNgReader, PcapNgWriter};
+///
+/// let file_in = File::open("test.pcapng").expect("Error opening file");
+/// let pcapng_reader = PcapNgReader::new(file_in).unwrap();
+///
+/// let file_out = File::create("out.pcapng").expect("Error creating file out");
+/// let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), file_out).unwrap();
+///
+/// // Read test.pcapng
+/// for block in pcapng_reader {
+///
+///     //Check if there is no error
+///     let block = block.unwrap();
+///
+///     //Write each block of test.pcapng in out.pcapng
+///     pcapng_writer.write_block(&block.parsed().unwrap()).unwrap();
+/// }
+/// ```
+pub struct PcapNgWriter<W: Write> {
+    section: SectionHeaderBlock<'static>,
+    interfaces: Vec<InterfaceDescriptionBlock<'static>>,
+    truncate_to_snaplen: bool,
+    writer: W
+}
+
+impl<W: Write> PcapNgWriter<W> {
+
+    /// Creates a new `PcapNgWriter` from an existing writer.
+    ///
+    /// Automatically writes a default SectionHeaderBlock (version 1.0, unspecified section length, no option)
+    /// in the native endianness of the CPU.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to.
+    pub fn new(writer: W) -> Result<PcapNgWriter<W>, PcapError> {
+
+        let endianness = match NativeEndian::read_u16(&[0x42, 0x00]) {
+            0x4200 => Endianness::Big,
+            _ => Endianness::Little
+        };
+
+        let mut section = SectionHeaderBlock::default();
+        section.set_endianness(endianness);
+
+        PcapNgWriter::with_section_header(section, writer)
+    }
+
+    /// Creates a new `PcapNgWriter` from an existing writer with a user defined SectionHeaderBlock.
+    ///
+    /// The endianness of the section is defined by the magic number of the SectionHeaderBlock.
+    /// It automatically writes the SectionHeaderBlock.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to.
+    pub fn with_section_header(section: SectionHeaderBlock<'static>, writer: W) -> Result<PcapNgWriter<W>, PcapError> {
+
+        let mut pcapng_writer = PcapNgWriter {
+            section: SectionHeaderBlock::default(),
+            interfaces: vec![],
+            truncate_to_snaplen: false,
+            writer
+        };
+
+        pcapng_writer.write_block(&ParsedBlock::SectionHeader(section))?;
+
+        Ok(pcapng_writer)
+    }
+
+    /// Writes a block and returns the number of bytes written, framing included.
+    ///
+    /// A SectionHeaderBlock starts a new section: its endianness is used for the following blocks
+    /// and the current interfaces are cleared.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to or if a packet or statistics block
+    /// references an interface which has not been written in the current section.
+    pub fn write_block(&mut self, block: &ParsedBlock) -> Result<usize, PcapError> {
+
+        match block {
+            ParsedBlock::SectionHeader(section) => {
+                self.section = section.to_owned();
+                self.interfaces.clear();
+            },
+            ParsedBlock::Packet(packet) => self.check_interface(packet.interface_id as u32)?,
+            ParsedBlock::EnhancedPacket(packet) => self.check_interface(packet.interface_id)?,
+            ParsedBlock::InterfaceStatistics(stats) => self.check_interface(stats.interface_id)?,
+            _ => {}
+        }
+
+        let truncated = self.truncated_block(block);
+        let block = match &truncated {
+            Some(truncated) => truncated,
+            None => block
+        };
+
+        let mut body = Vec::new();
+        match self.section.endianness() {
+            Endianness::Big => block.write_to::<_, BigEndian>(&mut body)?,
+            Endianness::Little => block.write_to::<_, LittleEndian>(&mut body)?
+        };
+
+        let len = self.write_framed(block.block_type(), &body)?;
+
+        if let ParsedBlock::InterfaceDescription(interface) = block {
+            self.interfaces.push(interface.to_owned());
+        }
+
+        Ok(len)
+    }
+
+    /// Parses a raw block, e.g. read by a `PcapNgReader`, and writes it as `write_block()` does.
+    ///
+    /// The block is rewritten from its parsed content: its lengths and its padding are recomputed, and its options
+    /// are ended by an end of options marker, so the output is well formed even if the input block was not.
+    /// The body of a block whose type is unknown is copied byte for byte, only its framing is rewritten.
+    ///
+    /// The strings are decoded with `Utf8Mode::Lossy`, as the `PcapNgReader` does, so a block which the reader
+    /// accepts can be copied. Since the sizes of the blocks can change, the `section_length` of a copied
+    /// SectionHeaderBlock is set to -1 (unspecified).
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the block can't be parsed, or for the same reasons as `write_block()`.
+    pub fn copy_block(&mut self, block: &Block) -> Result<usize, PcapError> {
+        match block.parsed_with(Utf8Mode::Lossy)? {
+            ParsedBlock::SectionHeader(mut section) => {
+                section.section_length = -1;
+                self.write_block(&ParsedBlock::SectionHeader(section))
+            },
+            parsed => self.write_block(&parsed)
+        }
+    }
+
+    /// Writes an InterfaceDescriptionBlock and returns its interface id,
+    /// which must be used by the packets captured on this interface.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to.
+    pub fn write_interface(&mut self, interface: &InterfaceDescriptionBlock) -> Result<u32, PcapError> {
+
+        let mut body = Vec::new();
+        match self.section.endianness() {
+            Endianness::Big => interface.write_to::<_, BigEndian>(&mut body)?,
+            Endianness::Little => interface.write_to::<_, LittleEndian>(&mut body)?
+        };
+
+        self.write_framed(BlockType::InterfaceDescription, &body)?;
+        self.interfaces.push(interface.to_owned());
+
+        Ok(self.interfaces.len() as u32 - 1)
+    }
+
+    /// Writes an EnhancedPacketBlock and returns the number of bytes written, framing included.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the writer can't be written to or if the packet
+    /// references an interface which has not been written in the current section.
+    pub fn write_enhanced_packet(&mut self, packet: &EnhancedPacketBlock) -> Result<usize, PcapError> {
+
+        self.check_interface(packet.interface_id)?;
+
+        let truncated = self.truncated_enhanced_packet(packet);
+        let packet = truncated.as_ref().unwrap_or(packet);
+
+        let mut body = Vec::new();
+        match self.section.endianness() {
+            Endianness::Big => packet.write_to::<_, BigEndian>(&mut body)?,
+            Endianness::Little => packet.write_to::<_, LittleEndian>(&mut body)?
+        };
+
+        self.write_framed(BlockType::EnhancedPacket, &body)
+    }
+
+    /// Enables or disables the truncation of the packets to the snaplen of their interface.
+    ///
+    /// When enabled, the data of the EnhancedPacketBlocks and SimplePacketBlocks longer than the snaplen
+    /// is truncated to it, the captured length is updated and the original length is preserved.
+    /// A SimplePacketBlock uses the snaplen of the first interface of the section.
+    /// A snaplen of 0 means no limit and the packets are never truncated.
+    ///
+    /// Disabled by default.
+    pub fn set_truncate_to_snaplen(&mut self, truncate_to_snaplen: bool) {
+        self.truncate_to_snaplen = truncate_to_snaplen;
+    }
+
+    /// Returns true if the packets are truncated to the snaplen of their interface
+    pub fn truncate_to_snaplen(&self) -> bool {
+        self.truncate_to_snaplen
+    }
+
+    /// Returns the current SectionHeaderBlock
+    pub fn section(&self) -> &SectionHeaderBlock<'static> {
+        &self.section
+    }
+
+    /// Returns the interfaces written in the current section
+    pub fn interfaces(&self) -> &[InterfaceDescriptionBlock<'static>] {
+        &self.interfaces[..]
+    }
+
+    /// Consumes the `PcapNgWriter`, returning the wrapped writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// It is inadvisable to directly write to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    fn check_interface(&self, interface_id: u32) -> Result<(), PcapError> {
+
+        if interface_id as usize >= self.interfaces.len() {
+            return Err(PcapError::InvalidField("PcapNgWriter: interface_id does not reference a written interface"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the snaplen to apply to the packets of the interface, if any.
+    fn snaplen(&self, interface_id: u32) -> Option<usize> {
+
+        if !self.truncate_to_snaplen {
+            return None;
+        }
+
+        match self.interfaces.get(interface_id as usize)?.snaplen {
+            0 => None,
+            snaplen => Some(snaplen as usize)
+        }
+    }
+
+    /// Returns a copy of the packet truncated to the snaplen of its interface, or `None` if it fits.
+    fn truncated_enhanced_packet<'b>(&self, packet: &'b EnhancedPacketBlock) -> Option<EnhancedPacketBlock<'b>> {
+
+        let snaplen = self.snaplen(packet.interface_id)?;
+        if packet.data.len() <= snaplen {
+            return None;
+        }
+
+        Some(
+            EnhancedPacketBlock {
+                interface_id: packet.interface_id,
+                timestamp: packet.timestamp,
+                captured_len: snaplen as u32,
+                original_len: packet.original_len,
+                data: Cow::Borrowed(&packet.data[..snaplen]),
+                options: packet.options.clone()
+            }
+        )
+    }
+
+    /// Returns a copy of the block truncated to the snaplen of its interface, or `None` if it fits.
+    fn truncated_block<'b>(&self, block: &'b ParsedBlock) -> Option<ParsedBlock<'b>> {
+
+        match block {
+            ParsedBlock::EnhancedPacket(packet) => self.truncated_enhanced_packet(packet).map(ParsedBlock::EnhancedPacket),
+            ParsedBlock::SimplePacket(packet) => {
+
+                let snaplen = self.snaplen(0)?;
+                if packet.data.len() <= snaplen {
+                    return None;
+                }
+
+                let packet = SimplePacketBlock {
+                    original_len: packet.original_len,
+                    data: Cow::Borrowed(&packet.data[..snaplen])
+                };
+
+                Some(ParsedBlock::SimplePacket(packet))
+            },
+            _ => None
+        }
+    }
+
+    fn write_framed(&mut self, type_: BlockType, body: &[u8]) -> Result<usize, PcapError> {
+        match self.section.endianness() {
+            Endianness::Big => write_framed::<_, BigEndian>(&mut self.writer, type_, body),
+            Endianness::Little => write_framed::<_, LittleEndian>(&mut self.writer, type_, body)
+        }
+    }
+}
+
+/// Writes the block type, the leading length, the padded body and the trailing length.
+fn write_framed<W: Write, B: ByteOrder>(writer: &mut W, type_: BlockType, body: &[u8]) -> Result<usize, PcapError> {
+
+    let pad_len = (4 - (body.len() % 4)) % 4;
+    let total_len = 12 + body.len() + pad_len;
+
+    if total_len > u32::MAX as usize {
+        return Err(PcapError::InvalidField("PcapNgWriter: block length > u32::MAX"));
+    }
+
+    writer.write_u32::<B>(type_.into())?;
+    writer.write_u32::<B>(total_len as u32)?;
+    writer.write_all(body)?;
+    writer.write_all(&[0_u8; 3][..pad_len])?;
+    writer.write_u32::<B>(total_len as u32)?;
+
+    Ok(total_len)
+}
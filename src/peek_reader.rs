@@ -1,71 +1,205 @@
-use std::io::{Read, Result as IoResult, Seek, SeekFrom};
+//! Reader which can look at the next bytes of a stream before consuming them.
+//!
+//! It is used by the readers to detect the end of the stream and can be reused to build
+//! custom framings on top of the same streams, e.g. to check a magic number before choosing the byte order.
 
+use std::io::{BufRead, Read, Result as IoResult, Seek, SeekFrom};
+use byteorder::ByteOrder;
+
+/// Wraps a reader and allows to peek its next bytes without consuming them.
+///
+/// The peeked bytes are kept in an internal buffer and returned by the following reads.
+///
+/// # Examples
+///
+/// Detect the byte order of a PcapNg stream from the magic number of its SectionHeaderBlock:
+///
+/// ```rust
+/// use byteorder::BigEndian;
+/// use pcap_file::Endianness;
+/// use pcap_file::peek_reader::PeekReader;
+/// use std::io::Read;
+///
+/// let data = [
+///     0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+///     0x4D, 0x3C, 0x2B, 0x1A
+/// ];
+///
+/// let mut reader = PeekReader::new(&data[..]);
+///
+/// // The magic number follows the block type and the block length
+/// let header = reader.peek(12).unwrap();
+/// let endianness = match &header[8..12] {
+///     [0x1A, 0x2B, 0x3C, 0x4D] => Endianness::Big,
+///     [0x4D, 0x3C, 0x2B, 0x1A] => Endianness::Little,
+///     _ => panic!("Not a SectionHeaderBlock")
+/// };
+/// assert_eq!(endianness, Endianness::Little);
+///
+/// // Nothing has been consumed
+/// assert_eq!(reader.peek_u32::<BigEndian>().unwrap(), 0x0A0D0D0A);
+///
+/// let mut block = vec![];
+/// reader.read_to_end(&mut block).unwrap();
+/// assert_eq!(block, data);
+/// ```
 #[derive(Debug)]
-pub(crate) struct PeekReader<R: Read> {
-    pub inner: R,
-    pub peeked: Option<u8>,
+pub struct PeekReader<R: Read> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    position: u64
 }
 
-impl<R: Read> Read for PeekReader<R> {
-
-    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+impl<R: Read> PeekReader<R> {
 
-        if buf.is_empty() {
-            return Ok(0);
+    /// Creates a new `PeekReader` wrapping `inner`.
+    pub fn new(inner: R) -> PeekReader<R> {
+        PeekReader {
+            inner,
+            buf: vec![],
+            pos: 0,
+            position: 0
         }
+    }
 
-        if let Some(b) = self.peeked {
+    /// Returns the next `len` bytes of the stream without consuming them.
+    ///
+    /// The returned slice is shorter than `len` only if the end of the stream is reached.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if the underlying reader fails.
+    pub fn peek(&mut self, len: usize) -> IoResult<&[u8]> {
 
-            buf[0] = b;
-            self.peeked = None;
+        // Drop the consumed bytes before growing the buffer
+        if self.pos > 0 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
 
-            //Read the input and add one to the number of byte read
-            self.inner.read(&mut buf[1..]).map(|x| x+1)
+        if self.buf.len() < len {
+            let missing = len - self.buf.len();
+            (&mut self.inner).take(missing as u64).read_to_end(&mut self.buf)?;
         }
-        else {
-            self.inner.read(buf)
+
+        let end = std::cmp::min(len, self.buf.len());
+        Ok(&self.buf[..end])
+    }
+
+    /// Returns the next 4 bytes of the stream as a u32 in the byte order `B`, without consuming them.
+    ///
+    /// # Errors
+    ///
+    /// Return an `UnexpectedEof` error if less than 4 bytes are left.
+    pub fn peek_u32<B: ByteOrder>(&mut self) -> IoResult<u32> {
+
+        let bytes = self.peek(4)?;
+        if bytes.len() < 4 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
         }
+
+        Ok(B::read_u32(bytes))
+    }
+
+    /// Returns true if there is no more data to read.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn is_empty(&mut self) -> IoResult<bool> {
+        Ok(self.peek(1)?.is_empty())
+    }
+
+    /// Returns the number of bytes consumed since the creation of the `PeekReader`, the peeked bytes excluded.
+    ///
+    /// After a seek, it is the position returned by the underlying reader.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// The bytes already peeked are not seen by the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the `PeekReader`, returning the wrapped reader.
+    ///
+    /// The bytes peeked but not consumed are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
     }
 }
 
-impl<R: Read + Seek> Seek for PeekReader<R> {
-    fn seek(&mut self, pos: SeekFrom) -> ::std::io::Result<u64> {
-        if self.peeked.is_some() {
-            self.inner.seek(SeekFrom::Current(-1))?;
-            self.peeked = None;
+impl<R: Read> Read for PeekReader<R> {
+
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+
+        if self.pos == self.buf.len() {
+            let len = self.inner.read(buf)?;
+            self.position += len as u64;
+            return Ok(len);
         }
-        self.inner.seek(pos)
+
+        let len = std::cmp::min(buf.len(), self.buf.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.consume(len);
+
+        Ok(len)
     }
 }
 
-impl<R: Read> PeekReader<R> {
+impl<R: Read> BufRead for PeekReader<R> {
 
-    pub fn new(inner: R) -> PeekReader<R> {
-        PeekReader {
-            inner,
-            peeked: None
+    /// Returns the peeked bytes, or reads a new chunk from the underlying reader if there is none.
+    fn fill_buf(&mut self) -> IoResult<&[u8]> {
+
+        if self.pos == self.buf.len() {
+            self.buf.resize(8192, 0);
+            self.pos = 0;
+
+            match self.inner.read(&mut self.buf) {
+                Ok(len) => self.buf.truncate(len),
+                Err(err) => {
+                    self.buf.clear();
+                    return Err(err);
+                }
+            }
         }
+
+        Ok(&self.buf[self.pos..])
     }
 
-    #[allow(clippy::wrong_self_convention)]
-    pub fn is_empty(&mut self) -> IoResult<bool> {
+    /// Consumes `amt` bytes of the buffer returned by `fill_buf` or `peek`.
+    fn consume(&mut self, amt: usize) {
+        let pos = std::cmp::min(self.pos + amt, self.buf.len());
+        self.position += (pos - self.pos) as u64;
+        self.pos = pos;
 
-        if self.peeked.is_some() {
-            return Ok(false);
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
         }
+    }
+}
 
-        let mut buf = [0; 1];
-        let nb_read = self.read(&mut buf)?;
-
-        Ok(
-            match nb_read {
-                0 => true,
-                1 => {
-                    self.peeked = Some(buf[0]);
-                    false
-                },
-                _ => unreachable!(),
-            }
-        )
+impl<R: Read + Seek> Seek for PeekReader<R> {
+
+    fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+
+        // Rewind the underlying reader to the first byte not consumed
+        let buffered = self.buf.len() - self.pos;
+        if buffered > 0 {
+            self.inner.seek(SeekFrom::Current(-(buffered as i64)))?;
+            self.buf.clear();
+            self.pos = 0;
+        }
+
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
     }
 }
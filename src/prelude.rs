@@ -0,0 +1,6 @@
+//! The items of the std prelude which are not in the core prelude, for the modules available without `std`.
+
+pub(crate) use std::borrow::ToOwned;
+pub(crate) use std::boxed::Box;
+pub(crate) use std::string::String;
+pub(crate) use std::vec::Vec;
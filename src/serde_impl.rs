@@ -0,0 +1,40 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::DataLink;
+use crate::pcapng::MacAddr;
+
+/// Serializes raw bytes as a lowercase hexadecimal string.
+pub(crate) fn hex<S: Serializer, T: AsRef<[u8]>>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error> {
+
+    let bytes = bytes.as_ref();
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+
+    serializer.serialize_str(&hex)
+}
+
+/// Serializes a `DataLink` as its canonical name, e.g. `ETHERNET` or `Unknown(1234)`.
+impl Serialize for DataLink {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes a `DataLink` from its canonical name, case-insensitively.
+impl<'de> Deserialize<'de> for DataLink {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        DataLink::from_str(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes a `MacAddr` as colon separated hexadecimal octets.
+impl Serialize for MacAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
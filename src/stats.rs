@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::Duration;
+use byteorder::BigEndian;
+use crate::errors::PcapError;
+use crate::pcap::PcapReader;
+use crate::pcapng::{BlockType, ParsedBlock, PcapNgReader};
+use crate::pcapng::options::Utf8Mode;
+use crate::peek_reader::PeekReader;
+
+/// Summary of a capture computed by `capture_stats()`, like the one printed by `capinfos`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CaptureStats {
+
+    /// Number of blocks of each type, the SectionHeaderBlocks included.
+    ///
+    /// Empty for a Pcap capture, which has no blocks.
+    pub block_counts: HashMap<BlockType, u64>,
+
+    /// Number of packets
+    pub nb_packets: u64,
+
+    /// Total length of the captured data of the packets
+    pub captured_bytes: u64,
+
+    /// Number of packets of each interface, in the order of the interfaces in the stream.
+    ///
+    /// The interfaces of all the sections are numbered one after the other.
+    /// A Pcap capture has a single interface.
+    pub interface_packets: Vec<u64>,
+
+    /// Earliest timestamp of the packets, since the epoch
+    pub first_timestamp: Option<Duration>,
+
+    /// Latest timestamp of the packets, since the epoch
+    pub last_timestamp: Option<Duration>
+}
+
+impl CaptureStats {
+
+    fn add_packet(&mut self, interface: usize, captured_len: usize, timestamp: Option<Duration>) {
+
+        self.nb_packets += 1;
+        self.captured_bytes += captured_len as u64;
+        self.interface_packets[interface] += 1;
+
+        if let Some(timestamp) = timestamp {
+            self.first_timestamp = Some(self.first_timestamp.map_or(timestamp, |first| first.min(timestamp)));
+            self.last_timestamp = Some(self.last_timestamp.map_or(timestamp, |last| last.max(timestamp)));
+        }
+    }
+}
+
+/// Reads a whole Pcap or PcapNg stream and returns its `CaptureStats`.
+///
+/// The format is detected from the magic number at the start of the stream.
+/// The timestamps of the PcapNg packets are resolved with the options of their interface,
+/// see `InterfaceDescriptionBlock::resolve_timestamp()`. The SimplePacketBlocks, which have no timestamp,
+/// are counted but are not taken into account for the first and last timestamps.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use pcap_file::capture_stats;
+///
+/// let file_in = File::open("test.pcapng").expect("Error opening file");
+/// let stats = capture_stats(file_in).unwrap();
+///
+/// println!("{} packets, {} bytes", stats.nb_packets, stats.captured_bytes);
+/// ```
+pub fn capture_stats<R: Read>(reader: R) -> Result<CaptureStats, PcapError> {
+
+    let mut reader = PeekReader::new(reader);
+
+    if reader.peek_u32::<BigEndian>()? == BlockType::SectionHeader.as_u32() {
+        pcapng_stats(reader)
+    }
+    else {
+        pcap_stats(reader)
+    }
+}
+
+fn pcap_stats<R: Read>(reader: R) -> Result<CaptureStats, PcapError> {
+
+    let pcap_reader = PcapReader::new(reader)?;
+
+    let mut stats = CaptureStats {
+        interface_packets: vec![0],
+        ..Default::default()
+    };
+
+    for packet in pcap_reader {
+        let packet = packet?;
+        stats.add_packet(0, packet.data.len(), Some(packet.header.timestamp()));
+    }
+
+    Ok(stats)
+}
+
+fn pcapng_stats<R: Read>(reader: R) -> Result<CaptureStats, PcapError> {
+
+    let mut pcapng_reader = PcapNgReader::new(reader)?;
+
+    let mut stats = CaptureStats::default();
+    stats.block_counts.insert(BlockType::SectionHeader, 1);
+
+    // Index of the first interface of the current section
+    let mut first_interface = 0;
+
+    while let Some(block) = pcapng_reader.next_block() {
+
+        let block = block?;
+        *stats.block_counts.entry(block.type_).or_insert(0) += 1;
+
+        let (interface_id, timestamp, captured_len) = match block.parsed_with(Utf8Mode::Lossy)? {
+            ParsedBlock::SectionHeader(_) => {
+                first_interface = stats.interface_packets.len();
+                continue;
+            },
+            ParsedBlock::InterfaceDescription(_) => {
+                stats.interface_packets.push(0);
+                continue;
+            },
+            ParsedBlock::EnhancedPacket(packet) => (packet.interface_id, Some(packet.timestamp), packet.data.len()),
+            ParsedBlock::SimplePacket(packet) => (0, None, packet.data.len()),
+            ParsedBlock::Packet(packet) => (packet.interface_id as u32, Some(packet.timestamp), packet.data.len()),
+            _ => continue
+        };
+
+        let interface = pcapng_reader.interface(interface_id)
+            .ok_or(PcapError::InvalidField("capture_stats: interface_id does not reference an interface of the section"))?;
+        let timestamp = timestamp.map(|raw| interface.resolve_timestamp(raw));
+
+        stats.add_packet(first_interface + interface_id as usize, captured_len, timestamp);
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,57 @@
+use crate::DataLink;
+use crate::prelude::*;
+
+/// Anomaly found in a packet by `validate_against_linktype()`.
+///
+/// It is not an error: truncated captures are legal, but such packets are likely corrupted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+
+    /// The captured data is shorter than the header of its link type
+    ShorterThanHeader {
+        datalink: DataLink,
+        len: usize,
+        min_len: usize
+    },
+
+    /// The captured data is longer than the original length of the packet
+    CapturedLongerThanOriginal {
+        captured_len: usize,
+        original_len: u32
+    }
+}
+
+/// Checks the captured data of a packet against its link type and its original length.
+///
+/// Only the link types whose header has a known minimum length are checked, see `DataLink::min_header_len()`,
+/// e.g. an Ethernet frame must contain at least its 14 bytes header.
+/// Nothing is checked when reading the packets, this validation must be called explicitly.
+///
+/// Returns an empty `Vec` if the packet looks valid.
+///
+/// # Examples
+///
+/// ```rust
+/// use pcap_file::{validate_against_linktype, DataLink, Warning};
+///
+/// let warnings = validate_against_linktype(DataLink::ETHERNET, &[0; 10], 10);
+/// assert_eq!(warnings, vec![Warning::ShorterThanHeader { datalink: DataLink::ETHERNET, len: 10, min_len: 14 }]);
+///
+/// assert!(validate_against_linktype(DataLink::ETHERNET, &[0; 60], 60).is_empty());
+/// ```
+pub fn validate_against_linktype(datalink: DataLink, data: &[u8], original_len: u32) -> Vec<Warning> {
+
+    let mut warnings = vec![];
+
+    if let Some(min_len) = datalink.min_header_len() {
+        if data.len() < min_len {
+            warnings.push(Warning::ShorterThanHeader { datalink, len: data.len(), min_len });
+        }
+    }
+
+    if data.len() as u64 > original_len as u64 {
+        warnings.push(Warning::CapturedLongerThanOriginal { captured_len: data.len(), original_len });
+    }
+
+    warnings
+}
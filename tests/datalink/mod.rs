@@ -0,0 +1,137 @@
+use pcap_file::{validate_against_linktype, DataLink, Warning};
+use pcap_file::pcap::Packet;
+use pcap_file::pcapng::EnhancedPacketBlock;
+use std::borrow::Cow;
+
+#[test]
+fn round_trip() {
+
+    for code in 0..=0xFFFF_u32 {
+        let datalink = DataLink::from(code);
+        assert_eq!(u32::from(datalink), code, "{:?}", datalink);
+    }
+
+    assert_eq!(DataLink::from(u32::MAX), DataLink::Unknown(u32::MAX));
+}
+
+#[test]
+fn common_link_types() {
+
+    let known = [
+        (0, DataLink::NULL),
+        (1, DataLink::ETHERNET),
+        (101, DataLink::RAW),
+        (105, DataLink::IEEE802_11),
+        (113, DataLink::LINUX_SLL),
+        (127, DataLink::IEEE802_11_RADIOTAP),
+        (189, DataLink::USB_LINUX),
+        (228, DataLink::IPV4),
+        (229, DataLink::IPV6),
+        (239, DataLink::NFLOG),
+        (252, DataLink::WIRESHARK_UPPER_PDU),
+        (276, DataLink::LINUX_SLL2),
+    ];
+
+    for (code, datalink) in known.iter() {
+        assert_eq!(DataLink::from(*code), *datalink);
+    }
+}
+
+#[test]
+fn display_from_str() {
+
+    for code in 0..=0xFFFF_u32 {
+        let datalink = DataLink::from(code);
+        let name = datalink.to_string();
+
+        assert_eq!(name.parse::<DataLink>().unwrap(), datalink);
+        assert_eq!(name.to_lowercase().parse::<DataLink>().unwrap(), datalink);
+    }
+
+    assert_eq!(DataLink::ETHERNET.to_string(), "ETHERNET");
+    assert_eq!(DataLink::LINUX_SLL.to_string(), "LINUX_SLL");
+    assert_eq!("ethernet".parse::<DataLink>().unwrap(), DataLink::ETHERNET);
+    assert!("not_a_link_type".parse::<DataLink>().is_err());
+}
+
+#[test]
+fn validate() {
+
+    assert!(validate_against_linktype(DataLink::ETHERNET, &[0; 14], 14).is_empty());
+    assert!(validate_against_linktype(DataLink::RAW, &[], 0).is_empty());
+
+    // No known minimum length
+    assert!(validate_against_linktype(DataLink::IEEE802_11, &[0; 1], 1).is_empty());
+
+    assert_eq!(
+        validate_against_linktype(DataLink::LINUX_SLL, &[0; 4], 2),
+        [
+            Warning::ShorterThanHeader { datalink: DataLink::LINUX_SLL, len: 4, min_len: 16 },
+            Warning::CapturedLongerThanOriginal { captured_len: 4, original_len: 2 }
+        ]
+    );
+
+    let packet = Packet::new(0, 0, &[0; 3], 100);
+    assert_eq!(packet.validate_against_linktype(DataLink::NULL), [Warning::ShorterThanHeader { datalink: DataLink::NULL, len: 3, min_len: 4 }]);
+    assert!(packet.validate_against_linktype(DataLink::IPV4).is_empty());
+
+    let packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 10,
+        original_len: 10,
+        data: Cow::Borrowed(&[0; 10]),
+        options: vec![]
+    };
+    assert_eq!(packet.validate_against_linktype(DataLink::ETHERNET).len(), 1);
+}
+
+#[test]
+fn min_header_len() {
+
+    assert_eq!(DataLink::NULL.min_header_len(), Some(4));
+    assert_eq!(DataLink::LOOP.min_header_len(), Some(4));
+    assert_eq!(DataLink::ETHERNET.min_header_len(), Some(14));
+    assert_eq!(DataLink::LINUX_SLL.min_header_len(), Some(16));
+    assert_eq!(DataLink::LINUX_SLL2.min_header_len(), Some(20));
+    assert_eq!(DataLink::RAW.min_header_len(), Some(0));
+    assert_eq!(DataLink::IPV6.min_header_len(), Some(0));
+    assert_eq!(DataLink::IEEE802_11.min_header_len(), None);
+    assert_eq!(DataLink::Unknown(0xFFFF).min_header_len(), None);
+}
+
+#[test]
+fn usb_bluetooth() {
+
+    let codes = [
+        (249, DataLink::USBPCAP),
+        (187, DataLink::BLUETOOTH_HCI_H4),
+        (201, DataLink::BLUETOOTH_HCI_H4_WITH_PHDR),
+        (220, DataLink::USB_LINUX_MMAPPED)
+    ];
+
+    for &(code, datalink) in &codes {
+        assert_eq!(DataLink::from(code), datalink);
+        assert_eq!(u32::from(datalink), code);
+    }
+}
+
+#[test]
+fn all_known() {
+
+    let mut count = 0;
+    for datalink in DataLink::all_known() {
+        assert!(datalink.is_known());
+        assert_eq!(DataLink::from(u32::from(datalink)), datalink);
+        assert_eq!(datalink.to_string().parse::<DataLink>().unwrap(), datalink);
+        assert_eq!(datalink.name(), Some(format!("{:?}", datalink).as_str()));
+        count += 1;
+    }
+
+    assert!(count > 100);
+    assert!(DataLink::all_known().any(|datalink| datalink == DataLink::ETHERNET));
+    assert!(!DataLink::Unknown(0xFFFF).is_known());
+    assert!(!DataLink::from(0xFFFF).is_known());
+    assert_eq!(DataLink::Unknown(0xFFFF).name(), None);
+    assert_eq!(DataLink::Unknown(0xFFFF).to_string(), "Unknown(65535)");
+}
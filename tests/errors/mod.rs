@@ -0,0 +1,161 @@
+use pcap_file::PcapError;
+use pcap_file::pcapng::PcapNgReader;
+use std::error::Error;
+
+fn read_empty() -> Result<(), Box<dyn Error>> {
+    PcapNgReader::new(&[][..])?;
+    Ok(())
+}
+
+#[test]
+fn boxed_error() {
+
+    let err = read_empty().unwrap_err();
+
+    let err = err.downcast_ref::<PcapError>().unwrap();
+    assert_eq!(err.to_string(), "Error reading bytes at offset 0");
+
+    // AtOffset -> IoError -> io::Error
+    let source = err.source().unwrap();
+    assert_eq!(source.to_string(), "Error reading bytes");
+
+    let io_err = source.source().unwrap().downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(io_err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn display() {
+
+    assert_eq!(PcapError::IncompleteBuffer(4).to_string(), "Need at least 4 more bytes");
+    assert_eq!(PcapError::InvalidField("Block: initial_len < 12").to_string(), "Invalid field value: Block: initial_len < 12");
+    assert_eq!(
+        PcapError::BlockLengthMismatch { start: 16, end: 24 }.to_string(),
+        "Block length mismatch: leading length 16 != trailing length 24"
+    );
+}
+
+#[test]
+fn truncated_blocks() {
+
+    use byteorder::{BigEndian, LittleEndian};
+    use pcap_file::pcapng::{Block, BlockType, ParsedBlock};
+
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+
+        // Every truncation of every block body must fail or succeed without panicking
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        while let Some(block) = pcapng_reader.next_block() {
+
+            let block = block.unwrap();
+            for len in 0..block.body.len() {
+                let body = &block.body[..len];
+                let _ = ParsedBlock::from_slice::<BigEndian>(block.type_, body);
+                let _ = ParsedBlock::from_slice::<LittleEndian>(block.type_, body);
+            }
+        }
+
+        for len in 0..data.len() {
+            let _ = Block::from_slice::<BigEndian>(&data[..len]);
+            let _ = Block::from_slice::<LittleEndian>(&data[..len]);
+            let _ = ParsedBlock::from_slice::<BigEndian>(BlockType::InterfaceDescription, &data[..len]);
+        }
+    }
+}
+
+#[test]
+fn pcap_usec_overflow() {
+
+    use pcap_file::pcap::PcapReader;
+
+    let mut data = std::fs::read("tests/pcap/little_endian.pcap").unwrap();
+
+    // ts_usec of the first packet, which can't be converted to nanoseconds
+    data[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut pcap_reader = PcapReader::new(&data[..]).unwrap();
+    match pcap_reader.next() {
+        Some(Err(PcapError::AtOffset { source, .. })) => assert!(matches!(*source, PcapError::InvalidField(_))),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn incomplete_buffer_len() {
+
+    use byteorder::BigEndian;
+    use pcap_file::pcapng::Block;
+
+    // Header of a 28 bytes block followed by 4 bytes of its body
+    let data = [0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00];
+    match Block::from_slice::<BigEndian>(&data) {
+        Err(PcapError::IncompleteBuffer(len)) => assert_eq!(len, 16),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+
+    match Block::from_slice::<BigEndian>(&data[..5]) {
+        Err(PcapError::IncompleteBuffer(len)) => assert_eq!(len, 7),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+}
+
+#[test]
+fn invalid_option() {
+
+    use byteorder::LittleEndian;
+    use pcap_file::pcapng::InterfaceDescriptionBlock;
+
+    // if_tsresol with a 2 bytes value
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x09, 0x00, 0x02, 0x00, 0x06, 0x00, 0x00, 0x00
+    ];
+
+    let err = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap_err();
+    assert!(matches!(err, PcapError::InvalidOption { code: 9, .. }));
+    assert_eq!(err.to_string(), "Invalid option 9: InterfaceDescriptionOption: IfTsResol length != 1");
+}
+
+#[test]
+fn short_block_len() {
+
+    use pcap_file::Endianness;
+    use pcap_file::pcapng::{PcapNgDecoder, PcapNgParser, PcapNgWriter};
+
+    let section = PcapNgWriter::new(vec![]).unwrap().into_writer();
+
+    // Blocks declaring a total length of 0, of less than the 12 bytes of the framing or not a multiple of 4
+    for len in [0_u32, 4, 8, 13] {
+
+        let mut data = section.clone();
+        for _ in 0..4 {
+            match PcapNgReader::new(&section[..]).unwrap().section().endianness() {
+                Endianness::Big => [1_u32, len].iter().for_each(|word| data.extend_from_slice(&word.to_be_bytes())),
+                Endianness::Little => [1_u32, len].iter().for_each(|word| data.extend_from_slice(&word.to_le_bytes()))
+            }
+            data.extend_from_slice(&[0_u8; 8]);
+        }
+
+        let is_invalid = |err: &PcapError| match err {
+            PcapError::AtOffset { source, offset } => *offset == section.len() as u64 && matches!(**source, PcapError::InvalidField(_)),
+            err => matches!(err, PcapError::InvalidField(_))
+        };
+
+        // The reader progresses: each error consumes the header of the block and the stream ends
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        let err = pcapng_reader.next_block().unwrap().unwrap_err();
+        assert!(is_invalid(&err), "{:?}", err);
+        assert!(pcapng_reader.take(data.len()).count() < data.len());
+
+        let (rem, mut pcapng_parser) = PcapNgParser::new(&data[..]).unwrap();
+        let err = pcapng_parser.next_block(rem).unwrap_err();
+        assert!(is_invalid(&err), "{:?}", err);
+
+        let mut decoder = PcapNgDecoder::new();
+        decoder.push(&data);
+        decoder.next_block().unwrap();
+        let err = decoder.next_block().unwrap_err();
+        assert!(is_invalid(&err), "{:?}", err);
+    }
+}
@@ -0,0 +1,45 @@
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use pcap_file::MaybeGzReader;
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::PcapNgReader;
+use std::io::Write;
+
+static PCAP: &[u8] = include_bytes!("../pcap/little_endian.pcap");
+static PCAPNG: &[u8] = include_bytes!("../pcapng/little_endian/advanced/test100.pcapng");
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn sniff() {
+
+    let compressed = gzip(PCAPNG);
+
+    let reader = MaybeGzReader::new(&compressed[..]).unwrap();
+    assert!(reader.is_gzip());
+    let blocks: Vec<_> = PcapNgReader::new(reader).unwrap().map(|block| format!("{:?}", block.unwrap())).collect();
+
+    let reader = MaybeGzReader::new(PCAPNG).unwrap();
+    assert!(!reader.is_gzip());
+    let plain_blocks: Vec<_> = PcapNgReader::new(reader).unwrap().map(|block| format!("{:?}", block.unwrap())).collect();
+
+    assert!(!blocks.is_empty());
+    assert_eq!(blocks, plain_blocks);
+}
+
+#[test]
+fn from_gz_path() {
+
+    let path = std::env::temp_dir().join(format!("pcap_file_from_gz_path_{}.pcap.gz", std::process::id()));
+    std::fs::write(&path, gzip(PCAP)).unwrap();
+
+    let packets: Vec<_> = PcapReader::from_gz_path(&path).unwrap().map(|packet| packet.unwrap()).collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!packets.is_empty());
+    assert_eq!(packets.len(), PcapReader::new(PCAP).unwrap().count());
+}
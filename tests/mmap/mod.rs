@@ -0,0 +1,32 @@
+use pcap_file::MmapFile;
+use pcap_file::pcapng::{index, PcapNgParser};
+use std::fs::File;
+use std::io::Read;
+
+#[test]
+fn mmap_parse() {
+
+    let path = "tests/pcapng/little_endian/advanced/test100.pcapng";
+
+    // Safety: the test files are never modified
+    let mapped = unsafe { MmapFile::open(path) }.unwrap();
+
+    let mut data = Vec::new();
+    File::open(path).unwrap().read_to_end(&mut data).unwrap();
+    assert_eq!(mapped.as_slice(), &data[..]);
+
+    let entries = mapped.pcapng_index().unwrap();
+    assert_eq!(entries, index(&mut &data[..]).unwrap());
+
+    let (mut src, mut pcapng_parser) = PcapNgParser::new(&mapped).unwrap();
+    let mut nb_blocks = 1;
+    while !src.is_empty() {
+        let (rem, block) = pcapng_parser.next_block(src).unwrap();
+        assert_eq!(block.initial_len, entries[nb_blocks].len);
+
+        src = rem;
+        nb_blocks += 1;
+    }
+
+    assert_eq!(nb_blocks, entries.len());
+}
@@ -0,0 +1,62 @@
+use pcap_file::{DataLink, PacketSource};
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::{pcap_to_pcapng, EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, PcapNgReader, PcapNgWriter, SimplePacketBlock};
+use std::borrow::Cow;
+use std::time::Duration;
+
+static DATA: &[u8] = include_bytes!("../pcap/little_endian.pcap");
+
+fn collect<S: PacketSource>(source: &mut S) -> Vec<(Duration, DataLink, u32, Vec<u8>)> {
+
+    let mut packets = vec![];
+    while let Some(packet) = source.next_packet() {
+        let packet = packet.unwrap();
+        packets.push((packet.timestamp, packet.interface_linktype, packet.orig_len, packet.data.into_owned()));
+    }
+
+    packets
+}
+
+#[test]
+fn pcap_and_pcapng() {
+
+    let mut pcapng = vec![];
+    pcap_to_pcapng(DATA, &mut pcapng).unwrap();
+
+    let pcap_packets = collect(&mut PcapReader::new(DATA).unwrap());
+    let pcapng_packets = collect(&mut PcapNgReader::new(&pcapng[..]).unwrap());
+
+    assert!(!pcap_packets.is_empty());
+    assert_eq!(pcap_packets, pcapng_packets);
+}
+
+#[test]
+fn pcapng_packet_kinds() {
+
+    let mut pcapng_writer = PcapNgWriter::new(vec![]).unwrap();
+
+    let interface = InterfaceDescriptionBlock::builder().linktype(DataLink::ETHERNET).build();
+    pcapng_writer.write_interface(&interface).unwrap();
+
+    let spb = SimplePacketBlock { original_len: 4, data: Cow::Borrowed(&[1, 2, 3, 4]) };
+    pcapng_writer.write_block(&ParsedBlock::SimplePacket(spb)).unwrap();
+
+    // Microsecond resolution by default
+    let epb = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 1_500_000,
+        captured_len: 2,
+        original_len: 8,
+        data: Cow::Borrowed(&[5, 6]),
+        options: vec![]
+    };
+    pcapng_writer.write_block(&ParsedBlock::EnhancedPacket(epb)).unwrap();
+
+    let data = pcapng_writer.into_writer();
+    let packets = collect(&mut PcapNgReader::new(&data[..]).unwrap());
+
+    assert_eq!(packets, vec![
+        (Duration::from_secs(0), DataLink::ETHERNET, 4, vec![1, 2, 3, 4]),
+        (Duration::from_millis(1500), DataLink::ETHERNET, 8, vec![5, 6])
+    ]);
+}
@@ -103,4 +103,154 @@ fn little_endian() {
     };
 
     assert_eq!(packet.header, pkt_hdr);
-}
\ No newline at end of file
+}
+
+#[test]
+fn error_offset() {
+
+    // Truncate the last packet of the file
+    let data = &DATA[..DATA.len() - 1];
+
+    let mut offset = 24;
+    for pcap in PcapReader::new(data).unwrap() {
+        match pcap {
+            Ok(pcap) => offset += 16 + pcap.data.len() as u64,
+            Err(pcap_file::PcapError::AtOffset { offset: err_offset, .. }) => {
+                assert_eq!(err_offset, offset);
+                return;
+            },
+            Err(err) => panic!("Unexpected error: {:?}", err)
+        }
+    }
+
+    panic!("The truncated packet has not been reported");
+}
+
+#[test]
+fn read_packet_nanosecond() {
+
+    let mut data = vec![
+        0xa1, 0xb2, 0x3c, 0x4d, 0x00, 0x02, 0x00, 0x04,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01
+    ];
+    data.extend_from_slice(&1_u32.to_be_bytes());
+    data.extend_from_slice(&500_000_001_u32.to_be_bytes());
+    data.extend_from_slice(&2_u32.to_be_bytes());
+    data.extend_from_slice(&4_u32.to_be_bytes());
+    data.extend_from_slice(&[0xAA, 0xBB]);
+
+    let mut pcap_reader = PcapReader::new(&data[..]).unwrap();
+    assert_eq!(pcap_reader.header.ts_resolution(), pcap_file::TsResolution::NanoSecond);
+    assert_eq!(pcap_reader.header.endianness(), pcap_file::Endianness::Big);
+    assert_eq!(pcap_reader.header.datalink, pcap_file::DataLink::ETHERNET);
+
+    let packet = pcap_reader.read_packet().unwrap().unwrap();
+    assert_eq!(packet.header, PacketHeader::new(1, 500_000_001, 2, 4));
+    assert_eq!(&packet.data[..], &[0xAA, 0xBB]);
+
+    assert!(pcap_reader.read_packet().is_none());
+}
+
+fn write_read(ts_resolution: pcap_file::TsResolution) {
+
+    let mut pcap_writer = PcapWriter::with_ts_resolution(ts_resolution, Vec::new()).unwrap();
+    assert_eq!(pcap_writer.header.ts_resolution(), ts_resolution);
+
+    let data = [0x01_u8, 0x02, 0x03, 0x04];
+    for i in 0..4 {
+        pcap_writer.write(i, 123_456_789, &data[..i as usize], 4).unwrap();
+    }
+
+    let out = pcap_writer.into_writer();
+    let pcap_reader = PcapReader::new(&out[..]).unwrap();
+    assert_eq!(pcap_reader.header.ts_resolution(), ts_resolution);
+
+    let packets: Vec<_> = pcap_reader.map(|packet| packet.unwrap()).collect();
+    assert_eq!(packets.len(), 4);
+
+    for (i, packet) in packets.iter().enumerate() {
+        let ts_nsec = match ts_resolution {
+            pcap_file::TsResolution::MicroSecond => 123_456_000,
+            pcap_file::TsResolution::NanoSecond => 123_456_789
+        };
+
+        assert_eq!(packet.header, PacketHeader::new(i as u32, ts_nsec, i as u32, 4));
+        assert_eq!(&packet.data[..], &data[..i]);
+    }
+}
+
+#[test]
+fn write_read_microsecond() {
+    write_read(pcap_file::TsResolution::MicroSecond);
+}
+
+#[test]
+fn write_read_nanosecond() {
+    write_read(pcap_file::TsResolution::NanoSecond);
+}
+
+#[test]
+fn write_snaplen_exceeded() {
+
+    let header = PcapHeader {
+        snaplen: 2,
+        ..Default::default()
+    };
+
+    let mut pcap_writer = PcapWriter::with_header(header, Vec::new()).unwrap();
+    pcap_writer.write(0, 0, &[1, 2], 2).unwrap();
+
+    match pcap_writer.write(0, 0, &[1, 2, 3], 3) {
+        Err(pcap_file::PcapError::SnaplenExceeded { len: 3, snaplen: 2 }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn write_snaplen_zero() {
+
+    // A snaplen of 0 doesn't limit the packets
+    let header = PcapHeader {
+        snaplen: 0,
+        ..Default::default()
+    };
+
+    let mut pcap_writer = PcapWriter::with_header(header, Vec::new()).unwrap();
+    pcap_writer.write(0, 0, &[1, 2, 3], 3).unwrap();
+    assert_eq!(pcap_writer.get_ref().len(), 24 + 16 + 3);
+}
+
+#[test]
+fn timestamp_resolution() {
+
+    use pcap_file::pcap::PcapParser;
+    use pcap_file::TsResolution;
+    use std::time::Duration;
+
+    // Same packets as little_endian.pcap, the timestamps of the nth packet are n nanoseconds later
+    let data_nsec = &include_bytes!("little_endian_nanosecond.pcap")[..];
+
+    let pcap_reader = PcapReader::new(&DATA[..]).unwrap();
+    assert_eq!(pcap_reader.timestamp_resolution(), TsResolution::MicroSecond);
+    let usec: Vec<_> = pcap_reader.map(|packet| packet.unwrap().header.timestamp()).collect();
+
+    let pcap_reader = PcapReader::new(data_nsec).unwrap();
+    assert_eq!(pcap_reader.timestamp_resolution(), TsResolution::NanoSecond);
+    let nsec: Vec<_> = pcap_reader.map(|packet| packet.unwrap().header.timestamp()).collect();
+
+    assert_eq!(usec.len(), 10);
+    assert_eq!(usec[0], Duration::new(0x4f633248, 0));
+    for (i, (usec, nsec)) in usec.iter().zip(&nsec).enumerate() {
+        assert_eq!(usec.subsec_nanos() % 1000, 0);
+        assert_eq!(*nsec, *usec + Duration::from_nanos(i as u64));
+    }
+
+    let (_, pcap_parser) = PcapParser::new(&DATA[..]).unwrap();
+    assert_eq!(pcap_parser.timestamp_resolution(), TsResolution::MicroSecond);
+
+    let (rem, pcap_parser) = PcapParser::new(data_nsec).unwrap();
+    assert_eq!(pcap_parser.timestamp_resolution(), TsResolution::NanoSecond);
+    let (_, packet) = pcap_parser.next_packet(rem).unwrap();
+    assert_eq!(packet.header.timestamp(), nsec[0]);
+}
@@ -0,0 +1,62 @@
+use pcap_file::PcapError;
+use pcap_file::pcapng::{AsyncPcapNgReader, PcapNgReader};
+use std::fs::File;
+use std::io::Read;
+use glob::glob;
+
+#[tokio::test]
+async fn async_reader_matches_reader() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+        let entry = entry.unwrap();
+
+        let mut data = Vec::new();
+        File::open(&entry).unwrap().read_to_end(&mut data).unwrap();
+
+        let mut async_reader = AsyncPcapNgReader::new(&data[..]).await.unwrap();
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        assert_eq!(format!("{:?}", async_reader.section()), format!("{:?}", pcapng_reader.section()));
+
+        while let Some(block) = pcapng_reader.next_block() {
+            let block = block.unwrap();
+            let async_block = async_reader.next_block().await
+                .unwrap_or_else(|| panic!("Missing block on file {:?}", entry))
+                .unwrap_or_else(|err| panic!("Error on file {:?}: {}", entry, err));
+
+            assert_eq!(format!("{:?}", async_block), format!("{:?}", block));
+            assert_eq!(async_reader.interfaces().len(), pcapng_reader.interfaces().len());
+        }
+
+        assert!(async_reader.next_block().await.is_none());
+    }
+}
+
+#[tokio::test]
+async fn async_reader_errors() {
+
+    // A SectionHeaderBlock followed by a SimplePacketBlock of 36 bytes
+    let mut data = vec![
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00,
+        0x03, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00
+    ];
+    data.extend_from_slice(&[0_u8; 24]);
+    data.extend_from_slice(&[0x24, 0x00, 0x00, 0x00]);
+
+    let mut async_reader = AsyncPcapNgReader::with_max_block_len(32, &data[..]).await.unwrap();
+    match async_reader.next_block().await {
+        Some(Err(PcapError::AtOffset { offset: 28, source })) => {
+            assert!(matches!(*source, PcapError::BlockTooLarge { len: 36, max: 32 }))
+        },
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // Truncated block
+    let mut async_reader = AsyncPcapNgReader::new(&data[..data.len() - 2]).await.unwrap();
+    match async_reader.next_block().await {
+        Some(Err(PcapError::AtOffset { offset: 28, source })) => assert!(matches!(*source, PcapError::IoError(_))),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
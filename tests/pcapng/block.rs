@@ -0,0 +1,516 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use pcap_file::{Endianness, PcapError};
+use pcap_file::pcapng::{Block, BlockType, ParsedBlock, PcapNgReader};
+use super::common::framed;
+
+fn dispatch<B: ByteOrder>() {
+
+    let mut body = [0_u8; 8];
+    B::write_u16(&mut body[0..2], 1);
+    B::write_u32(&mut body[4..8], 1500);
+
+    let mut data = framed::<B>(0x00000001, &body);
+    data.extend(framed::<B>(0x12345678, b"abcd"));
+
+    let (rem, block) = Block::from_slice::<B>(&data[..]).unwrap();
+    assert_eq!(block.type_, BlockType::InterfaceDescription);
+    assert_eq!(block.endianness(), Endianness::new::<B>());
+
+    match block.parsed().unwrap() {
+        ParsedBlock::InterfaceDescription(idb) => assert_eq!(idb.snaplen(), 1500),
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+
+    let (rem, block) = Block::from_slice::<B>(rem).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(block.type_, BlockType::Unknown(0x12345678));
+
+    match block.parsed().unwrap() {
+        ParsedBlock::Unknown(unknown) => assert_eq!(&unknown.value[..], b"abcd"),
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+}
+
+endian_tests!(dispatch => dispatch_le, dispatch_be);
+
+#[test]
+fn length_mismatch() {
+
+    let mut data = framed::<LittleEndian>(0x00000003, &[0, 0, 0, 0]);
+    let end = data.len();
+    LittleEndian::write_u32(&mut data[end - 4..], 24);
+
+    match Block::from_slice::<LittleEndian>(&data[..]) {
+        Err(PcapError::BlockLengthMismatch { start: 16, end: 24 }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    match Block::from_reader::<_, LittleEndian>(&mut &data[..]) {
+        Err(PcapError::BlockLengthMismatch { start: 16, end: 24 }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn reader_error_offset() {
+
+    // A SectionHeaderBlock followed by a truncated SimplePacketBlock
+    let mut data = vec![
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00
+    ];
+    data.extend(framed::<LittleEndian>(0x00000003, &[0, 0, 0, 0]));
+    data.truncate(data.len() - 2);
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+
+    match pcapng_reader.next() {
+        Some(Err(PcapError::AtOffset { offset: 28, source })) => assert!(matches!(*source, PcapError::IoError(_))),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn reader_resync_error_offset() {
+
+    // A SectionHeaderBlock, the header of an EnhancedPacketBlock with a garbage length, 4 bytes of junk,
+    // a SimplePacketBlock at 40 and a truncated SimplePacketBlock at 56
+    let mut data = vec![
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00,
+        0x06, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF,
+        0xAA, 0xBB, 0xCC, 0xDD
+    ];
+    data.extend(framed::<LittleEndian>(0x00000003, &[0, 0, 0, 0]));
+    data.extend(framed::<LittleEndian>(0x00000003, &[0, 0, 0, 0]));
+    data.truncate(data.len() - 2);
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+
+    match pcapng_reader.next() {
+        Some(Err(PcapError::AtOffset { offset: 28, source })) => assert!(matches!(*source, PcapError::BlockTooLarge { .. })),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // The header of the failed block has been consumed, only the junk is skipped
+    assert_eq!(pcapng_reader.resync(100).unwrap(), Some(4));
+    assert_eq!(pcapng_reader.next().unwrap().unwrap().type_, BlockType::SimplePacket);
+
+    match pcapng_reader.next() {
+        Some(Err(PcapError::AtOffset { offset: 56, source })) => assert!(matches!(*source, PcapError::IoError(_))),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn reader_forged_length() {
+
+    // A SimplePacketBlock declaring 4 GiB without the data
+    let mut data = [0_u8; 12];
+    LittleEndian::write_u32(&mut data[0..4], 0x00000003);
+    LittleEndian::write_u32(&mut data[4..8], 0xFFFF_FFFC);
+
+    match Block::from_reader::<_, LittleEndian>(&mut &data[..]) {
+        Err(PcapError::IoError(err)) => assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn reader_max_block_len() {
+
+    // A SectionHeaderBlock followed by a SimplePacketBlock of 36 bytes
+    let mut data = vec![
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00
+    ];
+    data.extend(framed::<LittleEndian>(0x00000003, &[0_u8; 24]));
+
+    let mut pcapng_reader = PcapNgReader::with_max_block_len(32, &data[..]).unwrap();
+    assert_eq!(pcapng_reader.max_block_len(), 32);
+
+    match pcapng_reader.next() {
+        Some(Err(PcapError::AtOffset { offset: 28, source })) => {
+            assert!(matches!(*source, PcapError::BlockTooLarge { len: 36, max: 32 }))
+        },
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // The block type and the length have been consumed, the next block starts in the body
+    match pcapng_reader.next() {
+        Some(Err(PcapError::AtOffset { offset: 36, source })) => assert!(matches!(*source, PcapError::InvalidField(_))),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    assert_eq!(pcapng_reader.max_block_len(), PcapNgReader::<&[u8]>::DEFAULT_MAX_BLOCK_LEN);
+    assert!(pcapng_reader.next().unwrap().is_ok());
+
+    // The SectionHeaderBlock is checked too
+    match PcapNgReader::with_max_block_len(16, &data[..]) {
+        Err(PcapError::AtOffset { offset: 0, source }) => {
+            assert!(matches!(*source, PcapError::BlockTooLarge { len: 28, max: 16 }))
+        },
+        res => panic!("Unexpected result: {:?}", res.is_ok())
+    }
+}
+
+#[test]
+fn unknown_raw_body() {
+
+    let data = framed::<LittleEndian>(0x12345678, b"abcdefgh");
+    let body = &data[8..16];
+
+    let (rem, parsed) = ParsedBlock::from_slice::<LittleEndian>(BlockType::Unknown(0x12345678), body).unwrap();
+    assert!(rem.is_empty());
+
+    match parsed {
+        ParsedBlock::Unknown(unknown) => {
+            assert_eq!(unknown.raw_body(), b"abcdefgh");
+
+            // The body is borrowed from the slice, not copied
+            assert_eq!(unknown.raw_body().as_ptr(), body.as_ptr());
+        },
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+}
+
+#[test]
+fn block_type_codes() {
+
+    for code in (0..0x1000_u32).chain([0x0A0D0D0A, 0x40000BAD, 0x80000000, 0xFFFFFFFF]) {
+        let type_ = BlockType::from_u32(code);
+        assert_eq!(type_.as_u32(), code);
+        assert_eq!(u32::from(type_), code);
+        assert_eq!(BlockType::from(code), type_);
+    }
+
+    assert_eq!(BlockType::from_u32(0x00000BAD), BlockType::Custom);
+    assert_eq!(BlockType::from_u32(0x00000007), BlockType::Unknown(7));
+
+    assert!(BlockType::Unknown(0x80000000).is_local_use());
+    assert!(!BlockType::CustomNonCopyable.is_local_use());
+}
+
+#[test]
+fn from_slice_endian() {
+
+    // An InterfaceDescriptionBlock whose byte order is only known at runtime
+    let body = [0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF];
+
+    for &endianness in &[Endianness::Big, Endianness::Little] {
+
+        let data = match endianness {
+            Endianness::Big => framed::<BigEndian>(1, &body),
+            Endianness::Little => framed::<LittleEndian>(1, &body)
+        };
+
+        let (rem, block) = Block::from_slice_endian(&data, endianness).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(block.endianness(), endianness);
+
+        let (_, parsed) = ParsedBlock::from_slice_endian(block.type_, &block.body, endianness).unwrap();
+        let interface = parsed.into_interface_description().unwrap();
+        let fields = (u32::from(interface.linktype), interface.snaplen);
+        match endianness {
+            Endianness::Big => assert_eq!(fields, (1, 0xFFFF)),
+            Endianness::Little => assert_eq!(fields, (0x100, 0xFFFF0000))
+        }
+    }
+}
+
+#[test]
+fn serialized_len() {
+
+    use pcap_file::pcapng::PcapNgWriter;
+
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+
+        let section = pcapng_reader.section().clone();
+        let mut pcapng_writer = PcapNgWriter::with_section_header(section.clone(), vec![]).unwrap();
+        assert_eq!(section.serialized_len(), pcapng_writer.get_ref().len());
+
+        while let Some(block) = pcapng_reader.next_block() {
+
+            let block = block.unwrap();
+            let parsed = block.parsed().unwrap();
+
+            let len = parsed.serialized_len();
+            assert_eq!(pcapng_writer.write_block(&parsed).unwrap(), len);
+
+            match &parsed {
+                ParsedBlock::InterfaceDescription(interface) => {
+                    assert_eq!(interface.serialized_len(), len);
+                    for opt in interface.options() {
+                        assert_eq!(opt.serialized_len(), opt.write_to::<_, LittleEndian>(&mut vec![]).unwrap());
+                    }
+                },
+                ParsedBlock::EnhancedPacket(packet) => {
+                    assert_eq!(packet.serialized_len(), len);
+                    for opt in &packet.options {
+                        assert_eq!(opt.serialized_len(), opt.write_to::<_, LittleEndian>(&mut vec![]).unwrap());
+                    }
+                },
+                ParsedBlock::SimplePacket(packet) => assert_eq!(packet.serialized_len(), len),
+                ParsedBlock::NameResolution(names) => assert_eq!(names.serialized_len(), len),
+                ParsedBlock::InterfaceStatistics(stats) => assert_eq!(stats.serialized_len(), len),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn serialized_len_odd_lengths() {
+
+    use pcap_file::pcapng::*;
+    use std::borrow::Cow;
+
+    let custom_binary = CustomBinaryOption::new(32473, &[1, 2, 3], true);
+    let custom_utf8 = CustomUtf8Option::new(32473, "odd", false);
+
+    let interface = InterfaceDescriptionBlock {
+        linktype: pcap_file::DataLink::ETHERNET,
+        reserved: 0,
+        snaplen: 0,
+        options: vec![
+            InterfaceDescriptionOption::IfName("eth0x".into()),
+            InterfaceDescriptionOption::IfTsResol(9),
+            InterfaceDescriptionOption::IfFilter(Cow::Borrowed(b"\x00tcp")),
+            InterfaceDescriptionOption::CustomBinary(custom_binary.clone())
+        ]
+    };
+
+    let blocks = vec![
+        ParsedBlock::InterfaceDescription(interface),
+        ParsedBlock::EnhancedPacket(EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: 0,
+            captured_len: 5,
+            original_len: 5,
+            data: Cow::Borrowed(&[1, 2, 3, 4, 5]),
+            options: vec![
+                EnhancedPacketOption::Comment("a".into()),
+                EnhancedPacketOption::Hash(Cow::Borrowed(&[2, 0xAA, 0xBB])),
+                EnhancedPacketOption::Flags(1),
+                EnhancedPacketOption::DropCount(7),
+                EnhancedPacketOption::CustomUtf8(custom_utf8.clone()),
+                EnhancedPacketOption::Unknown(UnknownOption::new(0x8000, 1, &[9]))
+            ]
+        }),
+        ParsedBlock::Packet(PacketBlock {
+            interface_id: 0,
+            drop_count: 0,
+            timestamp: 0,
+            captured_len: 2,
+            original_len: 2,
+            data: Cow::Borrowed(&[1, 2]),
+            options: vec![PacketOption::Comment("abc".into()), PacketOption::Flags(0)]
+        }),
+        ParsedBlock::SimplePacket(SimplePacketBlock { original_len: 3, data: Cow::Borrowed(&[1, 2, 3]) }),
+        ParsedBlock::NameResolution(NameResolutionBlock {
+            records: vec![Record::Ipv4(Ipv4Record { ip_addr: Cow::Borrowed(&[10, 0, 0, 1]), names: vec!["a.b".into(), "cd".into()] })],
+            options: vec![NameResolutionOption::NsDnsName("dns".into())]
+        }),
+        ParsedBlock::InterfaceStatistics(InterfaceStatisticsBlock {
+            interface_id: 0,
+            timestamp: 0,
+            options: vec![InterfaceStatisticsOption::Comment("12345".into()), InterfaceStatisticsOption::IsbIfDrop(1)]
+        }),
+        ParsedBlock::DecryptionSecrets(DecryptionSecretsBlock {
+            secrets_type: DecryptionSecretsBlock::TLS_KEY_LOG_SECRETS,
+            secrets_data: Cow::Borrowed(b"secrets"),
+            options: vec![DecryptionSecretsOption::CustomBinary(custom_binary)]
+        }),
+        ParsedBlock::SystemdJournalExport(SystemdJournalExportBlock { journal_entry: Cow::Borrowed(b"MESSAGE=x\n") }),
+        ParsedBlock::Custom(CustomBlock { pen: 32473, copyable: true, data: Cow::Borrowed(&[1, 2, 3, 4, 5]) }),
+        ParsedBlock::Unknown(UnknownBlock::new(BlockType::Unknown(0x1234), 6, &[1, 2, 3, 4, 5, 6])),
+        ParsedBlock::SectionHeader(SectionHeaderBlock {
+            options: vec![SectionHeaderOption::Hardware("x86".into()), SectionHeaderOption::CustomUtf8(custom_utf8)],
+            ..Default::default()
+        })
+    ];
+
+    let mut pcapng_writer = PcapNgWriter::new(vec![]).unwrap();
+    for block in &blocks {
+        assert_eq!(block.serialized_len(), pcapng_writer.write_block(block).unwrap(), "{:?}", block);
+    }
+
+    if let ParsedBlock::EnhancedPacket(packet) = &blocks[1] {
+        for opt in &packet.options {
+            assert_eq!(opt.serialized_len(), opt.write_to::<_, BigEndian>(&mut vec![]).unwrap());
+        }
+    }
+}
+
+#[test]
+fn from_reader() {
+
+    use pcap_file::pcapng::{EnhancedPacketBlock, InterfaceDescriptionBlock, SectionHeaderBlock};
+
+    fn body(block: &ParsedBlock, endianness: Endianness) -> Vec<u8> {
+        let mut body = vec![];
+        match endianness {
+            Endianness::Big => block.write_to::<_, BigEndian>(&mut body).unwrap(),
+            Endianness::Little => block.write_to::<_, LittleEndian>(&mut body).unwrap()
+        };
+        body
+    }
+
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+
+        // The blocks read from a reader are the blocks parsed by the PcapNgReader
+        let mut src = &data[..];
+        let section = SectionHeaderBlock::from_reader(&mut src).unwrap();
+        let mut endianness = section.endianness();
+
+        let mut read = vec![];
+        while !src.is_empty() {
+            let block = match endianness {
+                Endianness::Big => ParsedBlock::from_reader::<_, BigEndian>(&mut src).unwrap(),
+                Endianness::Little => ParsedBlock::from_reader::<_, LittleEndian>(&mut src).unwrap()
+            };
+            if let ParsedBlock::SectionHeader(section) = &block {
+                endianness = section.endianness();
+            }
+            read.push(body(&block, endianness));
+        }
+
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        assert_eq!(pcapng_reader.section().endianness(), section.endianness());
+
+        let mut expected = vec![];
+        while let Some(block) = pcapng_reader.next_block() {
+            let block = block.unwrap();
+            expected.push(body(&block.parsed().unwrap(), pcapng_reader.section().endianness()));
+        }
+
+        assert_eq!(read, expected);
+    }
+
+    // The typed entry points check the type of the block
+    let interface = InterfaceDescriptionBlock::ethernet();
+    let mut body = vec![];
+    interface.write_to::<_, LittleEndian>(&mut body).unwrap();
+    let data = framed::<LittleEndian>(BlockType::InterfaceDescription.as_u32(), &body);
+
+    let read = InterfaceDescriptionBlock::from_reader::<_, LittleEndian>(&mut &data[..]).unwrap();
+    assert_eq!(read.linktype, interface.linktype);
+    assert_eq!(read.snaplen, interface.snaplen);
+
+    match EnhancedPacketBlock::from_reader::<_, LittleEndian>(&mut &data[..]) {
+        Err(PcapError::InvalidField(_)) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn raw_options() {
+
+    use pcap_file::pcapng::options::opt_to_writer;
+
+    fn written<B: ByteOrder>(raw: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut out = vec![];
+        for (code, value) in raw {
+            opt_to_writer::<_, B>(&mut out, *code, value).unwrap();
+        }
+        out
+    }
+
+    fn typed<B: ByteOrder>(block: &ParsedBlock) -> Option<(usize, Vec<u8>)> {
+        let mut out = vec![];
+        let len = match block {
+            ParsedBlock::SectionHeader(block) => { block.options.iter().for_each(|opt| { opt.write_to::<_, B>(&mut out).unwrap(); }); block.options.len() },
+            ParsedBlock::InterfaceDescription(block) => { block.options.iter().for_each(|opt| { opt.write_to::<_, B>(&mut out).unwrap(); }); block.options.len() },
+            ParsedBlock::EnhancedPacket(block) => { block.options.iter().for_each(|opt| { opt.write_to::<_, B>(&mut out).unwrap(); }); block.options.len() },
+            ParsedBlock::NameResolution(block) => { block.options.iter().for_each(|opt| { opt.write_to::<_, B>(&mut out).unwrap(); }); block.options.len() },
+            ParsedBlock::InterfaceStatistics(block) => { block.options.iter().for_each(|opt| { opt.write_to::<_, B>(&mut out).unwrap(); }); block.options.len() },
+            _ => return None
+        };
+        Some((len, out))
+    }
+
+    let mut nb_options = 0;
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+
+        while let Some(block) = pcapng_reader.next_block() {
+
+            let block = block.unwrap();
+            let raw = block.raw_options().unwrap();
+            let parsed = block.parsed().unwrap();
+
+            // The raw options are the options decoded by the typed parsers
+            let res = match block.endianness() {
+                Endianness::Big => typed::<BigEndian>(&parsed).map(|typed| (typed, written::<BigEndian>(&raw))),
+                Endianness::Little => typed::<LittleEndian>(&parsed).map(|typed| (typed, written::<LittleEndian>(&raw)))
+            };
+
+            if let Some(((len, typed), written)) = res {
+                assert_eq!(len, raw.len());
+                assert_eq!(typed, written);
+                nb_options += len;
+            }
+        }
+    }
+    assert!(nb_options > 0);
+
+    // A SimplePacketBlock has no options
+    let data = framed::<LittleEndian>(BlockType::SimplePacket.as_u32(), &[0x01, 0x00, 0x00, 0x00, 0xAA, 0x00, 0x00, 0x00]);
+    let (_, block) = Block::from_slice::<LittleEndian>(&data).unwrap();
+    assert!(block.raw_options().unwrap().is_empty());
+
+    // The raw value of an EnhancedPacketBlock option, after its padded packet data
+    let mut body = vec![0_u8; 20];
+    LittleEndian::write_u32(&mut body[12..16], 3);
+    LittleEndian::write_u32(&mut body[16..20], 3);
+    body.extend_from_slice(&[0xAA, 0xBB, 0xCC, 0x00]);
+    body.extend_from_slice(&[0x02, 0x00, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    let data = framed::<LittleEndian>(BlockType::EnhancedPacket.as_u32(), &body);
+    let (_, block) = Block::from_slice::<LittleEndian>(&data).unwrap();
+    assert_eq!(block.raw_options().unwrap(), vec![(2, &[0x01, 0x00, 0x00, 0x00][..])]);
+}
+
+#[test]
+fn parsed_round_trip() {
+
+    use pcap_file::pcapng::PcapNgWriter;
+
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+
+        let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), vec![]).unwrap();
+
+        let mut blocks = vec![];
+        while let Some(block) = pcapng_reader.next_block() {
+            let parsed = block.unwrap().parsed().unwrap().into_owned();
+            pcapng_writer.write_block(&parsed).unwrap();
+            blocks.push(parsed);
+        }
+
+        // Parse -> write -> parse gives the same blocks
+        let out = pcapng_writer.into_writer();
+        let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+        let mut written = vec![];
+        while let Some(block) = pcapng_reader.next_block() {
+            written.push(block.unwrap().parsed().unwrap().into_owned());
+        }
+
+        assert_eq!(written, blocks);
+    }
+}
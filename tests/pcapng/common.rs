@@ -0,0 +1,57 @@
+use byteorder::ByteOrder;
+
+/// Runs a test generic over the byte order once in little endian and once in big endian
+macro_rules! endian_tests {
+    ($func:ident => $le:ident, $be:ident) => {
+        #[test]
+        fn $le() {
+            $func::<byteorder::LittleEndian>();
+        }
+
+        #[test]
+        fn $be() {
+            $func::<byteorder::BigEndian>();
+        }
+    };
+}
+
+/// Frames a block body, which must already be padded, with its block type and its lengths
+pub fn framed<B: ByteOrder>(type_: u32, body: &[u8]) -> Vec<u8> {
+
+    let len = 12 + body.len() as u32;
+
+    let mut data = vec![0_u8; 8];
+    B::write_u32(&mut data[0..4], type_);
+    B::write_u32(&mut data[4..8], len);
+    data.extend_from_slice(body);
+    data.extend_from_slice(&[0_u8; 4]);
+    B::write_u32(&mut data[len as usize - 4..], len);
+
+    data
+}
+
+/// Encodes an option, or a name resolution record, padded to 32 bits
+pub fn opt<B: ByteOrder>(code: u16, value: &[u8]) -> Vec<u8> {
+
+    let pad_len = (4 - value.len() % 4) % 4;
+
+    let mut data = vec![0_u8; 4];
+    B::write_u16(&mut data[0..2], code);
+    B::write_u16(&mut data[2..4], value.len() as u16);
+    data.extend_from_slice(value);
+    data.extend_from_slice(&[0_u8; 3][..pad_len]);
+
+    data
+}
+
+/// Appends the options and the end of options marker to the fixed part of a block body
+pub fn body<B: ByteOrder>(fixed: &[u8], options: &[Vec<u8>]) -> Vec<u8> {
+
+    let mut data = fixed.to_vec();
+    for opt in options {
+        data.extend_from_slice(opt);
+    }
+    data.extend(opt::<B>(0, &[]));
+
+    data
+}
@@ -0,0 +1,72 @@
+use pcap_file::TsResolution;
+use pcap_file::pcap::{PcapReader, PcapWriter};
+use pcap_file::pcapng::{pcap_to_pcapng, ParsedBlock, PcapNgReader};
+use std::time::Duration;
+
+static DATA: &[u8] = include_bytes!("../pcap/little_endian.pcap");
+
+#[test]
+fn pcap_to_pcapng_round_trip() {
+
+    let mut out = Vec::new();
+    pcap_to_pcapng(DATA, &mut out).unwrap();
+
+    let pcap_reader = PcapReader::new(DATA).unwrap();
+    let header = pcap_reader.header;
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    assert_eq!(pcapng_reader.section().endianness(), header.endianness());
+
+    let block = pcapng_reader.next().unwrap().unwrap();
+    let interface = block.parsed().unwrap().into_interface_description().unwrap().into_owned();
+    assert_eq!(interface.linktype(), header.datalink);
+    assert_eq!(interface.snaplen(), header.snaplen);
+    assert_eq!(interface.if_tsresol(), Some(6));
+
+    let mut nb_packets = 0;
+    for (packet, block) in pcap_reader.zip(pcapng_reader) {
+        let packet = packet.unwrap();
+        let block = block.unwrap();
+
+        match block.parsed().unwrap() {
+            ParsedBlock::EnhancedPacket(epb) => {
+                assert_eq!(epb.interface_id, 0);
+                assert_eq!(interface.resolve_timestamp(epb.timestamp), packet.header.timestamp());
+                assert_eq!(epb.original_len, packet.header.orig_len);
+                assert_eq!(epb.data, packet.data);
+            },
+            parsed => panic!("Unexpected block: {:?}", parsed)
+        }
+
+        nb_packets += 1;
+    }
+
+    assert!(nb_packets > 0);
+}
+
+#[test]
+fn pcap_to_pcapng_nanosecond() {
+
+    let mut pcap_writer = PcapWriter::with_ts_resolution(TsResolution::NanoSecond, Vec::new()).unwrap();
+    pcap_writer.write(1_600_000_000, 123_456_789, &[1, 2, 3], 3).unwrap();
+    let pcap = pcap_writer.into_writer();
+
+    let mut out = Vec::new();
+    pcap_to_pcapng(&pcap[..], &mut out).unwrap();
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    pcapng_reader.next().unwrap().unwrap();
+    let interface = pcapng_reader.interfaces()[0].clone();
+    assert_eq!(interface.if_tsresol(), Some(9));
+
+    let block = pcapng_reader.next().unwrap().unwrap();
+    match block.parsed().unwrap() {
+        ParsedBlock::EnhancedPacket(epb) => {
+            assert_eq!(epb.timestamp, 1_600_000_000_123_456_789);
+            assert_eq!(interface.resolve_timestamp(epb.timestamp), Duration::new(1_600_000_000, 123_456_789));
+        },
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+
+    assert!(pcapng_reader.next().is_none());
+}
@@ -0,0 +1,67 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use pcap_file::pcapng::{Block, BlockType, CustomBlock, ParsedBlock, PcapNgReader, PcapNgWriter};
+use std::borrow::Cow;
+
+fn round_trip<B: ByteOrder>(type_: u32, copyable: bool) {
+
+    let mut data = [0_u8; 20];
+    B::write_u32(&mut data[0..4], type_);
+    B::write_u32(&mut data[4..8], 20);
+    B::write_u32(&mut data[8..12], 32473);
+    data[12..16].copy_from_slice(b"data");
+    B::write_u32(&mut data[16..20], 20);
+
+    let (rem, block) = Block::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+
+    let custom = match block.parsed().unwrap() {
+        ParsedBlock::Custom(custom) => custom.to_owned(),
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    };
+
+    assert_eq!(custom.pen, 32473);
+    assert_eq!(custom.copyable, copyable);
+    assert_eq!(&custom.data[..], b"data");
+
+    let parsed = ParsedBlock::Custom(custom);
+    assert_eq!(u32::from(parsed.block_type()), type_);
+
+    let mut out = Vec::new();
+    assert_eq!(parsed.write_to::<_, B>(&mut out).unwrap(), 8);
+    assert_eq!(&out[..], &data[8..16]);
+}
+
+#[test]
+fn copyable() {
+    round_trip::<LittleEndian>(0x00000BAD, true);
+    round_trip::<BigEndian>(0x00000BAD, true);
+}
+
+#[test]
+fn non_copyable() {
+    round_trip::<LittleEndian>(0x40000BAD, false);
+    round_trip::<BigEndian>(0x40000BAD, false);
+}
+
+#[test]
+fn writer() {
+
+    let block = CustomBlock {
+        pen: 32473,
+        copyable: false,
+        data: Cow::Borrowed(&[1, 2, 3])
+    };
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_block(&ParsedBlock::Custom(block)).unwrap();
+
+    let out = pcapng_writer.into_writer();
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let block = pcapng_reader.next().unwrap().unwrap();
+    assert_eq!(block.type_, BlockType::CustomNonCopyable);
+
+    match block.parsed().unwrap() {
+        ParsedBlock::Custom(custom) => assert_eq!(&custom.data[..], &[1, 2, 3, 0]),
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+}
@@ -0,0 +1,64 @@
+use pcap_file::PcapError;
+use pcap_file::pcapng::{PcapNgDecoder, PcapNgReader};
+use glob::glob;
+
+#[test]
+fn decoder_chunks() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+
+        for chunk_len in [1, 7, 64, 4096] {
+
+            let mut decoder = PcapNgDecoder::new();
+            let mut blocks = vec![];
+
+            for chunk in data.chunks(chunk_len) {
+                decoder.push(chunk);
+                while let Some(block) = decoder.next_block().unwrap() {
+                    blocks.push(block);
+                }
+            }
+
+            assert_eq!(decoder.buffered_len(), 0);
+
+            let pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+            let section = pcapng_reader.section().clone();
+
+            // The reader doesn't return the first SectionHeaderBlock
+            let expected: Vec<_> = pcapng_reader.map(|block| block.unwrap()).collect();
+            assert_eq!(blocks.len(), expected.len() + 1);
+
+            for (block, expected) in blocks[1..].iter().zip(expected) {
+                assert_eq!(block.type_, expected.type_);
+                assert_eq!(block.body, expected.body);
+            }
+
+            assert_eq!(decoder.section().unwrap().endianness(), section.endianness());
+        }
+    }
+}
+
+#[test]
+fn decoder_errors() {
+
+    // EnhancedPacketBlock before any SectionHeaderBlock
+    let mut decoder = PcapNgDecoder::new();
+    decoder.push(&[0x00, 0x00, 0x00, 0x06]);
+    match decoder.next_block() {
+        Err(PcapError::AtOffset { offset: 0, source }) => assert!(matches!(*source, PcapError::InvalidField(_))),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // SectionHeaderBlock header declaring 1 MiB
+    let mut decoder = PcapNgDecoder::with_max_block_len(1024);
+    decoder.push(&[0x0A, 0x0D, 0x0D, 0x0A, 0x00, 0x10, 0x00, 0x00]);
+    assert!(decoder.next_block().unwrap().is_none());
+
+    decoder.push(&[0x1A, 0x2B, 0x3C, 0x4D]);
+    match decoder.next_block() {
+        Err(PcapError::AtOffset { offset: 0, source }) => assert!(matches!(*source, PcapError::BlockTooLarge { len: 0x100000, max: 1024 })),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
@@ -0,0 +1,52 @@
+use byteorder::{ByteOrder, LittleEndian};
+use pcap_file::pcapng::{DecryptionSecretsBlock, DecryptionSecretsOption};
+use super::common::{body, opt};
+
+const KEY_LOG: &[u8] = b"CLIENT_RANDOM 0011 2233\n";
+
+fn block<B: ByteOrder>(secrets_type: u32, secrets: &[u8]) -> Vec<u8> {
+
+    let pad_len = (4 - secrets.len() % 4) % 4;
+
+    let mut data = vec![0_u8; 8];
+    B::write_u32(&mut data[0..4], secrets_type);
+    B::write_u32(&mut data[4..8], secrets.len() as u32);
+    data.extend_from_slice(secrets);
+    data.extend_from_slice(&[0_u8; 3][..pad_len]);
+
+    body::<B>(&data, &[opt::<B>(1, b"keys")])
+}
+
+fn round_trip<B: ByteOrder>() {
+
+    let data = block::<B>(DecryptionSecretsBlock::TLS_KEY_LOG_SECRETS, KEY_LOG);
+
+    let (rem, dsb) = DecryptionSecretsBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(dsb.secrets_type(), 0x544c534b);
+    assert_eq!(dsb.data(), KEY_LOG);
+    assert_eq!(dsb.tls_key_log().unwrap().unwrap(), "CLIENT_RANDOM 0011 2233\n");
+
+    match &dsb.options[..] {
+        [DecryptionSecretsOption::Comment(comment)] => assert_eq!(comment, "keys"),
+        options => panic!("Unexpected options: {:?}", options)
+    }
+
+    let mut out = Vec::new();
+    assert_eq!(dsb.to_owned().write_to::<_, B>(&mut out).unwrap(), data.len());
+    assert_eq!(out, data);
+}
+
+endian_tests!(round_trip => round_trip_le, round_trip_be);
+
+#[test]
+fn unknown_secrets_type() {
+
+    // 5 bytes of secrets followed by 3 bytes of padding
+    let data = block::<LittleEndian>(0x12345678, &[0xFF, 0xFE, 0xFD, 0xFC, 0xFB]);
+
+    let (_, dsb) = DecryptionSecretsBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(dsb.secrets_type(), 0x12345678);
+    assert_eq!(dsb.data(), &[0xFF, 0xFE, 0xFD, 0xFC, 0xFB]);
+    assert!(dsb.tls_key_log().is_none());
+}
@@ -0,0 +1,248 @@
+use byteorder::{ByteOrder, LittleEndian};
+use pcap_file::PcapError;
+use pcap_file::pcapng::{DecodedEpbFlags, EnhancedPacketBlock, EnhancedPacketOption, PacketDirection, ReceptionType};
+use std::borrow::Cow;
+use super::common::{body, opt};
+
+fn block<B: ByteOrder>() -> Vec<u8> {
+
+    let mut u64_value = [0_u8; 8];
+    B::write_u64(&mut u64_value, 0x0102030405060708);
+
+    let mut u32_value = [0_u8; 4];
+    B::write_u32(&mut u32_value, 0x01020304);
+
+    let mut data = vec![0_u8; 20];
+    B::write_u32(&mut data[0..4], 1);
+    B::write_u32(&mut data[4..8], 0x11223344);
+    B::write_u32(&mut data[8..12], 0x55667788);
+    B::write_u32(&mut data[12..16], 5);
+    B::write_u32(&mut data[16..20], 60);
+    data.extend_from_slice(&[1, 2, 3, 4, 5, 0, 0, 0]);
+
+    body::<B>(&data, &[
+        opt::<B>(1, b"comment"),
+        opt::<B>(2, &u32_value),
+        opt::<B>(3, &[2, 0xAA, 0xBB]),
+        opt::<B>(4, &u64_value),
+        opt::<B>(5, &u64_value),
+        opt::<B>(6, &u32_value),
+        opt::<B>(7, &[1, 0xCC])
+    ])
+}
+
+fn round_trip<B: ByteOrder>() {
+
+    let data = block::<B>();
+
+    let (rem, epb) = EnhancedPacketBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(epb.interface_id, 1);
+    assert_eq!(epb.timestamp, 0x1122334455667788);
+    assert_eq!(epb.captured_len, 5);
+    assert_eq!(epb.original_len, 60);
+    assert_eq!(&epb.data[..], &[1, 2, 3, 4, 5]);
+    assert_eq!(epb.options.len(), 7);
+
+    match &epb.options[..] {
+        [
+            EnhancedPacketOption::Comment(comment),
+            EnhancedPacketOption::Flags(0x01020304),
+            EnhancedPacketOption::Hash(hash),
+            EnhancedPacketOption::DropCount(0x0102030405060708),
+            EnhancedPacketOption::PacketId(0x0102030405060708),
+            EnhancedPacketOption::Queue(0x01020304),
+            EnhancedPacketOption::Verdict(verdict)
+        ] => {
+            assert_eq!(comment, "comment");
+            assert_eq!(&hash[..], &[2, 0xAA, 0xBB]);
+            assert_eq!(&verdict[..], &[1, 0xCC]);
+        },
+        options => panic!("Unexpected options: {:?}", options)
+    }
+
+    let mut out = Vec::new();
+    let len = epb.to_owned().write_to::<_, B>(&mut out).unwrap();
+    assert_eq!(len, data.len());
+    assert_eq!(out, data);
+}
+
+endian_tests!(round_trip => round_trip_le, round_trip_be);
+
+#[test]
+fn flags() {
+
+    // Outbound, broadcast, 4 bytes FCS, CRC and symbol errors
+    let flags = (0b1000_0001 << 24) | (4 << 5) | (3 << 2) | 2;
+
+    let mut data = vec![0_u8; 20];
+    data.extend(opt::<LittleEndian>(2, &u32::to_le_bytes(flags)));
+    data.extend(opt::<LittleEndian>(0, &[]));
+
+    let (_, epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let decoded = epb.flags();
+
+    assert_eq!(decoded.direction, PacketDirection::Outbound);
+    assert_eq!(decoded.reception_type, ReceptionType::Broadcast);
+    assert_eq!(decoded.fcs_len, 4);
+    assert_eq!(decoded.link_layer_errors, 0b1000_0001_0000_0000);
+
+    let decoded = DecodedEpbFlags::from(0b1_0001);
+    assert_eq!(decoded.direction, PacketDirection::Inbound);
+    assert_eq!(decoded.reception_type, ReceptionType::Promiscuous);
+    assert_eq!(decoded.fcs_len, 0);
+
+    let (_, epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&[0_u8; 20][..]).unwrap();
+    assert_eq!(epb.flags(), DecodedEpbFlags::from(0));
+    assert_eq!(epb.flags().direction, PacketDirection::Unknown);
+    assert_eq!(epb.flags().reception_type, ReceptionType::Unspecified);
+}
+
+#[test]
+fn data_only() {
+
+    let data = block::<LittleEndian>();
+
+    let (rem, lazy) = EnhancedPacketBlock::data_only_from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(lazy.interface_id, 1);
+    assert_eq!(lazy.timestamp, 0x1122334455667788);
+    assert_eq!(lazy.original_len, 60);
+    assert_eq!(lazy.data, &[1, 2, 3, 4, 5]);
+    assert_eq!(lazy.raw_options, &data[28..]);
+
+    let options = lazy.options::<LittleEndian>().unwrap();
+    let (_, epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(format!("{:?}", options), format!("{:?}", epb.options));
+
+    // Invalid options are only reported when decoded
+    let mut data = data;
+    data.truncate(36);
+    let (_, lazy) = EnhancedPacketBlock::data_only_from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(lazy.data, &[1, 2, 3, 4, 5]);
+    assert!(lazy.options::<LittleEndian>().is_err());
+}
+
+#[test]
+fn truncated() {
+
+    // 60 bytes packet truncated to 5 bytes
+    let data = block::<LittleEndian>();
+    let (_, epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert!(epb.is_truncated());
+
+    let (_, lazy) = EnhancedPacketBlock::data_only_from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert!(lazy.is_truncated());
+
+    // Whole packet
+    let mut data = data;
+    LittleEndian::write_u32(&mut data[16..20], 5);
+    let (_, epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert!(!epb.is_truncated());
+
+    // The captured length overruns the block
+    let mut data = data[..28].to_vec();
+    LittleEndian::write_u32(&mut data[12..16], 9);
+    match EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]) {
+        Err(PcapError::InvalidField(msg)) => assert_eq!(msg, "EnhancedPacketBlock: captured_len > block length"),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn verify_hash() {
+
+    let mut epb = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 9,
+        original_len: 9,
+        data: Cow::Borrowed(b"123456789"),
+        options: vec![EnhancedPacketOption::DropCount(0)]
+    };
+    assert_eq!(epb.verify_hash(), None);
+
+    epb.options.push(EnhancedPacketOption::Hash(Cow::Borrowed(&[2, 0xCB, 0xF4, 0x39, 0x26])));
+    assert_eq!(epb.verify_hash(), Some(true));
+
+    epb.data = Cow::Borrowed(b"123456780");
+    assert_eq!(epb.verify_hash(), Some(false));
+
+    // Unsupported algorithm and empty hash
+    assert_eq!(EnhancedPacketOption::Hash(Cow::Borrowed(&[3, 0])).verify_hash(b""), None);
+    assert_eq!(EnhancedPacketOption::Hash(Cow::Borrowed(&[])).verify_hash(b""), None);
+    assert_eq!(EnhancedPacketOption::Flags(0).verify_hash(b""), None);
+
+    // CRC32 of an empty packet
+    assert_eq!(EnhancedPacketOption::Hash(Cow::Borrowed(&[2, 0, 0, 0, 0])).verify_hash(b""), Some(true));
+}
+
+#[test]
+fn timestamp() {
+
+    use pcap_file::pcapng::InterfaceDescriptionBlock;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let data = block::<LittleEndian>();
+    let (_, mut epb) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    epb.timestamp = 1_000_000_123;
+
+    // Nanosecond resolution with a 10s offset
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x09, 0x00, 0x01, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x0E, 0x00, 0x08, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(epb.timestamp(&idb), Some(UNIX_EPOCH + Duration::new(11, 123)));
+
+    // An offset beyond the range of SystemTime
+    let mut data = data;
+    data[20..28].copy_from_slice(&u64::MAX.to_le_bytes());
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(epb.timestamp(&idb), None);
+}
+
+#[test]
+fn padded_to_snaplen() {
+
+    use byteorder::NativeEndian;
+    use pcap_file::pcapng::{InterfaceDescriptionBlock, PcapNgReader, PcapNgWriter};
+
+    // 5 bytes captured but the data is zero padded up to the 64 bytes snaplen
+    let mut body = vec![0_u8; 20];
+    NativeEndian::write_u32(&mut body[12..16], 5);
+    NativeEndian::write_u32(&mut body[16..20], 60);
+    body.extend_from_slice(&[1, 2, 3, 4, 5]);
+    body.resize(20 + 64, 0);
+
+    let (rem, epb) = EnhancedPacketBlock::from_slice::<NativeEndian>(&body[..]).unwrap();
+    assert_eq!(epb.captured_len, 5);
+    assert_eq!(epb.data, &[1, 2, 3, 4, 5][..]);
+    assert!(epb.options.is_empty());
+    assert_eq!(rem.len(), 64 - 8 - 4);
+
+    let (_, lazy) = EnhancedPacketBlock::data_only_from_slice::<NativeEndian>(&body[..]).unwrap();
+    assert_eq!(lazy.data, &[1, 2, 3, 4, 5]);
+    assert!(lazy.options::<NativeEndian>().unwrap().is_empty());
+
+    // The padding doesn't leak into the packet nor desynchronize the next block
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().snaplen(64).build()).unwrap();
+    let mut out = pcapng_writer.into_writer();
+
+    let block_len = (12 + body.len()) as u32;
+    for _ in 0..2 {
+        out.extend_from_slice(&6_u32.to_ne_bytes());
+        out.extend_from_slice(&block_len.to_ne_bytes());
+        out.extend_from_slice(&body);
+        out.extend_from_slice(&block_len.to_ne_bytes());
+    }
+
+    let pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let packets: Vec<_> = pcapng_reader
+        .map(|block| block.unwrap().lazy_enhanced_packet().map(|epb| epb.unwrap().data.to_vec()))
+        .collect();
+    assert_eq!(packets, [None, Some(vec![1, 2, 3, 4, 5]), Some(vec![1, 2, 3, 4, 5])]);
+}
@@ -0,0 +1,70 @@
+use pcap_file::{Endianness, PcapError};
+use pcap_file::pcapng::{index, read_block_at, BlockType, PcapNgReader};
+use std::fs::File;
+use std::io::Cursor;
+use glob::glob;
+
+#[test]
+fn index_matches_reader() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+        let entry = entry.unwrap();
+
+        let mut file = File::open(&entry).unwrap();
+        let entries = index(&mut file).unwrap_or_else(|err| panic!("Error indexing file {:?}: {}", entry, err));
+
+        let file = File::open(&entry).unwrap();
+        let pcapng_reader = PcapNgReader::new(file).unwrap();
+        let mut file = File::open(&entry).unwrap();
+
+        // The reader consumes the first SectionHeaderBlock
+        assert_eq!(entries[0].offset, 0);
+        assert_eq!(entries[0].block_type(), BlockType::SectionHeader);
+        let section = read_block_at(&mut file, 0, Endianness::Big).unwrap();
+        assert_eq!(section.initial_len, entries[0].len);
+
+        let mut nb_blocks = 1;
+        let mut endianness = pcapng_reader.section().endianness();
+
+        for (block, index_entry) in pcapng_reader.zip(&entries[1..]) {
+            let block = block.unwrap();
+            assert_eq!(block.type_, index_entry.block_type());
+            assert_eq!(block.initial_len, index_entry.len);
+
+            let block_at = read_block_at(&mut file, index_entry.offset, endianness).unwrap();
+            assert_eq!(format!("{:?}", block_at), format!("{:?}", block));
+            endianness = block_at.endianness();
+
+            assert_eq!(index_entry.offset, entries[nb_blocks - 1].offset + entries[nb_blocks - 1].len as u64);
+
+            nb_blocks += 1;
+        }
+
+        assert_eq!(nb_blocks, entries.len());
+    }
+}
+
+#[test]
+fn index_error_offset() {
+
+    // A SectionHeaderBlock followed by a SimplePacketBlock whose trailing length is wrong
+    let data = [
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00,
+        0x03, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00
+    ];
+
+    match index(&mut Cursor::new(&data[..])) {
+        Err(PcapError::AtOffset { offset: 28, source }) => {
+            assert!(matches!(*source, PcapError::BlockLengthMismatch { start: 16, end: 20 }))
+        },
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    let entries = index(&mut &data[..28]).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].len, 28);
+}
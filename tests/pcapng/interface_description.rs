@@ -0,0 +1,483 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use pcap_file::{DataLink, PcapError};
+use pcap_file::pcapng::{FilterKind, InterfaceDescriptionBlock, InterfaceDescriptionOption, MacAddr, ParsedBlock, PcapNgReader};
+use pcap_file::pcapng::options::Utf8Mode;
+use std::borrow::Cow;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+use super::common::{body, opt};
+
+#[test]
+fn ipv4_addr() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x04, 0x00, 0x08, 0x00, 192, 168, 1, 10, 255, 255, 255, 0,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let (addr, netmask) = idb.options()[0].as_ipv4().unwrap();
+
+    assert_eq!(addr, Ipv4Addr::new(192, 168, 1, 10));
+    assert_eq!(netmask, Ipv4Addr::new(255, 255, 255, 0));
+}
+
+#[test]
+fn ipv6_addr() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x05, 0x00, 0x11, 0x00,
+        0x20, 0x01, 0x0D, 0xB8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        64, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let (addr, prefix_len) = idb.options()[0].as_ipv6().unwrap();
+
+    assert_eq!(addr, "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(prefix_len, 64);
+}
+
+#[test]
+fn ipv6_addr_wrong_length() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x05, 0x00, 0x10, 0x00,
+        0x20, 0x01, 0x0D, 0xB8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    assert!(InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).is_err());
+}
+
+#[test]
+fn mac_addr() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x06, 0x00, 0x06, 0x00, 0x00, 0x1B, 0x21, 0xAB, 0xCD, 0xEF, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let mac = idb.options()[0].as_mac().unwrap();
+
+    assert_eq!(mac, [0x00, 0x1B, 0x21, 0xAB, 0xCD, 0xEF]);
+    assert_eq!(MacAddr::from(mac).to_string(), "00:1b:21:ab:cd:ef");
+}
+
+#[test]
+fn write_block() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+        0x09, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+
+    let mut out = Vec::new();
+    let len = idb.write_to::<_, LittleEndian>(&mut out).unwrap();
+
+    assert_eq!(len, data.len());
+    assert_eq!(&out[..], &data[..]);
+}
+
+fn all_options<B: ByteOrder>() -> Vec<Vec<u8>> {
+
+    let mut u64_value = [0_u8; 8];
+    B::write_u64(&mut u64_value, 0x0102030405060708);
+
+    let mut u32_value = [0_u8; 4];
+    B::write_u32(&mut u32_value, 0x01020304);
+
+    let mut custom = [0_u8; 7];
+    B::write_u32(&mut custom[..4], 32473);
+    custom[4..].copy_from_slice(b"abc");
+
+    vec![
+        opt::<B>(1, b"comment"),
+        opt::<B>(2, b"eth0"),
+        opt::<B>(3, b"description"),
+        opt::<B>(4, &[192, 168, 1, 1, 255, 255, 255, 0]),
+        opt::<B>(5, &[0x20, 0x01, 0x0D, 0xB8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 64]),
+        opt::<B>(6, &[0x00, 0x1B, 0x21, 0xAB, 0xCD, 0xEF]),
+        opt::<B>(7, &u64_value),
+        opt::<B>(8, &u64_value),
+        opt::<B>(9, &[9]),
+        opt::<B>(10, &u32_value),
+        opt::<B>(11, b"\x00tcp port 80"),
+        opt::<B>(12, b"Linux"),
+        opt::<B>(13, &[4]),
+        opt::<B>(14, &u64_value),
+        opt::<B>(15, b"hardware"),
+        opt::<B>(2988, &custom),
+        opt::<B>(2989, &custom),
+        opt::<B>(19372, &custom),
+        opt::<B>(19373, &custom),
+        opt::<B>(0x7FFF, b"unknown"),
+    ]
+}
+
+fn options_round_trip<B: ByteOrder>() {
+
+    let options = all_options::<B>();
+
+    let mut data = vec![0_u8; 8];
+    B::write_u16(&mut data[0..2], 1);
+    B::write_u32(&mut data[4..8], 0xFFFF);
+    let data = body::<B>(&data, &options);
+
+    let (rem, idb) = InterfaceDescriptionBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(idb.options().len(), options.len());
+
+    for (expected, opt) in options.iter().zip(idb.options()) {
+        let mut out = Vec::new();
+        let len = opt.write_to::<_, B>(&mut out).unwrap();
+
+        assert_eq!(len, expected.len());
+        assert_eq!(&out, expected);
+    }
+
+    let mut out = Vec::new();
+    idb.write_to::<_, B>(&mut out).unwrap();
+    assert_eq!(out, data);
+}
+
+endian_tests!(options_round_trip => options_round_trip_le, options_round_trip_be);
+
+#[test]
+fn ts_resolution() {
+
+    assert_eq!(InterfaceDescriptionOption::IfTsResol(6).units_per_second(), Some(1_000_000));
+    assert_eq!(InterfaceDescriptionOption::IfTsResol(9).units_per_second(), Some(1_000_000_000));
+    assert_eq!(InterfaceDescriptionOption::IfTsResol(0x80 | 10).units_per_second(), Some(1024));
+    assert_eq!(InterfaceDescriptionOption::IfTsResol(20).units_per_second(), None);
+    assert_eq!(InterfaceDescriptionOption::IfSpeed(6).units_per_second(), None);
+
+    let data = [0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00];
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(idb.ts_units_per_second(), Some(1_000_000));
+}
+
+#[test]
+fn resolve_timestamp() {
+
+    // Default resolution, no offset
+    let data = [0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00];
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(idb.resolve_timestamp(1_500_000), Duration::new(1, 500_000_000));
+
+    // Nanosecond resolution with a 10s offset
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x09, 0x00, 0x01, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x0E, 0x00, 0x08, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(idb.resolve_timestamp(1_000_000_123), Duration::new(11, 123));
+    assert_eq!(idb.resolve_timestamp(u64::MAX), Duration::new(u64::MAX / 1_000_000_000 + 10, (u64::MAX % 1_000_000_000) as u32));
+}
+
+#[test]
+fn unknown_option() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x34, 0x12, 0x03, 0x00, 0xAA, 0xBB, 0xCC, 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(idb.options().len(), 2);
+
+    match &idb.options()[0] {
+        InterfaceDescriptionOption::Unknown(opt) => {
+            assert_eq!(opt.code, 0x1234);
+            assert_eq!(opt.length, 3);
+            assert_eq!(&opt.value[..], &[0xAA, 0xBB, 0xCC]);
+        },
+        opt => panic!("Unexpected option: {:?}", opt)
+    }
+}
+
+#[test]
+fn option_accessors() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x01, 0x00, 0x03, 0x00, b'o', b'n', b'e', 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+        0x01, 0x00, 0x03, 0x00, b't', b'w', b'o', 0x00,
+        0x09, 0x00, 0x01, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+
+    assert_eq!(idb.if_name(), Some("eth0"));
+    assert_eq!(idb.if_tsresol(), Some(9));
+    assert_eq!(idb.if_speed(), None);
+    assert_eq!(idb.comments().collect::<Vec<_>>(), vec!["one", "two"]);
+}
+
+#[test]
+fn builder() {
+
+    let idb = InterfaceDescriptionBlock::builder().build();
+    assert_eq!(idb.linktype(), DataLink::ETHERNET);
+    assert_eq!(idb.snaplen(), 0);
+    assert!(idb.options().is_empty());
+
+    let idb = InterfaceDescriptionBlock::builder()
+        .linktype(DataLink::RAW)
+        .snaplen(1500)
+        .name("eth0")
+        .tsresol(9)
+        .speed(1_000_000_000)
+        .build();
+
+    let mut data = Vec::new();
+    idb.write_to::<_, LittleEndian>(&mut data).unwrap();
+    let (_, parsed) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+
+    assert_eq!(parsed.linktype(), DataLink::RAW);
+    assert_eq!(parsed.snaplen(), 1500);
+    assert_eq!(parsed.if_name(), Some("eth0"));
+    assert_eq!(parsed.if_tsresol(), Some(9));
+    assert_eq!(parsed.if_speed(), Some(1_000_000_000));
+}
+
+#[test]
+fn to_owned() {
+
+    let mut data = vec![0_u8; 8];
+    LittleEndian::write_u16(&mut data[0..2], 1);
+    let data = body::<LittleEndian>(&data, &all_options::<LittleEndian>());
+
+    let owned = {
+        let copy = data.clone();
+        let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&copy[..]).unwrap();
+        assert!(idb.options()[3].to_owned().as_ipv4().is_some());
+        idb.to_owned()
+    };
+
+    assert_eq!(owned.if_name(), Some("eth0"));
+
+    let mut out = Vec::new();
+    owned.write_to::<_, LittleEndian>(&mut out).unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn lossy_name() {
+
+    // A SectionHeaderBlock followed by an InterfaceDescriptionBlock named "eth\xe9" in Latin-1
+    let data = [
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00,
+        0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00,
+        0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00,
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', 0xE9,
+        0x00, 0x00, 0x00, 0x00,
+        0x20, 0x00, 0x00, 0x00
+    ];
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    let block = pcapng_reader.next().unwrap().unwrap();
+
+    // The reader keeps going and tracks the interface
+    assert_eq!(pcapng_reader.interfaces()[0].if_name(), Some("eth\u{FFFD}"));
+
+    assert!(matches!(block.parsed(), Err(PcapError::Utf8Error(_))));
+    match block.parsed_with(Utf8Mode::Lossy).unwrap() {
+        ParsedBlock::InterfaceDescription(idb) => assert_eq!(idb.if_name(), Some("eth\u{FFFD}")),
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    }
+}
+
+#[test]
+fn mutate_options() {
+
+    let mut idb = InterfaceDescriptionBlock::builder().name("eth0").tsresol(9).build();
+
+    let mut before = vec![];
+    idb.write_to::<_, LittleEndian>(&mut before).unwrap();
+
+    // Rename the interface, remove the resolution and add a comment
+    assert_eq!(idb.remove_option_by_code(2), 1);
+    assert_eq!(idb.remove_option_by_code(9), 1);
+    assert_eq!(idb.remove_option_by_code(9), 0);
+    idb.push_option(InterfaceDescriptionOption::IfName("enp0s31f6".into()));
+    idb.push_option(InterfaceDescriptionOption::Comment("uplink".into()));
+    idb.set_snaplen(128);
+
+    let mut after = vec![];
+    let len = idb.write_to::<_, LittleEndian>(&mut after).unwrap();
+    assert_eq!(len, after.len());
+    assert_ne!(after.len(), before.len());
+
+    let (rem, parsed) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&after).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(parsed.snaplen(), 128);
+    assert_eq!(parsed.if_name(), Some("enp0s31f6"));
+    assert_eq!(parsed.if_tsresol(), None);
+    assert_eq!(parsed.comments().collect::<Vec<_>>(), vec!["uplink"]);
+    assert_eq!(parsed.options().iter().map(|opt| opt.code()).collect::<Vec<_>>(), vec![2, 1]);
+}
+
+#[test]
+fn timezone_offset() {
+
+    // if_tzone of -3600 seconds
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x0A, 0x00, 0x04, 0x00, 0xF0, 0xF1, 0xFF, 0xFF,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(idb.if_tzone(), Some(0xFFFFF1F0));
+    assert_eq!(idb.timezone_offset(), Some(-3600));
+
+    // The raw value is written back unchanged
+    let mut written = vec![];
+    idb.write_to::<_, LittleEndian>(&mut written).unwrap();
+    assert_eq!(written, data);
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..8]).unwrap();
+    assert_eq!(idb.timezone_offset(), None);
+}
+
+#[test]
+fn from_linktype() {
+
+    let idb = InterfaceDescriptionBlock::ethernet();
+    assert_eq!(idb.linktype(), DataLink::ETHERNET);
+    assert_eq!(idb.snaplen(), 0);
+    assert_eq!(idb.ts_units_per_second(), Some(1_000_000));
+
+    let idb = InterfaceDescriptionBlock::from_linktype(DataLink::LINUX_SLL, 262144);
+
+    let mut data = vec![];
+    idb.write_to::<_, BigEndian>(&mut data).unwrap();
+    assert_eq!(data, [0x00, 0x71, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00]);
+
+    let (rem, parsed) = InterfaceDescriptionBlock::from_slice::<BigEndian>(&data).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(parsed.linktype(), DataLink::LINUX_SLL);
+    assert_eq!(parsed.snaplen(), 262144);
+    assert!(parsed.options().is_empty());
+}
+
+#[test]
+fn if_filter() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        // BPF bytecode
+        0x0B, 0x00, 0x05, 0x00, 0x01, 0x28, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00,
+        // libpcap filter string with a NUL terminator
+        0x0B, 0x00, 0x05, 0x00, 0x00, b't', b'c', b'p', 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+
+    assert_eq!(idb.options()[0].as_filter(), Some((FilterKind::Bpf, &[0x28, 0x00, 0x00, 0x0C][..])));
+    assert_eq!(idb.options()[0].as_bpf_string(), None);
+    assert_eq!(idb.options()[1].as_filter(), Some((FilterKind::Libpcap, &b"tcp\0"[..])));
+    assert_eq!(idb.options()[1].as_bpf_string(), Some("tcp"));
+    assert_eq!(idb.bpf_filter(), Some("tcp"));
+
+    assert_eq!(FilterKind::from(7), FilterKind::Unknown(7));
+    assert_eq!(u8::from(FilterKind::Unknown(7)), 7);
+    assert_eq!(InterfaceDescriptionOption::IfName("eth0".into()).as_filter(), None);
+}
+
+#[test]
+fn clone_borrowed() {
+
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+        0x00, 0x00, 0x00, 0x00
+    ];
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+
+    // The clone borrows the same data instead of owning a copy
+    let mut clone = idb.clone();
+    match (&idb.options[0], &clone.options[0]) {
+        (InterfaceDescriptionOption::IfName(Cow::Borrowed(name)), InterfaceDescriptionOption::IfName(Cow::Borrowed(cloned))) => {
+            assert_eq!(name.as_ptr(), cloned.as_ptr());
+        },
+        opts => panic!("Unexpected options: {:?}", opts)
+    }
+
+    // And is independent of the original
+    clone.set_snaplen(10);
+    clone.options.clear();
+    assert_eq!(idb.snaplen, 0xFFFF);
+    assert_eq!(idb.if_name(), Some("eth0"));
+}
+
+#[test]
+fn canonicalize() {
+
+    // if_speed, a comment, if_name and a second comment
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x08, 0x00, 0x08, 0x00, 0x00, 0xCA, 0x9A, 0x3B, 0x00, 0x00, 0x00, 0x00,
+        0x01, 0x00, 0x01, 0x00, b'a', 0x00, 0x00, 0x00,
+        0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+        0x01, 0x00, 0x01, 0x00, b'b', 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    // The order of the block is kept
+    let (_, mut idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let codes: Vec<u16> = idb.options.iter().map(|opt| opt.code()).collect();
+    assert_eq!(codes, [8, 1, 2, 1]);
+
+    idb.canonicalize();
+    let codes: Vec<u16> = idb.options.iter().map(|opt| opt.code()).collect();
+    assert_eq!(codes, [1, 1, 2, 8]);
+    assert_eq!(idb.comments().collect::<Vec<_>>(), ["a", "b"]);
+
+    let mut canonical = idb.clone();
+    canonical.canonicalize();
+    assert_eq!(canonical, idb);
+
+    let mut out = vec![];
+    idb.write_to::<_, LittleEndian>(&mut out).unwrap();
+    assert_eq!(out.len(), data.len());
+    assert_eq!(&out[8..16], [0x01, 0x00, 0x01, 0x00, b'a', 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn bpf_string() {
+
+    let opt = InterfaceDescriptionOption::bpf_string("tcp port 80");
+    assert_eq!(opt, InterfaceDescriptionOption::IfFilter(Cow::Borrowed(b"\x00tcp port 80")));
+    assert_eq!(opt.as_filter(), Some((FilterKind::Libpcap, &b"tcp port 80"[..])));
+
+    let idb = InterfaceDescriptionBlock::builder().filter("udp").build();
+
+    let mut data = Vec::new();
+    idb.write_to::<_, BigEndian>(&mut data).unwrap();
+    let (_, parsed) = InterfaceDescriptionBlock::from_slice::<BigEndian>(&data).unwrap();
+
+    assert_eq!(parsed.bpf_filter(), Some("udp"));
+    assert_eq!(parsed, idb);
+}
@@ -0,0 +1,51 @@
+use byteorder::ByteOrder;
+use pcap_file::pcapng::{InterfaceStatisticsBlock, InterfaceStatisticsOption};
+use super::common::{body, opt};
+
+fn round_trip<B: ByteOrder>() {
+
+    let mut timestamp = [0_u8; 8];
+    B::write_u32(&mut timestamp[..4], 0x11223344);
+    B::write_u32(&mut timestamp[4..], 0x55667788);
+
+    let mut counter = [0_u8; 8];
+    B::write_u64(&mut counter, 42);
+
+    let mut data = vec![0_u8; 12];
+    B::write_u32(&mut data[0..4], 2);
+    data[4..12].copy_from_slice(&timestamp);
+
+    let mut options = vec![
+        opt::<B>(1, b"done"),
+        opt::<B>(2, &timestamp),
+        opt::<B>(3, &timestamp)
+    ];
+    options.extend((4..=8).map(|code| opt::<B>(code, &counter)));
+    let data = body::<B>(&data, &options);
+
+    let (rem, isb) = InterfaceStatisticsBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(isb.interface_id, 2);
+    assert_eq!(isb.timestamp, 0x1122334455667788);
+
+    match &isb.options[..] {
+        [
+            InterfaceStatisticsOption::Comment(comment),
+            InterfaceStatisticsOption::IsbStartTime(0x1122334455667788),
+            InterfaceStatisticsOption::IsbEndTime(0x1122334455667788),
+            InterfaceStatisticsOption::IsbIfRecv(42),
+            InterfaceStatisticsOption::IsbIfDrop(42),
+            InterfaceStatisticsOption::IsbFilterAccept(42),
+            InterfaceStatisticsOption::IsbOsDrop(42),
+            InterfaceStatisticsOption::IsbUsrDeliv(42)
+        ] => assert_eq!(comment, "done"),
+        options => panic!("Unexpected options: {:?}", options)
+    }
+
+    let mut out = Vec::new();
+    let len = isb.to_owned().write_to::<_, B>(&mut out).unwrap();
+    assert_eq!(len, data.len());
+    assert_eq!(out, data);
+}
+
+endian_tests!(round_trip => round_trip_le, round_trip_be);
@@ -0,0 +1,179 @@
+use pcap_file::DataLink;
+use pcap_file::pcapng::{merge_pcapng, merge_pcapng_chronological, CustomBinaryOption, CustomUtf8Option, EnhancedPacketBlock, EnhancedPacketOption, InterfaceDescriptionBlock, InterfaceDescriptionOption, ParsedBlock, PcapNgReader, PcapNgWriter, SimplePacketBlock};
+use std::borrow::Cow;
+
+/// Writes the interfaces then one packet per `(interface_id, data)`
+fn capture(interfaces: &[InterfaceDescriptionBlock], packets: &[(u32, u8)]) -> Vec<u8> {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    for interface in interfaces {
+        pcapng_writer.write_interface(interface).unwrap();
+    }
+
+    for &(interface_id, data) in packets {
+        let packet = EnhancedPacketBlock {
+            interface_id,
+            timestamp: 0,
+            captured_len: 1,
+            original_len: 1,
+            data: Cow::Owned(vec![data]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    pcapng_writer.into_writer()
+}
+
+fn interface(linktype: DataLink, snaplen: u32) -> InterfaceDescriptionBlock<'static> {
+    InterfaceDescriptionBlock::builder().linktype(linktype).snaplen(snaplen).build()
+}
+
+#[test]
+fn merge() {
+
+    let first = capture(&[interface(DataLink::ETHERNET, 0), interface(DataLink::RAW, 0)], &[(1, 1), (0, 2)]);
+
+    // The first RAW interface is identical to the one of the first capture, but not the second one
+    let mut second = capture(&[interface(DataLink::RAW, 0), interface(DataLink::RAW, 0), interface(DataLink::ETHERNET, 100)], &[(0, 3), (1, 4), (2, 5)]);
+
+    // A SimplePacketBlock references the first interface
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&interface(DataLink::RAW, 0)).unwrap();
+    pcapng_writer.write_block(&ParsedBlock::SimplePacket(SimplePacketBlock { original_len: 1, data: Cow::Borrowed(&[6]) })).unwrap();
+    second.extend(pcapng_writer.into_writer());
+
+    let mut out = vec![];
+    merge_pcapng(vec![&first[..], &second[..]], &mut out).unwrap();
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let mut packets = vec![];
+
+    while let Some(block) = pcapng_reader.next_block() {
+        if let ParsedBlock::EnhancedPacket(packet) = block.unwrap().parsed().unwrap() {
+            let interface = pcapng_reader.packet_interface(&packet).unwrap();
+            packets.push((packet.interface_id, interface.linktype(), packet.data[0]));
+        }
+    }
+
+    let interfaces: Vec<_> = pcapng_reader.interfaces().iter().map(|interface| (interface.linktype(), interface.snaplen())).collect();
+    assert_eq!(interfaces, [(DataLink::ETHERNET, 0), (DataLink::RAW, 0), (DataLink::RAW, 0), (DataLink::ETHERNET, 100)]);
+
+    assert_eq!(packets, [
+        (1, DataLink::RAW, 1),
+        (0, DataLink::ETHERNET, 2),
+        (1, DataLink::RAW, 3),
+        (2, DataLink::RAW, 4),
+        (3, DataLink::ETHERNET, 5),
+        (1, DataLink::RAW, 6)
+    ]);
+}
+
+#[test]
+fn merge_invalid_interface() {
+
+    let mut data = capture(&[interface(DataLink::RAW, 0)], &[(0, 1)]);
+
+    // Rewrite the interface_id of the packet
+    let pos = data.len() - 28;
+    data[pos..pos + 4].copy_from_slice(&5_u32.to_ne_bytes());
+
+    assert!(merge_pcapng(vec![&data[..]], Vec::new()).is_err());
+}
+
+#[test]
+fn merge_non_copyable_options() {
+
+    let interface = InterfaceDescriptionBlock::builder()
+        .option(InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::new(32473, "kept", true)))
+        .option(InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::new(32473, "dropped", false)))
+        .option(InterfaceDescriptionOption::CustomBinary(CustomBinaryOption::new(32473, &[1], false)))
+        .build();
+
+    let packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 1,
+        original_len: 1,
+        data: Cow::Borrowed(&[1]),
+        options: vec![
+            EnhancedPacketOption::CustomBinary(CustomBinaryOption::new(32473, &[2], false)),
+            EnhancedPacketOption::CustomBinary(CustomBinaryOption::new(32473, &[3], true))
+        ]
+    };
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&interface).unwrap();
+    pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    let data = pcapng_writer.into_writer();
+
+    let mut out = vec![];
+    merge_pcapng(vec![&data[..]], &mut out).unwrap();
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let mut packets = vec![];
+
+    while let Some(block) = pcapng_reader.next_block() {
+        if let ParsedBlock::EnhancedPacket(packet) = block.unwrap().parsed().unwrap() {
+            packets.push(packet.into_owned());
+        }
+    }
+
+    assert_eq!(pcapng_reader.interfaces()[0].options(), [InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::new(32473, "kept", true))]);
+    assert_eq!(packets.len(), 1);
+    assert_eq!(packets[0].options, [EnhancedPacketOption::CustomBinary(CustomBinaryOption::new(32473, &[3], true))]);
+}
+
+/// Writes one interface then one packet per `(timestamp, data)`
+fn timed_capture(interface: InterfaceDescriptionBlock, packets: &[(u64, u8)]) -> Vec<u8> {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&interface).unwrap();
+
+    for &(timestamp, data) in packets {
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp,
+            captured_len: 1,
+            original_len: 1,
+            data: Cow::Owned(vec![data]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    pcapng_writer.into_writer()
+}
+
+#[test]
+fn merge_chronological() {
+
+    // Microseconds: 1s and 3s
+    let first = timed_capture(InterfaceDescriptionBlock::builder().build(), &[(1_000_000, 1), (3_000_000, 4)]);
+
+    // Nanoseconds: 2s and 3s
+    let second = timed_capture(InterfaceDescriptionBlock::builder().tsresol(9).build(), &[(2_000_000_000, 3), (3_000_000_000, 5)]);
+
+    // Microseconds with an offset of 1s: 1.5s
+    let third = timed_capture(
+        InterfaceDescriptionBlock::builder().option(InterfaceDescriptionOption::IfTsOffset(1)).build(),
+        &[(500_000, 2)]
+    );
+
+    let mut out = vec![];
+    merge_pcapng_chronological(vec![&first[..], &second[..], &third[..]], &mut out).unwrap();
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let mut packets = vec![];
+
+    while let Some(block) = pcapng_reader.next_block() {
+        if let ParsedBlock::EnhancedPacket(packet) = block.unwrap().parsed().unwrap() {
+            let time = pcapng_reader.packet_interface(&packet).unwrap().resolve_timestamp(packet.timestamp);
+            packets.push((packet.data[0], time.as_millis()));
+        }
+    }
+
+    // The tie at 3s is resolved in the order of the inputs
+    assert_eq!(packets, [(1, 1000), (2, 1500), (3, 2000), (4, 3000), (5, 3000)]);
+    assert_eq!(pcapng_reader.interfaces().len(), 3);
+}
@@ -1,8 +1,34 @@
-use pcap_file::pcapng::{PcapNgReader, PcapNgParser};
+#[macro_use]
+mod common;
+
+#[cfg(feature = "tokio")]
+mod async_reader;
+mod block;
+mod convert;
+mod custom;
+mod decoder;
+mod decryption_secrets;
+mod enhanced_packet;
+mod index;
+mod interface_description;
+mod interface_statistics;
+mod merge;
+mod name_resolution;
+mod options;
+mod packet;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod section_header;
+mod simple_packet;
+mod split;
+mod systemd_journal_export;
+
+use pcap_file::{DataLink, Endianness};
+use pcap_file::pcapng::{index, BlockType, EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, PcapNgReader, PcapNgParser, PcapNgWriter, SectionHeaderBlock, SimplePacketBlock};
+use std::borrow::Cow;
 use std::fs::File;
 use glob::glob;
 use std::io::Read;
-use pcap_file::PcapError;
 
 #[test]
 fn reader() {
@@ -14,8 +40,8 @@ fn reader() {
         let pcapng_reader = PcapNgReader::new(file).unwrap();
 
         for (i, block) in pcapng_reader.enumerate() {
-            let _block = block.expect(&format!("Error on block {} on file: {:?}", i, entry));
-            let parsed = _block.parsed().expect(&format!("Error on parsed block {} file: {:?}", i, entry));
+            let block = block.unwrap_or_else(|err| panic!("Error on block {} on file: {:?}: {}", i, entry, err));
+            block.parsed().unwrap_or_else(|err| panic!("Error on parsed block {} file: {:?}: {}", i, entry, err));
         }
     }
 }
@@ -40,11 +66,647 @@ fn parser() {
                 break;
             }
 
-            let (rem, block) =  pcapng_parser.next_block(src).expect(&format!("Error on block {} on file: {:?}", i, entry));
-            block.parsed().expect(&format!("Error on parsed block {} on file: {:?}", i, entry));
+            let (rem, block) =  pcapng_parser.next_block(src).unwrap_or_else(|err| panic!("Error on block {} on file: {:?}: {}", i, entry, err));
+            block.parsed().unwrap_or_else(|err| panic!("Error on parsed block {} on file: {:?}: {}", i, entry, err));
             src = rem;
 
             i += 1;
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn reader_multiple_sections() {
+
+    let file = File::open("tests/pcapng/little_endian/difficult/test202.pcapng").unwrap();
+    let mut pcapng_reader = PcapNgReader::new(file).unwrap();
+
+    let mut sections = vec![pcapng_reader.section().endianness()];
+    let mut interfaces = 0;
+
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block.unwrap();
+
+        match block.type_ {
+            BlockType::SectionHeader => {
+                sections.push(pcapng_reader.section().endianness());
+                interfaces = 0;
+            },
+            BlockType::InterfaceDescription => interfaces += 1,
+            _ => {}
+        }
+
+        assert_eq!(pcapng_reader.interfaces().len(), interfaces);
+    }
+
+    assert_eq!(sections, vec![Endianness::Little, Endianness::Big, Endianness::Little]);
+}
+
+#[test]
+fn writer() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+        let entry = entry.unwrap();
+
+        let mut data = Vec::new();
+        File::open(&entry).unwrap().read_to_end(&mut data).unwrap();
+
+        let pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), Vec::new()).unwrap();
+
+        let mut expected = vec![];
+        for (i, block) in pcapng_reader.enumerate() {
+            let parsed = block.unwrap().parsed().unwrap().into_owned();
+            pcapng_writer.write_block(&parsed).unwrap_or_else(|err| panic!("Error on block {} on file: {:?}: {}", i, entry, err));
+            expected.push(format!("{:?}", parsed));
+        }
+
+        let out = pcapng_writer.into_writer();
+        let pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+
+        let mut written = vec![];
+        for (i, block) in pcapng_reader.enumerate() {
+            let block = block.unwrap_or_else(|err| panic!("Error on written block {} on file: {:?}: {}", i, entry, err));
+            written.push(format!("{:?}", block.parsed().unwrap()));
+        }
+
+        assert_eq!(written, expected, "Round trip mismatch on file: {:?}", entry);
+    }
+}
+
+#[test]
+fn writer_interfaces() {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+
+    let mut packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 3,
+        original_len: 3,
+        data: Cow::Borrowed(&[1, 2, 3]),
+        options: vec![]
+    };
+
+    // No interface has been written yet
+    assert!(pcapng_writer.write_enhanced_packet(&packet).is_err());
+
+    let interface = InterfaceDescriptionBlock::builder().linktype(DataLink::RAW).build();
+    assert_eq!(pcapng_writer.write_interface(&interface).unwrap(), 0);
+    assert_eq!(pcapng_writer.write_interface(&interface).unwrap(), 1);
+
+    packet.interface_id = 1;
+    assert_eq!(pcapng_writer.write_enhanced_packet(&packet).unwrap(), 32 + 4);
+
+    let out = pcapng_writer.into_writer();
+    assert_eq!(out.len(), 28 + 20 + 20 + 36);
+    assert_eq!(out.len() % 4, 0);
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    let blocks: Vec<_> = pcapng_reader.by_ref().map(|block| block.unwrap()).collect();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[2].initial_len, blocks[2].trailer_len);
+    assert_eq!(pcapng_reader.interfaces().len(), 2);
+}
+
+#[test]
+fn reader_packet_interface() {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(DataLink::RAW).build()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().tsresol(9).build()).unwrap();
+
+    let packet = EnhancedPacketBlock {
+        interface_id: 1,
+        timestamp: 0,
+        captured_len: 3,
+        original_len: 3,
+        data: Cow::Borrowed(&[1, 2, 3]),
+        options: vec![]
+    };
+    pcapng_writer.write_enhanced_packet(&packet).unwrap();
+
+    let out = pcapng_writer.into_writer();
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+
+    let block = pcapng_reader.by_ref().nth(2).unwrap().unwrap();
+    let packet = match block.parsed().unwrap() {
+        ParsedBlock::EnhancedPacket(packet) => packet,
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    };
+
+    let interface = pcapng_reader.packet_interface(&packet).unwrap();
+    assert_eq!(interface.linktype(), DataLink::ETHERNET);
+    assert_eq!(interface.if_tsresol(), Some(9));
+
+    assert_eq!(pcapng_reader.interface(0).unwrap().linktype(), DataLink::RAW);
+    assert!(pcapng_reader.interface(2).is_none());
+}
+
+#[test]
+fn writer_truncate_to_snaplen() {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().snaplen(4).build()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().snaplen(0).build()).unwrap();
+
+    let mut packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 5,
+        original_len: 5,
+        data: Cow::Borrowed(&[1, 2, 3, 4, 5]),
+        options: vec![]
+    };
+
+    // Disabled by default
+    assert!(!pcapng_writer.truncate_to_snaplen());
+    pcapng_writer.write_enhanced_packet(&packet).unwrap();
+
+    pcapng_writer.set_truncate_to_snaplen(true);
+    pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    pcapng_writer.write_block(&ParsedBlock::EnhancedPacket(packet.clone())).unwrap();
+
+    // A snaplen of 0 means no limit
+    packet.interface_id = 1;
+    pcapng_writer.write_enhanced_packet(&packet).unwrap();
+
+    let simple = SimplePacketBlock { original_len: 5, data: Cow::Borrowed(&[1, 2, 3, 4, 5]) };
+    pcapng_writer.write_block(&ParsedBlock::SimplePacket(simple)).unwrap();
+
+    let out = pcapng_writer.into_writer();
+    let pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+
+    let lens: Vec<_> = pcapng_reader.skip(2)
+        .map(|block| match block.unwrap().parsed().unwrap() {
+            ParsedBlock::EnhancedPacket(packet) => (packet.captured_len, packet.data.len(), packet.original_len),
+            ParsedBlock::SimplePacket(packet) => (packet.data.len() as u32, packet.data.len(), packet.original_len),
+            parsed => panic!("Unexpected block: {:?}", parsed)
+        })
+        .collect();
+
+    assert_eq!(lens, [(5, 5, 5), (4, 4, 5), (4, 4, 5), (5, 5, 5), (4, 4, 5)]);
+}
+
+#[test]
+fn writer_copy_block() {
+
+    use byteorder::LittleEndian;
+    use common::framed;
+
+    // The options of the SectionHeaderBlock and of the InterfaceDescriptionBlock end without an end of options marker
+    let input = [
+        framed::<LittleEndian>(0x0A0D0D0A, &[
+            0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x03, 0x00, 0x05, 0x00, b'L', b'i', b'n', b'u', b'x', 0x00, 0x00, 0x00
+        ]),
+        framed::<LittleEndian>(0x00000001, &[0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0']),
+        framed::<LittleEndian>(0x00000003, &[0x02, 0x00, 0x00, 0x00, 0xAA, 0xBB, 0x00, 0x00]),
+        framed::<LittleEndian>(0x12345678, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+    ].concat();
+
+    let mut pcapng_reader = PcapNgReader::new(&input[..]).unwrap();
+    let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), vec![]).unwrap();
+    while let Some(block) = pcapng_reader.next_block() {
+        pcapng_writer.copy_block(&block.unwrap()).unwrap();
+    }
+
+    let expected = [
+        framed::<LittleEndian>(0x0A0D0D0A, &[
+            0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+            0x03, 0x00, 0x05, 0x00, b'L', b'i', b'n', b'u', b'x', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+        ]),
+        framed::<LittleEndian>(0x00000001, &[
+            0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0',
+            0x00, 0x00, 0x00, 0x00
+        ]),
+        framed::<LittleEndian>(0x00000003, &[0x02, 0x00, 0x00, 0x00, 0xAA, 0xBB, 0x00, 0x00]),
+        framed::<LittleEndian>(0x12345678, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08])
+    ].concat();
+
+    assert_eq!(pcapng_writer.into_writer(), expected);
+}
+
+#[test]
+fn writer_copy_section() {
+
+    use byteorder::LittleEndian;
+    use common::framed;
+    use pcap_file::pcapng::InterfaceDescriptionOption;
+    use pcap_file::pcapng::options::Utf8Mode;
+
+    // A second section whose length is specified, with an interface whose comment is Latin-1
+    let input = [
+        framed::<LittleEndian>(0x0A0D0D0A, &[0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+        framed::<LittleEndian>(0x0A0D0D0A, &[0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+        framed::<LittleEndian>(0x00000001, &[0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0xE9, 0x00, 0x00, 0x00])
+    ].concat();
+
+    let mut pcapng_reader = PcapNgReader::new(&input[..]).unwrap();
+    let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), vec![]).unwrap();
+    while let Some(block) = pcapng_reader.next_block() {
+        pcapng_writer.copy_block(&block.unwrap()).unwrap();
+    }
+
+    let out = pcapng_writer.into_writer();
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+
+    // The end of options marker added to the interface makes the section 4 bytes longer than declared
+    let section = pcapng_reader.next_block().unwrap().unwrap().parsed().unwrap().into_section_header().unwrap().into_owned();
+    assert_eq!(section.section_length, -1);
+
+    let interface = pcapng_reader.next_block().unwrap().unwrap().parsed_with(Utf8Mode::Lossy).unwrap().into_interface_description().unwrap().into_owned();
+    assert_eq!(interface.options(), [InterfaceDescriptionOption::Comment(Cow::Borrowed("\u{FFFD}"))]);
+}
+
+#[test]
+fn reader_error_policy() {
+
+    use byteorder::{ByteOrder, NativeEndian};
+    use pcap_file::PcapError;
+    use pcap_file::pcapng::ErrorPolicy;
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+
+    let mut starts = vec![];
+    for len in 1..=4 {
+        starts.push(pcapng_writer.get_ref().len());
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: 0,
+            captured_len: len,
+            original_len: len,
+            data: Cow::Owned(vec![0; len as usize]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    // The captured_len of the second packet overruns its block, the trailing length of the third is wrong
+    let mut out = pcapng_writer.into_writer();
+    NativeEndian::write_u32(&mut out[starts[1] + 20..], 100);
+    NativeEndian::write_u32(&mut out[starts[3] - 4..], 0);
+
+    // Stopped by default at the length mismatch, the blocks are not decoded
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    assert_eq!(pcapng_reader.error_policy(), ErrorPolicy::Stop);
+    let blocks: Vec<_> = pcapng_reader.by_ref().take(4).collect();
+    assert!(blocks[2].as_ref().unwrap().parsed().is_err());
+    match &blocks[3] {
+        Err(PcapError::AtOffset { source, offset }) => {
+            assert_eq!(*offset, starts[2] as u64);
+            assert!(matches!(**source, PcapError::BlockLengthMismatch { .. }));
+        },
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // The block with the length mismatch has been read as a whole, a following error is at the offset of the next block
+    let truncated = &out[..out.len() - 4];
+    let is_eof_at_last = |err: PcapError| match err {
+        PcapError::AtOffset { source, offset } => offset == starts[3] as u64 && matches!(*source, PcapError::IoError(_)),
+        _ => false
+    };
+
+    let mut pcapng_reader = PcapNgReader::new(truncated).unwrap();
+    assert!(pcapng_reader.by_ref().nth(3).unwrap().is_err());
+    assert!(is_eof_at_last(pcapng_reader.next_block().unwrap().unwrap_err()));
+
+    let mut pcapng_reader = PcapNgReader::new(truncated).unwrap();
+    for _ in 0..4 {
+        pcapng_reader.next_block_ref();
+    }
+    assert!(is_eof_at_last(pcapng_reader.next_block_ref().unwrap().unwrap_err()));
+
+    // The corrupted packets are skipped
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    pcapng_reader.set_error_policy(ErrorPolicy::SkipBlock);
+    let lens: Vec<_> = pcapng_reader.by_ref()
+        .filter_map(|block| block.unwrap().lazy_enhanced_packet().map(|packet| packet.unwrap().data.len()))
+        .collect();
+    assert_eq!(lens, [1, 4]);
+    assert_eq!(pcapng_reader.skipped_blocks(), 2);
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    pcapng_reader.set_error_policy(ErrorPolicy::SkipBlock);
+    let mut lens = vec![];
+    while let Some(block) = pcapng_reader.next_block_ref() {
+        if let Some(packet) = block.unwrap().lazy_enhanced_packet() {
+            lens.push(packet.unwrap().data.len());
+        }
+    }
+    assert_eq!(lens, [1, 4]);
+    assert_eq!(pcapng_reader.skipped_blocks(), 2);
+}
+
+#[test]
+fn reader_resync() {
+
+    use byteorder::{ByteOrder, NativeEndian};
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+
+    let mut starts = vec![];
+    for len in 1..=3 {
+        starts.push(pcapng_writer.get_ref().len());
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: 0,
+            captured_len: len,
+            original_len: len,
+            data: Cow::Owned(vec![0; len as usize]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    // The length of the first packet is garbage, the reader fails after reading the block type and the length
+    let mut out = pcapng_writer.into_writer();
+    NativeEndian::write_u32(&mut out[starts[0] + 4..], 0xFFFF_FF00);
+
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    pcapng_reader.next_block().unwrap().unwrap();
+    assert!(pcapng_reader.next_block().unwrap().is_err());
+
+    // Not found within the scanned bytes: nothing is consumed
+    let skipped = starts[1] - starts[0] - 8;
+    assert_eq!(pcapng_reader.resync(skipped - 1).unwrap(), None);
+    assert_eq!(pcapng_reader.resync(skipped).unwrap(), Some(skipped));
+
+    let lens: Vec<_> = pcapng_reader
+        .map(|block| block.unwrap().lazy_enhanced_packet().unwrap().unwrap().data.len())
+        .collect();
+    assert_eq!(lens, [2, 3]);
+
+    // Nothing to skip at the start of a block, nothing to find at the end of the stream
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+    assert_eq!(pcapng_reader.resync(100).unwrap(), Some(0));
+    while pcapng_reader.next_block().is_some() {}
+    assert_eq!(pcapng_reader.resync(100).unwrap(), None);
+}
+
+/// A little endian capture followed by a big endian one, each with one interface and one packet
+fn mixed_endianness_sections() -> Vec<u8> {
+
+    let mut data = vec![];
+
+    for (endianness, linktype) in [(Endianness::Little, DataLink::ETHERNET), (Endianness::Big, DataLink::RAW)] {
+
+        let mut section = SectionHeaderBlock::default();
+        section.set_endianness(endianness);
+
+        let mut pcapng_writer = PcapNgWriter::with_section_header(section, Vec::new()).unwrap();
+        pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(linktype).build()).unwrap();
+
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: 0x0102030405060708,
+            captured_len: 3,
+            original_len: 3,
+            data: Cow::Borrowed(&[1, 2, 3]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+
+        data.extend(pcapng_writer.into_writer());
+    }
+
+    data
+}
+
+#[test]
+fn mixed_endianness() {
+
+    let data = mixed_endianness_sections();
+
+    let check = |block: ParsedBlock, endianness: Endianness, interface: Option<&InterfaceDescriptionBlock>| {
+        match block {
+            ParsedBlock::SectionHeader(section) => assert_eq!(section.endianness(), endianness),
+            ParsedBlock::InterfaceDescription(_) => {},
+            ParsedBlock::EnhancedPacket(packet) => {
+                assert_eq!(packet.timestamp, 0x0102030405060708);
+                assert_eq!(&packet.data[..], &[1, 2, 3]);
+
+                let expected = match endianness {
+                    Endianness::Little => DataLink::ETHERNET,
+                    Endianness::Big => DataLink::RAW
+                };
+                assert_eq!(interface.unwrap().linktype(), expected);
+            },
+            parsed => panic!("Unexpected block: {:?}", parsed)
+        }
+    };
+
+    // Reader
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    let mut nb_blocks = 1;
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block.unwrap();
+        let endianness = pcapng_reader.section().endianness();
+        assert_eq!(block.endianness(), endianness);
+
+        check(block.parsed().unwrap(), endianness, pcapng_reader.interface(0));
+        nb_blocks += 1;
+    }
+    assert_eq!(nb_blocks, 6);
+    assert_eq!(pcapng_reader.section().endianness(), Endianness::Big);
+
+    // Parser
+    let (mut src, mut pcapng_parser) = PcapNgParser::new(&data[..]).unwrap();
+    while !src.is_empty() {
+        let (rem, block) = pcapng_parser.next_block(src).unwrap();
+        check(block.parsed().unwrap(), pcapng_parser.section().endianness(), pcapng_parser.interface(0));
+        src = rem;
+    }
+    assert_eq!(pcapng_parser.section().endianness(), Endianness::Big);
+
+    // Index
+    let entries = index(&mut &data[..]).unwrap();
+    assert_eq!(entries.len(), 6);
+    assert_eq!(entries[3].block_type(), BlockType::SectionHeader);
+    assert_eq!(entries[5].block_type(), BlockType::EnhancedPacket);
+}
+
+#[test]
+fn current_endianness() {
+
+    let data = mixed_endianness_sections();
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    assert_eq!(pcapng_reader.current_endianness(), Endianness::Little);
+
+    let mut endiannesses = vec![];
+    while let Some(block) = pcapng_reader.next_block() {
+        assert_eq!(block.unwrap().endianness(), pcapng_reader.current_endianness());
+        endiannesses.push(pcapng_reader.current_endianness());
+    }
+    assert_eq!(endiannesses.first(), Some(&Endianness::Little));
+    assert_eq!(endiannesses.last(), Some(&Endianness::Big));
+
+    let (mut rem, mut pcapng_parser) = PcapNgParser::new(&data[..]).unwrap();
+    assert_eq!(pcapng_parser.current_endianness(), Endianness::Little);
+    while !rem.is_empty() {
+        let (next, block) = pcapng_parser.next_block(rem).unwrap();
+        assert_eq!(block.endianness(), pcapng_parser.current_endianness());
+        rem = next;
+    }
+    assert_eq!(pcapng_parser.current_endianness(), Endianness::Big);
+}
+
+#[test]
+fn reader_next_block_ref() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+        let entry = entry.unwrap();
+
+        let blocks: Vec<_> = PcapNgReader::new(File::open(&entry).unwrap()).unwrap()
+            .map(|block| block.unwrap())
+            .collect();
+
+        let mut pcapng_reader = PcapNgReader::new(File::open(&entry).unwrap()).unwrap();
+        let mut i = 0;
+        while let Some(block) = pcapng_reader.next_block_ref() {
+            let block = block.unwrap_or_else(|err| panic!("Error on block {} on file: {:?}: {}", i, entry, err));
+
+            assert_eq!(block.type_, blocks[i].type_);
+            assert_eq!(block.body, blocks[i].body);
+            block.parsed().unwrap();
+
+            i += 1;
+        }
+
+        assert_eq!(i, blocks.len());
+    }
+}
+
+#[test]
+fn reader_filtered_blocks() {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+
+    for (interface_id, len) in [(0, 1), (1, 2), (0, 3), (1, 4), (0, 5)] {
+        let packet = EnhancedPacketBlock {
+            interface_id,
+            timestamp: 0,
+            captured_len: len,
+            original_len: len,
+            data: Cow::Owned(vec![0; len as usize]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    let out = pcapng_writer.into_writer();
+    let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+
+    let lens: Vec<_> = pcapng_reader
+        .filtered_blocks(|block| match block.lazy_enhanced_packet() {
+            Some(Ok(packet)) => packet.interface_id == 0 && packet.original_len > 1,
+            _ => false
+        })
+        .map(|block| block.unwrap().lazy_enhanced_packet().unwrap().unwrap().data.len())
+        .collect();
+
+    assert_eq!(lens, [3, 5]);
+
+    // The skipped interfaces are still tracked
+    assert_eq!(pcapng_reader.interfaces().len(), 2);
+    assert!(pcapng_reader.next_block().is_none());
+}
+
+#[test]
+fn reader_direction() {
+
+    use pcap_file::pcapng::EnhancedPacketOption;
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+
+    // Inbound, outbound, no flags, direction not available
+    let flags = [Some(0b01), Some(0b10), None, Some(0b1100)];
+    for (len, flags) in flags.iter().enumerate() {
+        let len = len as u32 + 1;
+        let packet = EnhancedPacketBlock {
+            interface_id: 0,
+            timestamp: 0,
+            captured_len: len,
+            original_len: len,
+            data: Cow::Owned(vec![0; len as usize]),
+            options: flags.iter().map(|flags| EnhancedPacketOption::Flags(*flags)).collect()
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    let out = pcapng_writer.into_writer();
+    let lens = |inbound: bool, include_unknown: bool| -> Vec<usize> {
+        let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+        let blocks: Vec<_> = match inbound {
+            true => pcapng_reader.inbound_only(include_unknown).collect(),
+            false => pcapng_reader.outbound_only(include_unknown).collect()
+        };
+        blocks.into_iter().map(|block| block.unwrap().lazy_enhanced_packet().unwrap().unwrap().data.len()).collect()
+    };
+
+    assert_eq!(lens(true, false), [1]);
+    assert_eq!(lens(false, false), [2]);
+    assert_eq!(lens(true, true), [1, 3, 4]);
+    assert_eq!(lens(false, true), [2, 3, 4]);
+}
+
+#[test]
+fn reader_direction_packet_and_invalid_flags() {
+
+    use byteorder::{ByteOrder, NativeEndian};
+    use pcap_file::pcapng::{EnhancedPacketOption, PacketBlock, PacketOption, UnknownOption};
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().build()).unwrap();
+
+    // A Flags option whose value is too short to be decoded
+    let invalid_flags = UnknownOption::new(2, 2, &[1, 0]);
+
+    let epb = |original_len: u32, option: EnhancedPacketOption<'static>| ParsedBlock::EnhancedPacket(EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 4,
+        original_len,
+        data: Cow::Owned(vec![0; 4]),
+        options: vec![option]
+    });
+
+    let pb = |original_len: u32, option: PacketOption<'static>| ParsedBlock::Packet(PacketBlock {
+        interface_id: 0,
+        drop_count: 0,
+        timestamp: 0,
+        captured_len: 4,
+        original_len,
+        data: Cow::Owned(vec![0; 4]),
+        options: vec![option]
+    });
+
+    // Inbound EPB, EPB with invalid flags, outbound PacketBlock, PacketBlock with invalid flags, inbound PacketBlock
+    pcapng_writer.write_block(&epb(1, EnhancedPacketOption::Flags(0b01))).unwrap();
+    pcapng_writer.write_block(&epb(2, EnhancedPacketOption::Unknown(invalid_flags.clone()))).unwrap();
+    pcapng_writer.write_block(&pb(3, PacketOption::Flags(0b10))).unwrap();
+    pcapng_writer.write_block(&pb(4, PacketOption::Unknown(invalid_flags))).unwrap();
+    pcapng_writer.write_block(&pb(5, PacketOption::Flags(0b01))).unwrap();
+
+    let out = pcapng_writer.into_writer();
+    let lens = |inbound: bool, include_unknown: bool| -> Vec<u32> {
+        let mut pcapng_reader = PcapNgReader::new(&out[..]).unwrap();
+        let blocks: Vec<_> = match inbound {
+            true => pcapng_reader.inbound_only(include_unknown).collect(),
+            false => pcapng_reader.outbound_only(include_unknown).collect()
+        };
+        // The original length is at the same offset in the body of both blocks
+        blocks.into_iter().map(|block| NativeEndian::read_u32(&block.unwrap().body[16..20])).collect()
+    };
+
+    assert_eq!(lens(true, false), [1, 5]);
+    assert_eq!(lens(false, false), [3]);
+    assert_eq!(lens(true, true), [1, 2, 4, 5]);
+    assert_eq!(lens(false, true), [2, 3, 4]);
+}
@@ -0,0 +1,84 @@
+use byteorder::{ByteOrder, LittleEndian};
+use pcap_file::pcapng::{Ipv4Record, Ipv6Record, NameResolutionBlock, NameResolutionOption, Record};
+use std::borrow::Cow;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use super::common::opt;
+
+fn round_trip<B: ByteOrder>() {
+
+    let mut ipv6 = Ipv6Addr::LOCALHOST.octets().to_vec();
+    ipv6.extend_from_slice(b"localhost\0");
+
+    let mut data = Vec::new();
+    data.extend(opt::<B>(1, b"\x0A\x00\x00\x01host\0host.lan\0"));
+    data.extend(opt::<B>(2, &ipv6));
+    data.extend(opt::<B>(0x1234, b"unknown"));
+    data.extend(opt::<B>(0, &[]));
+    data.extend(opt::<B>(2, b"dns.lan"));
+    data.extend(opt::<B>(3, &[10, 0, 0, 53]));
+    data.extend(opt::<B>(0, &[]));
+
+    let (rem, nrb) = NameResolutionBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(nrb.records().len(), 3);
+
+    match &nrb.records()[0] {
+        Record::Ipv4(record) => {
+            assert_eq!(record.addr(), Some(Ipv4Addr::new(10, 0, 0, 1)));
+            assert_eq!(record.names, vec!["host", "host.lan"]);
+        },
+        record => panic!("Unexpected record: {:?}", record)
+    }
+
+    match &nrb.records()[1] {
+        Record::Ipv6(record) => {
+            assert_eq!(record.addr(), Some(Ipv6Addr::LOCALHOST));
+            assert_eq!(record.names, vec!["localhost"]);
+        },
+        record => panic!("Unexpected record: {:?}", record)
+    }
+
+    match &nrb.records()[2] {
+        Record::Unknown(record) => {
+            assert_eq!(record.type_, 0x1234);
+            assert_eq!(&record.value[..], b"unknown");
+        },
+        record => panic!("Unexpected record: {:?}", record)
+    }
+
+    match nrb.options() {
+        [NameResolutionOption::NsDnsName(name), NameResolutionOption::NsDnsIpv4Addr(addr)] => {
+            assert_eq!(name, "dns.lan");
+            assert_eq!(&addr[..], &[10, 0, 0, 53]);
+        },
+        options => panic!("Unexpected options: {:?}", options)
+    }
+
+    let mut out = Vec::new();
+    let len = nrb.to_owned().write_to::<_, B>(&mut out).unwrap();
+    assert_eq!(len, data.len());
+    assert_eq!(out, data);
+}
+
+endian_tests!(round_trip => round_trip_le, round_trip_be);
+
+#[test]
+fn missing_record_padding() {
+
+    // The record declares 7 bytes but its padding is missing
+    let mut data = opt::<LittleEndian>(1, b"\x0A\x00\x00\x01ab\0");
+    data.truncate(11);
+
+    assert!(NameResolutionBlock::from_slice::<LittleEndian>(&data[..]).is_err());
+    assert!(NameResolutionBlock::from_slice::<LittleEndian>(&data[..2]).is_err());
+}
+
+#[test]
+fn addr_wrong_length() {
+
+    let ipv4 = Ipv4Record { ip_addr: Cow::Borrowed(&[10, 0, 0]), names: vec![] };
+    assert_eq!(ipv4.addr(), None);
+
+    let ipv6 = Ipv6Record { ip_addr: Cow::Borrowed(&Ipv4Addr::LOCALHOST.octets()), names: vec![] };
+    assert_eq!(ipv6.addr(), None);
+}
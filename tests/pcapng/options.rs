@@ -0,0 +1,332 @@
+use byteorder::{ByteOrder, LittleEndian};
+use pcap_file::PcapError;
+use pcap_file::pcapng::{BlockType, CustomBinaryOption, CustomUtf8Option, EnhancedPacketBlock, EnhancedPacketOption, InterfaceDescriptionBlock, InterfaceDescriptionOption, InterfaceStatisticsBlock, InterfaceStatisticsOption, ParsedBlock, SectionHeaderBlock};
+use pcap_file::pcapng::options::{HasOptions, LengthMode};
+use std::borrow::Cow;
+
+#[test]
+fn overlong_option() {
+
+    // if_name declares 8 bytes but only 4 are left
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x02, 0x00, 0x08, 0x00, b'e', b't', b'h', b'0'
+    ];
+
+    match InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..]) {
+        Err(PcapError::IncompleteBuffer(4)) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn wrong_fixed_length() {
+
+    // isb_ifrecv with a 4 bytes value instead of 8
+    let data = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x04, 0x00, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    match InterfaceStatisticsBlock::from_slice::<LittleEndian>(&data[..]) {
+        Err(PcapError::InvalidOption { code: 4, .. }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // isb_ifrecv with a 12 bytes value: only rejected in strict mode
+    let data = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x04, 0x00, 0x0C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let (_, isb) = InterfaceStatisticsBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert_eq!(isb.options, [InterfaceStatisticsOption::IsbIfRecv(1)]);
+
+    let (_, isb) = InterfaceStatisticsBlock::from_slice_with_lengths::<LittleEndian>(&data[..], LengthMode::Lenient).unwrap();
+    assert_eq!(isb.options, [InterfaceStatisticsOption::IsbIfRecv(1)]);
+
+    match InterfaceStatisticsBlock::from_slice_with_lengths::<LittleEndian>(&data[..], LengthMode::Strict) {
+        Err(PcapError::InvalidOption { code: 4, reason }) => assert_eq!(reason, "InterfaceStatisticsOption: value length != 8"),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // A ParsedBlock is always lenient for the statistics
+    assert!(ParsedBlock::from_slice::<LittleEndian>(BlockType::InterfaceStatistics, &data[..]).is_ok());
+
+    // The fixed-size options of the other blocks are always strict: if_speed with a 12 bytes value
+    let data = [
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+        0x08, 0x00, 0x0C, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    match ParsedBlock::from_slice::<LittleEndian>(BlockType::InterfaceDescription, &data[..]) {
+        Err(PcapError::InvalidOption { code: 8, .. }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+fn custom_options<B: ByteOrder>() {
+
+    let options = vec![
+        InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::new(32473, "text", true)),
+        InterfaceDescriptionOption::CustomBinary(CustomBinaryOption::new(32473, &[1, 2, 3], true)),
+        InterfaceDescriptionOption::CustomUtf8(CustomUtf8Option::new(32473, "text", false)),
+        InterfaceDescriptionOption::CustomBinary(CustomBinaryOption::new(32473, &[1, 2, 3], false)),
+    ];
+
+    let mut data = vec![0_u8; 8];
+    for opt in &options {
+        opt.write_to::<_, B>(&mut data).unwrap();
+    }
+    data.extend_from_slice(&[0, 0, 0, 0]);
+
+    // The PEN follows the option header and uses the section byte order
+    assert_eq!(B::read_u16(&data[8..10]), 2988);
+    assert_eq!(B::read_u16(&data[10..12]), 8);
+    assert_eq!(B::read_u32(&data[12..16]), 32473);
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<B>(&data[..]).unwrap();
+    assert_eq!(idb.options().len(), 4);
+
+    for (i, opt) in idb.options().iter().enumerate() {
+        match opt {
+            InterfaceDescriptionOption::CustomUtf8(opt) => {
+                assert_eq!(opt.pen, 32473);
+                assert_eq!(opt.value, "text");
+                assert_eq!(opt.is_copyable(), i < 2);
+            },
+            InterfaceDescriptionOption::CustomBinary(opt) => {
+                assert_eq!(opt.pen, 32473);
+                assert_eq!(&opt.value[..], &[1, 2, 3]);
+                assert_eq!(opt.is_copyable(), i < 2);
+            },
+            opt => panic!("Unexpected option: {:?}", opt)
+        }
+    }
+}
+
+endian_tests!(custom_options => custom_options_le, custom_options_be);
+
+fn add_comments<'a, T: HasOptions<'a>>(block: &mut T) -> Vec<String> {
+
+    block.add_comment("one");
+    block.add_comment("two");
+
+    block.comments().map(String::from).collect()
+}
+
+#[test]
+fn comments() {
+
+    let mut section = SectionHeaderBlock::default();
+    assert_eq!(section.comments().count(), 0);
+    assert_eq!(add_comments(&mut section), ["one", "two"]);
+
+    let mut packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 0,
+        captured_len: 0,
+        original_len: 0,
+        data: Cow::Borrowed(&[]),
+        options: vec![EnhancedPacketOption::DropCount(1)]
+    };
+    assert_eq!(add_comments(&mut packet), ["one", "two"]);
+    assert_eq!(packet.options.len(), 3);
+
+    // The comments are written as regular options
+    let mut data = vec![];
+    packet.write_to::<_, LittleEndian>(&mut data).unwrap();
+    let (_, packet) = EnhancedPacketBlock::from_slice::<LittleEndian>(&data).unwrap();
+    assert_eq!(packet.comments().collect::<Vec<_>>(), ["one", "two"]);
+
+    let mut stats = InterfaceStatisticsBlock { interface_id: 0, timestamp: 0, options: vec![] };
+    assert_eq!(add_comments(&mut stats), ["one", "two"]);
+}
+
+#[test]
+fn unknown_options_roundtrip() {
+
+    use pcap_file::pcapng::{ParsedBlock, PcapNgReader, PcapNgWriter, SectionHeaderOption};
+
+    fn block(type_: u32, body: &[u8]) -> Vec<u8> {
+        let len = (12 + body.len()) as u32;
+        let mut data = vec![];
+        data.extend_from_slice(&type_.to_be_bytes());
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(body);
+        data.extend_from_slice(&len.to_be_bytes());
+        data
+    }
+
+    // Options with codes unknown to the parser, whose lengths are not a multiple of 4
+    let shb = [
+        0x1A, 0x2B, 0x3C, 0x4D, 0x00, 0x01, 0x00, 0x00,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x7F, 0xF0, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+    let idb = [
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x7F, 0xF1, 0x00, 0x01, 0x09, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+    let epb = [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x04, 0xDE, 0xAD, 0xBE, 0xEF,
+        0x7F, 0xF2, 0x00, 0x06, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let mut data = block(0x0A0D0D0A, &shb);
+    data.extend(block(1, &idb));
+    data.extend(block(6, &epb));
+
+    let mut pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+    match &pcapng_reader.section().options[0] {
+        SectionHeaderOption::Unknown(opt) => {
+            assert_eq!(opt.code, 0x7FF0);
+            assert_eq!(opt.length, 5);
+            assert_eq!(&opt.value[..], &[1, 2, 3, 4, 5]);
+        },
+        opt => panic!("Unexpected option: {:?}", opt)
+    }
+
+    let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), vec![]).unwrap();
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block.unwrap();
+        let parsed = block.parsed().unwrap();
+        if let ParsedBlock::EnhancedPacket(packet) = &parsed {
+            assert!(matches!(&packet.options[0], EnhancedPacketOption::Unknown(opt) if opt.length == 6));
+        }
+        pcapng_writer.write_block(&parsed).unwrap();
+    }
+
+    assert_eq!(pcapng_writer.into_writer(), data);
+}
+
+#[test]
+fn option_overruns_block() {
+
+    use pcap_file::pcapng::{Block, PcapNgParser};
+
+    // The if_name of the InterfaceDescriptionBlock declares 12 bytes: its body ends after 4 of them,
+    // the 8 others would be the header of the following SimplePacketBlock
+    let data = [
+        0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00, 0x4D, 0x3C, 0x2B, 0x1A,
+        0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x1C, 0x00, 0x00, 0x00,
+
+        0x01, 0x00, 0x00, 0x00, 0x1C, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0xFF, 0xFF, 0x00, 0x00, 0x02, 0x00, 0x0C, 0x00, b'e', b't', b'h', b'0',
+        0x1C, 0x00, 0x00, 0x00,
+
+        0x03, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x10, 0x00, 0x00, 0x00
+    ];
+
+    let (rem, _) = Block::from_slice::<LittleEndian>(&data[..]).unwrap();
+    let (_, block) = Block::from_slice::<LittleEndian>(rem).unwrap();
+    match block.parsed() {
+        Err(PcapError::InvalidField(_)) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+
+    // The parser must not ask for more data, the block is complete
+    let (rem, mut pcapng_parser) = PcapNgParser::new(&data[..]).unwrap();
+    match pcapng_parser.next_block(rem) {
+        Err(PcapError::InvalidField(_)) => {},
+        res => panic!("Unexpected result: {:?}", res.map(|(_, block)| block))
+    }
+}
+
+#[test]
+fn end_of_options() {
+
+    use pcap_file::pcapng::options::opts_from_slice;
+
+    fn parse(data: &[u8]) -> Vec<(u16, Vec<u8>)> {
+        let (rem, opts) = opts_from_slice::<LittleEndian, _, _>(data, |value, code, _| Ok((code, value.to_vec()))).unwrap();
+        assert!(rem.is_empty());
+        opts
+    }
+
+    let if_name = [0x02, 0x00, 0x04, 0x00, b'e', b't', b'h', b'0'];
+    let end = [0x00, 0x00, 0x00, 0x00];
+
+    // No option at all
+    assert!(parse(&[]).is_empty());
+    assert!(parse(&end).is_empty());
+
+    // Explicit end of options marker, then omitted
+    let mut data = if_name.to_vec();
+    data.extend_from_slice(&end);
+    assert_eq!(parse(&data), vec![(2, b"eth0".to_vec())]);
+    assert_eq!(parse(&if_name), vec![(2, b"eth0".to_vec())]);
+
+    // A zero-length option which is not the end of options marker
+    let mut data = vec![0x00, 0x80, 0x00, 0x00];
+    data.extend_from_slice(&if_name);
+    assert_eq!(parse(&data), vec![(0x8000, vec![]), (2, b"eth0".to_vec())]);
+
+    // The same through a block whose body ends without the marker
+    let mut data = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    data.extend_from_slice(&if_name);
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data).unwrap();
+    assert_eq!(idb.if_name(), Some("eth0"));
+
+    let (_, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&data[..8]).unwrap();
+    assert!(idb.options().is_empty());
+}
+
+#[test]
+fn option_padding() {
+
+    use pcap_file::pcapng::options::opts_from_slice;
+
+    // A 3 bytes opt_comment padded to 4, then an if_name with a 1 byte value padded to 4
+    let data = [
+        0x01, 0x00, 0x03, 0x00, b'a', b'b', b'c', 0x00,
+        0x02, 0x00, 0x01, 0x00, b'x', 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00
+    ];
+
+    let mut block = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    block.extend_from_slice(&data);
+    let (rem, idb) = InterfaceDescriptionBlock::from_slice::<LittleEndian>(&block).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(idb.options.len(), 2);
+    assert!(matches!(&idb.options[0], InterfaceDescriptionOption::Comment(comment) if comment == "abc"));
+    assert_eq!(idb.if_name(), Some("x"));
+
+    // The padding of the last option can be omitted, totally or partially
+    for len in 13..=16 {
+        let (rem, opts) = opts_from_slice::<LittleEndian, _, _>(&data[..len], |value, code, _| Ok((code, value))).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(opts, vec![(1, &b"abc"[..]), (2, &b"x"[..])]);
+    }
+
+    // But not the value itself
+    match opts_from_slice::<LittleEndian, _, _>(&data[..12], |value, code, _| Ok((code, value))) {
+        Err(PcapError::IncompleteBuffer(4)) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
+
+#[test]
+fn option_codes() {
+
+    use pcap_file::pcapng::options::*;
+
+    assert_eq!((OPT_ENDOFOPT, OPT_COMMENT), (0, 1));
+    assert_eq!((OPT_CUSTOM_UTF8_COPYABLE, OPT_CUSTOM_BINARY_COPYABLE), (2988, 2989));
+    assert_eq!((OPT_CUSTOM_UTF8_NON_COPYABLE, OPT_CUSTOM_BINARY_NON_COPYABLE), (19372, 19373));
+
+    let custom: Vec<u16> = (0..=u16::MAX).filter(|code| is_custom_option(*code)).collect();
+    assert_eq!(custom, [2988, 2989, 19372, 19373]);
+
+    assert_eq!(CustomUtf8Option::new(1, "a", true).code, OPT_CUSTOM_UTF8_COPYABLE);
+    assert_eq!(CustomBinaryOption::new(1, &[], false).code, OPT_CUSTOM_BINARY_NON_COPYABLE);
+}
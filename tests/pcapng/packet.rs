@@ -0,0 +1,65 @@
+use byteorder::{ByteOrder, LittleEndian};
+use pcap_file::PcapError;
+use pcap_file::pcapng::{PacketBlock, PacketOption, ParsedBlock, PcapNgReader, PcapNgWriter};
+
+/// A little endian file written by a tool still using the obsolete Packet Block:
+/// a SectionHeaderBlock, an Ethernet interface and a 5 bytes packet with a pack_flags option
+const CAPTURE: [u8; 100] = [
+    0x0A, 0x0D, 0x0D, 0x0A, 0x1C, 0x00, 0x00, 0x00, 0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x1C, 0x00, 0x00, 0x00,
+
+    0x01, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+    0x14, 0x00, 0x00, 0x00,
+
+    0x02, 0x00, 0x00, 0x00, 0x34, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x03, 0x00,
+    0x04, 0x00, 0x00, 0x00, 0x40, 0xE2, 0x01, 0x00,
+    0x05, 0x00, 0x00, 0x00, 0x3C, 0x00, 0x00, 0x00,
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x00, 0x00, 0x00,
+    0x02, 0x00, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    0x34, 0x00, 0x00, 0x00
+];
+
+#[test]
+fn legacy_capture() {
+
+    let mut pcapng_reader = PcapNgReader::new(&CAPTURE[..]).unwrap();
+    pcapng_reader.next_block().unwrap().unwrap();
+
+    let block = pcapng_reader.next_block().unwrap().unwrap();
+    let packet = match block.parsed().unwrap() {
+        ParsedBlock::Packet(packet) => packet,
+        parsed => panic!("Unexpected block: {:?}", parsed)
+    };
+
+    assert_eq!(packet.interface_id, 0);
+    assert_eq!(packet.drop_count, 3);
+    assert_eq!(packet.timestamp, (4 << 32) + 123_456);
+    assert_eq!(packet.captured_len, 5);
+    assert_eq!(packet.original_len, 60);
+    assert_eq!(&packet.data[..], [1, 2, 3, 4, 5]);
+    assert!(matches!(packet.options[..], [PacketOption::Flags(1)]));
+    assert!(pcapng_reader.next_block().is_none());
+
+    // It is written back as is
+    let mut pcapng_reader = PcapNgReader::new(&CAPTURE[..]).unwrap();
+    let mut pcapng_writer = PcapNgWriter::with_section_header(pcapng_reader.section().clone(), vec![]).unwrap();
+    while let Some(block) = pcapng_reader.next_block() {
+        pcapng_writer.write_block(&block.unwrap().parsed().unwrap()).unwrap();
+    }
+    assert_eq!(pcapng_writer.into_writer(), CAPTURE);
+}
+
+#[test]
+fn captured_len_overrun() {
+
+    // Body of the packet of the capture whose captured_len overruns the block
+    let mut data = CAPTURE[56..96].to_vec();
+    LittleEndian::write_u32(&mut data[12..16], 100);
+
+    match PacketBlock::from_slice::<LittleEndian>(&data[..]) {
+        Err(PcapError::InvalidField(msg)) => assert_eq!(msg, "PacketBlock: captured_len > block length"),
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
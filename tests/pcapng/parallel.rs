@@ -0,0 +1,37 @@
+use pcap_file::pcapng::{index, par_blocks, section_interfaces, BlockType, PcapNgReader};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::Read;
+use glob::glob;
+
+#[test]
+fn par_blocks_matches_reader() {
+
+    for entry in glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+        let entry = entry.unwrap();
+
+        let mut data = Vec::new();
+        File::open(&entry).unwrap().read_to_end(&mut data).unwrap();
+
+        let entries = index(&mut &data[..]).unwrap();
+        let blocks: Vec<_> = par_blocks(&data, &entries).collect();
+        assert_eq!(blocks.len(), entries.len());
+
+        // The reader consumes the first SectionHeaderBlock
+        let pcapng_reader = PcapNgReader::new(&data[..]).unwrap();
+        assert_eq!(blocks[0].as_ref().unwrap().type_, BlockType::SectionHeader);
+
+        for (block, par_block) in pcapng_reader.zip(&blocks[1..]) {
+            let block = block.unwrap();
+            let par_block = par_block.as_ref().unwrap_or_else(|err| panic!("Error on file {:?}: {}", entry, err));
+
+            assert_eq!(format!("{:?}", par_block.parsed().unwrap()), format!("{:?}", block.parsed().unwrap()));
+        }
+
+        let sections = section_interfaces(&data, &entries).unwrap();
+        let nb_sections = entries.iter().filter(|e| e.block_type() == BlockType::SectionHeader).count();
+        let nb_interfaces = entries.iter().filter(|e| e.block_type() == BlockType::InterfaceDescription).count();
+        assert_eq!(sections.len(), nb_sections);
+        assert_eq!(sections.iter().map(Vec::len).sum::<usize>(), nb_interfaces);
+    }
+}
@@ -0,0 +1,75 @@
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use pcap_file::Endianness;
+use pcap_file::pcapng::SectionHeaderBlock;
+use super::common::{body, opt};
+
+fn block<B: ByteOrder>(section_length: i64) -> Vec<u8> {
+
+    let mut data = vec![0_u8; 16];
+    B::write_u32(&mut data[0..4], 0x1A2B3C4D);
+    B::write_u16(&mut data[4..6], 1);
+    B::write_u16(&mut data[6..8], 0);
+    B::write_i64(&mut data[8..16], section_length);
+
+    body::<B>(&data, &[
+        opt::<B>(2, b"x86_64"),
+        opt::<B>(3, b"Linux"),
+        opt::<B>(4, b"pcap-file")
+    ])
+}
+
+fn parse<B: ByteOrder>() {
+
+    let data = block::<B>(1024);
+
+    let (rem, shb) = SectionHeaderBlock::from_slice(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(shb.endianness(), Endianness::new::<B>());
+    assert_eq!(shb.major_version(), 1);
+    assert_eq!(shb.minor_version(), 0);
+    assert_eq!(shb.section_length(), Some(1024));
+    assert_eq!(shb.hardware(), Some("x86_64"));
+    assert_eq!(shb.os(), Some("Linux"));
+    assert_eq!(shb.user_application(), Some("pcap-file"));
+
+    let mut out = Vec::new();
+    let len = shb.to_owned().write_to::<_, B>(&mut out).unwrap();
+    assert_eq!(len, data.len());
+    assert_eq!(out, data);
+
+    let data = block::<B>(-1);
+    let (_, shb) = SectionHeaderBlock::from_slice(&data[..]).unwrap();
+    assert_eq!(shb.section_length(), None);
+}
+
+endian_tests!(parse => parse_le, parse_be);
+
+#[test]
+fn invalid_magic() {
+
+    let mut data = block::<BigEndian>(-1);
+    data[0] = 0xFF;
+
+    assert!(SectionHeaderBlock::from_slice(&data[..]).is_err());
+}
+
+#[test]
+fn version() {
+
+    use pcap_file::PcapError;
+
+    // A new minor version is parsed as a 1.x
+    let mut data = block::<LittleEndian>(-1);
+    LittleEndian::write_u16(&mut data[6..8], 5);
+    let (_, shb) = SectionHeaderBlock::from_slice(&data[..]).unwrap();
+    assert_eq!((shb.major_version(), shb.minor_version()), (1, 5));
+    assert_eq!(shb.os(), Some("Linux"));
+
+    // But not a new major version
+    LittleEndian::write_u16(&mut data[4..6], 2);
+    LittleEndian::write_u16(&mut data[6..8], 0);
+    match SectionHeaderBlock::from_slice(&data[..]) {
+        Err(PcapError::UnsupportedVersion { major: 2, minor: 0 }) => {},
+        res => panic!("Unexpected result: {:?}", res)
+    }
+}
@@ -0,0 +1,34 @@
+use byteorder::ByteOrder;
+use pcap_file::pcapng::SimplePacketBlock;
+
+fn captured_len<B: ByteOrder>() {
+
+    // 5 bytes packet followed by 3 bytes of padding
+    let mut data = vec![0_u8; 4];
+    B::write_u32(&mut data[..4], 5);
+    data.extend_from_slice(&[1, 2, 3, 4, 5, 0, 0, 0]);
+
+    let (rem, spb) = SimplePacketBlock::from_slice::<B>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(spb.original_len(), 5);
+    assert_eq!(spb.data(), &[1, 2, 3, 4, 5]);
+    assert!(!spb.is_truncated());
+
+    let mut out = Vec::new();
+    assert_eq!(spb.to_owned().write_to::<_, B>(&mut out).unwrap(), data.len());
+    assert_eq!(out, data);
+
+    // 100 bytes packet truncated to 8 bytes
+    B::write_u32(&mut data[..4], 100);
+
+    let (_, spb) = SimplePacketBlock::from_slice::<B>(&data[..]).unwrap();
+    assert_eq!(spb.original_len(), 100);
+    assert_eq!(spb.data(), &[1, 2, 3, 4, 5, 0, 0, 0]);
+    assert!(spb.is_truncated());
+
+    // Empty packet
+    let (_, spb) = SimplePacketBlock::from_slice::<B>(&[0, 0, 0, 0][..]).unwrap();
+    assert!(spb.data().is_empty());
+}
+
+endian_tests!(captured_len => captured_len_le, captured_len_be);
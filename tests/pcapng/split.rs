@@ -0,0 +1,157 @@
+use pcap_file::DataLink;
+use pcap_file::pcapng::{split_pcapng, BlockType, EnhancedPacketBlock, InterfaceDescriptionBlock, ParsedBlock, PcapNgReader, PcapNgWriter, SplitLimit};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+/// Two interfaces followed by 5 packets of 8 bytes alternating between them
+fn capture() -> Vec<u8> {
+
+    let mut pcapng_writer = PcapNgWriter::new(Vec::new()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(DataLink::ETHERNET).build()).unwrap();
+    pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(DataLink::RAW).build()).unwrap();
+
+    for i in 0..5_u8 {
+        let packet = EnhancedPacketBlock {
+            interface_id: i as u32 % 2,
+            timestamp: i as u64,
+            captured_len: 8,
+            original_len: 8,
+            data: Cow::Owned(vec![i; 8]),
+            options: vec![]
+        };
+        pcapng_writer.write_enhanced_packet(&packet).unwrap();
+    }
+
+    pcapng_writer.into_writer()
+}
+
+/// Reads a fragment on its own and returns the timestamps of its packets
+fn timestamps(fragment: &[u8]) -> Vec<u64> {
+
+    let mut pcapng_reader = PcapNgReader::new(fragment).unwrap();
+    let mut timestamps = vec![];
+
+    while let Some(block) = pcapng_reader.next_block() {
+        let block = block.unwrap();
+
+        if let ParsedBlock::EnhancedPacket(packet) = block.parsed().unwrap() {
+            let interface = pcapng_reader.packet_interface(&packet).unwrap();
+            let linktype = if packet.timestamp % 2 == 0 { DataLink::ETHERNET } else { DataLink::RAW };
+            assert_eq!(interface.linktype(), linktype);
+            assert_eq!(&packet.data[..], &[packet.timestamp as u8; 8]);
+
+            timestamps.push(packet.timestamp);
+        }
+    }
+
+    assert_eq!(pcapng_reader.interfaces().len(), 2);
+    timestamps
+}
+
+/// Writer of a fragment whose content stays available to the test
+struct Sink(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn split(limit: SplitLimit) -> Vec<Vec<u8>> {
+
+    let data = capture();
+    let mut fragments: Vec<Rc<RefCell<Vec<u8>>>> = vec![];
+
+    let nb_fragments = split_pcapng(&data[..], limit, |i| {
+        assert_eq!(i, fragments.len());
+        fragments.push(Rc::default());
+        Ok(Sink(fragments[i].clone()))
+    }).unwrap();
+
+    assert_eq!(nb_fragments, fragments.len());
+    fragments.iter().map(|fragment| fragment.borrow().clone()).collect()
+}
+
+#[test]
+fn split_packets() {
+
+    let fragments = split(SplitLimit::Packets(2));
+    let timestamps: Vec<_> = fragments.iter().map(|fragment| timestamps(fragment)).collect();
+
+    assert_eq!(timestamps, [vec![0, 1], vec![2, 3], vec![4]]);
+}
+
+#[test]
+fn split_bytes() {
+
+    // SHB (28) + 2 IDB (20) + 3 EPB (40) = 188 bytes
+    let fragments = split(SplitLimit::Bytes(188));
+    let timestamps: Vec<_> = fragments.iter().map(|fragment| timestamps(fragment)).collect();
+
+    assert_eq!(timestamps, [vec![0, 1, 2], vec![3, 4]]);
+    assert_eq!(fragments[0].len(), 188);
+
+    // A packet is always written, even if it doesn't fit
+    let fragments = split(SplitLimit::Bytes(1));
+    assert_eq!(fragments.len(), 5);
+
+    let mut pcapng_reader = PcapNgReader::new(&fragments[4][..]).unwrap();
+    let types: Vec<_> = pcapng_reader.by_ref().map(|block| block.unwrap().type_).collect();
+    assert_eq!(types, [BlockType::InterfaceDescription, BlockType::InterfaceDescription, BlockType::EnhancedPacket]);
+}
+
+#[test]
+fn rotating_writer() {
+
+    use pcap_file::pcapng::RotatingPcapNgWriter;
+
+    let mut files: Vec<Rc<RefCell<Vec<u8>>>> = vec![];
+
+    {
+        let data = capture();
+        let mut section = PcapNgReader::new(&data[..]).unwrap().section().clone();
+        section.section_length = 1000;
+
+        let mut pcapng_writer = RotatingPcapNgWriter::with_section_header(section, SplitLimit::Packets(2), |i| {
+            assert_eq!(i, files.len());
+            files.push(Rc::default());
+            Ok(Sink(files[i].clone()))
+        }).unwrap();
+
+        pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(DataLink::ETHERNET).build()).unwrap();
+        pcapng_writer.write_interface(&InterfaceDescriptionBlock::builder().linktype(DataLink::RAW).build()).unwrap();
+
+        for i in 0..5_u8 {
+            let packet = EnhancedPacketBlock {
+                interface_id: i as u32 % 2,
+                timestamp: i as u64,
+                captured_len: 8,
+                original_len: 8,
+                data: Cow::Owned(vec![i; 8]),
+                options: vec![]
+            };
+            pcapng_writer.write_enhanced_packet(&packet).unwrap();
+            assert_eq!(pcapng_writer.file_len(), pcapng_writer.get_ref().0.borrow().len() as u64);
+        }
+
+        assert_eq!(pcapng_writer.nb_files(), 3);
+    }
+
+    let files: Vec<_> = files.iter().map(|file| file.borrow().clone()).collect();
+    let timestamps: Vec<_> = files.iter().map(|file| timestamps(file)).collect();
+    assert_eq!(timestamps, [vec![0, 1], vec![2, 3], vec![4]]);
+
+    for file in &files {
+        let pcapng_reader = PcapNgReader::new(&file[..]).unwrap();
+        assert_eq!(pcapng_reader.section().section_length, -1);
+    }
+
+    // The files are the same as the fragments of the split of the stream
+    assert_eq!(files, split(SplitLimit::Packets(2)));
+}
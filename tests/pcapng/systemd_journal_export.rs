@@ -0,0 +1,30 @@
+use byteorder::LittleEndian;
+use pcap_file::pcapng::SystemdJournalExportBlock;
+
+#[test]
+fn entries() {
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"__CURSOR=s=1\n__REALTIME_TIMESTAMP=1\nMESSAGE=first\n\n");
+    data.extend_from_slice(b"__CURSOR=s=2\nMESSAGE\n");
+    data.extend_from_slice(&7_u64.to_le_bytes());
+    data.extend_from_slice(b"a\n\nb\n\nc\n");
+    data.extend_from_slice(b"_PID=42\n\n");
+
+    let entry_len = data.len();
+    data.extend_from_slice(&[0_u8; 3][..(4 - entry_len % 4) % 4]);
+
+    let (rem, block) = SystemdJournalExportBlock::from_slice::<LittleEndian>(&data[..]).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(block.journal_entry.len(), entry_len);
+
+    let entries: Vec<_> = block.entries().collect();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], &b"__CURSOR=s=1\n__REALTIME_TIMESTAMP=1\nMESSAGE=first\n"[..]);
+    assert!(entries[1].starts_with(b"__CURSOR=s=2\nMESSAGE\n"));
+    assert!(entries[1].ends_with(b"a\n\nb\n\nc\n_PID=42\n"));
+
+    let mut out = Vec::new();
+    assert_eq!(block.to_owned().write_to::<_, LittleEndian>(&mut out).unwrap(), data.len());
+    assert_eq!(out, data);
+}
@@ -0,0 +1,52 @@
+use byteorder::{BigEndian, LittleEndian};
+use pcap_file::peek_reader::PeekReader;
+use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
+
+#[test]
+fn peek_and_consume() {
+
+    let data = [1, 2, 3, 4, 5, 6];
+    let mut reader = PeekReader::new(&data[..]);
+
+    assert_eq!(reader.peek_u32::<BigEndian>().unwrap(), 0x01020304);
+    assert_eq!(reader.peek_u32::<LittleEndian>().unwrap(), 0x04030201);
+
+    // Peeking more than available returns what is left
+    assert_eq!(reader.peek(8).unwrap(), &data[..]);
+    assert_eq!(reader.position(), 0);
+
+    assert_eq!(reader.fill_buf().unwrap(), &data[..]);
+    reader.consume(3);
+    assert_eq!(reader.fill_buf().unwrap(), &data[3..]);
+    assert_eq!(reader.position(), 3);
+
+    assert!(reader.peek_u32::<BigEndian>().is_err());
+
+    let mut buf = [0_u8; 2];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [4, 5]);
+    assert_eq!(reader.position(), 5);
+
+    assert!(!reader.is_empty().unwrap());
+    reader.consume(1);
+    assert!(reader.is_empty().unwrap());
+    assert!(reader.fill_buf().unwrap().is_empty());
+    assert_eq!(reader.position(), 6);
+}
+
+#[test]
+fn seek_after_peek() {
+
+    let mut reader = PeekReader::new(Cursor::new(vec![1, 2, 3, 4, 5, 6]));
+
+    reader.peek(4).unwrap();
+    reader.consume(1);
+
+    // The peeked bytes are given back to the underlying reader
+    assert_eq!(reader.seek(SeekFrom::Current(1)).unwrap(), 2);
+
+    let mut rem = vec![];
+    reader.read_to_end(&mut rem).unwrap();
+    assert_eq!(rem, [3, 4, 5, 6]);
+    assert_eq!(reader.position(), 6);
+}
@@ -0,0 +1,48 @@
+use pcap_file::DataLink;
+use pcap_file::pcapng::{EnhancedPacketBlock, EnhancedPacketOption, InterfaceDescriptionBlock, InterfaceDescriptionOption, ParsedBlock};
+use serde_json::json;
+use std::borrow::Cow;
+
+#[test]
+fn datalink() {
+
+    assert_eq!(serde_json::to_value(DataLink::ETHERNET).unwrap(), json!("ETHERNET"));
+    assert_eq!(serde_json::to_value(DataLink::Unknown(1234)).unwrap(), json!("Unknown(1234)"));
+
+    assert_eq!(serde_json::from_value::<DataLink>(json!("linux_sll")).unwrap(), DataLink::LINUX_SLL);
+    assert_eq!(serde_json::from_value::<DataLink>(json!("Unknown(1234)")).unwrap(), DataLink::Unknown(1234));
+    assert!(serde_json::from_value::<DataLink>(json!("NOT_A_LINKTYPE")).is_err());
+}
+
+#[test]
+fn interface_description() {
+
+    let interface = InterfaceDescriptionBlock::builder()
+        .snaplen(1500)
+        .name("eth0")
+        .option(InterfaceDescriptionOption::IfMacAddr(Cow::Borrowed(&[0x00, 0x11, 0x22, 0xaa, 0xbb, 0xcc])))
+        .build();
+
+    let value = serde_json::to_value(&interface).unwrap();
+    assert_eq!(value["linktype"], json!("ETHERNET"));
+    assert_eq!(value["snaplen"], json!(1500));
+    assert_eq!(value["options"][0], json!({ "IfName": "eth0" }));
+    assert_eq!(value["options"][1], json!({ "IfMacAddr": "001122aabbcc" }));
+}
+
+#[test]
+fn enhanced_packet() {
+
+    let packet = EnhancedPacketBlock {
+        interface_id: 0,
+        timestamp: 42,
+        captured_len: 3,
+        original_len: 3,
+        data: Cow::Borrowed(&[0xde, 0xad, 0x01]),
+        options: vec![EnhancedPacketOption::Comment(Cow::Borrowed("comment"))]
+    };
+
+    let value = serde_json::to_value(ParsedBlock::EnhancedPacket(packet)).unwrap();
+    assert_eq!(value["EnhancedPacket"]["data"], json!("dead01"));
+    assert_eq!(value["EnhancedPacket"]["options"][0], json!({ "Comment": "comment" }));
+}
@@ -0,0 +1,80 @@
+use pcap_file::capture_stats;
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::{BlockType, PcapNgReader};
+use std::time::Duration;
+
+#[test]
+fn pcap() {
+
+    let data = std::fs::read("tests/pcap/little_endian.pcap").unwrap();
+    let stats = capture_stats(&data[..]).unwrap();
+
+    let packets: Vec<_> = PcapReader::new(&data[..]).unwrap().map(|packet| packet.unwrap()).collect();
+
+    assert!(stats.block_counts.is_empty());
+    assert_eq!(stats.nb_packets, packets.len() as u64);
+    assert_eq!(stats.captured_bytes, packets.iter().map(|packet| packet.data.len() as u64).sum::<u64>());
+    assert_eq!(stats.interface_packets, vec![packets.len() as u64]);
+    assert_eq!(stats.first_timestamp, packets.iter().map(|packet| packet.header.timestamp()).min());
+    assert_eq!(stats.last_timestamp, packets.iter().map(|packet| packet.header.timestamp()).max());
+}
+
+#[test]
+fn pcapng() {
+
+    for entry in glob::glob("tests/pcapng/**/**/*.pcapng").expect("Failed to read glob pattern") {
+
+        let data = std::fs::read(entry.unwrap()).unwrap();
+        let stats = capture_stats(&data[..]).unwrap();
+
+        let nb_blocks = 1 + PcapNgReader::new(&data[..]).unwrap().count() as u64;
+        assert_eq!(stats.block_counts.values().sum::<u64>(), nb_blocks);
+        assert_eq!(stats.interface_packets.iter().sum::<u64>(), stats.nb_packets);
+
+        let nb_packets = [BlockType::EnhancedPacket, BlockType::SimplePacket, BlockType::Packet].iter()
+            .map(|type_| stats.block_counts.get(type_).copied().unwrap_or(0))
+            .sum::<u64>();
+        assert_eq!(stats.nb_packets, nb_packets);
+        assert!(stats.first_timestamp <= stats.last_timestamp);
+    }
+}
+
+#[test]
+fn pcapng_interfaces() {
+
+    // Two packets on the second interface at 1s and 3s, one on the first at 2s
+    let data = [
+        // SectionHeaderBlock
+        0x0A, 0x0D, 0x0D, 0x0A, 0x00, 0x00, 0x00, 0x1C, 0x1A, 0x2B, 0x3C, 0x4D,
+        0x00, 0x01, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        0x00, 0x00, 0x00, 0x1C,
+        // InterfaceDescriptionBlocks with a second resolution
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+        // EnhancedPacketBlocks with 4 bytes of data
+        0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04,
+        0x00, 0x00, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x24,
+        0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x04,
+        0x00, 0x00, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x24,
+        0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04,
+        0x00, 0x00, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x24
+    ];
+
+    let stats = capture_stats(&data[..]).unwrap();
+
+    assert_eq!(stats.block_counts.get(&BlockType::SectionHeader), Some(&1));
+    assert_eq!(stats.block_counts.get(&BlockType::InterfaceDescription), Some(&2));
+    assert_eq!(stats.block_counts.get(&BlockType::EnhancedPacket), Some(&3));
+    assert_eq!(stats.nb_packets, 3);
+    assert_eq!(stats.captured_bytes, 12);
+    assert_eq!(stats.interface_packets, vec![1, 2]);
+    assert_eq!(stats.first_timestamp, Some(Duration::from_secs(1)));
+    assert_eq!(stats.last_timestamp, Some(Duration::from_secs(3)));
+}
@@ -1,4 +1,15 @@
 #![allow(clippy::unreadable_literal)]
 
+mod datalink;
+mod errors;
+#[cfg(feature = "flate2")]
+mod gzip;
+#[cfg(feature = "memmap2")]
+mod mmap;
+mod packet_source;
 mod pcap;
-mod pcapng;
\ No newline at end of file
+mod pcapng;
+mod peek_reader;
+#[cfg(feature = "serde")]
+mod serialize;
+mod stats;
\ No newline at end of file